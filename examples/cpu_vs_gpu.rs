@@ -0,0 +1,71 @@
+//! Evaluates the same probability field on the CPU (`QuantumSlamCore::evaluate_grid`)
+//! and on the GPU (`HeadlessRenderer::evaluate_grid`) at several resolutions, printing
+//! the timings and the largest per-cell difference.
+//!
+//! ```sh
+//! cargo run --release --example cpu_vs_gpu
+//! ```
+
+use std::time::Instant;
+
+use inverse_observation_induced_probability_field_interference::{HeadlessRenderer, QuantumSlamCore};
+
+const RESOLUTIONS: [usize; 5] = [64, 128, 256, 512, 1024];
+const BOUNDS: (f32, f32, f32, f32) = (-5.0, 5.0, -5.0, 5.0);
+
+// 両者が一致しているとみなす最大誤差 (f32 の sin/cos の実装差を許容)
+const TOLERANCE: f64 = 1e-3;
+
+fn main() {
+    let mut core = QuantumSlamCore::new(10.0).unwrap();
+    for (x, y) in [(0.0, 4.0), (-4.0, -3.0), (4.0, -3.0), (2.5, 2.5), (-3.0, 1.0)] {
+        core.add_landmark(x, y).unwrap();
+    }
+    core.observe(0.7, -0.4).unwrap();
+
+    let mut renderer = match HeadlessRenderer::new(1, 1) {
+        Ok(renderer) => renderer,
+        Err(e) => {
+            eprintln!("No GPU available: {e}");
+            return;
+        }
+    };
+
+    // 初回はパイプライン生成を含むので計測から外す
+    renderer.evaluate_grid(&core, BOUNDS, 16, 16).expect("warm-up evaluation");
+
+    println!("{:>10} {:>12} {:>12} {:>9} {:>12}", "grid", "cpu [ms]", "gpu [ms]", "speedup", "max |diff|");
+
+    let mut all_agree = true;
+    for n in RESOLUTIONS {
+        let start = Instant::now();
+        let cpu = core.evaluate_grid(BOUNDS, n, n);
+        let cpu_ms = start.elapsed().as_secs_f64() * 1e3;
+
+        let start = Instant::now();
+        let gpu = match renderer.evaluate_grid(&core, BOUNDS, n, n) {
+            Ok(field) => field,
+            Err(e) => {
+                println!("{:>10} skipped: {e}", format!("{n}x{n}"));
+                continue;
+            }
+        };
+        let gpu_ms = start.elapsed().as_secs_f64() * 1e3;
+
+        let max_diff = cpu.values.iter()
+            .zip(&gpu.values)
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0, f64::max);
+        all_agree &= max_diff <= TOLERANCE;
+
+        println!(
+            "{:>10} {:>12.2} {:>12.2} {:>8.1}x {:>12.2e}",
+            format!("{n}x{n}"), cpu_ms, gpu_ms, cpu_ms / gpu_ms, max_diff
+        );
+    }
+
+    if !all_agree {
+        eprintln!("CPU and GPU fields differ by more than {TOLERANCE:e}");
+        std::process::exit(1);
+    }
+}
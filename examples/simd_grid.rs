@@ -0,0 +1,48 @@
+//! Times the range field on a 256x256 grid for growing landmark counts, once with the
+//! scalar per-point loop (`probability_at` per cell) and once with the f32x8 landmark
+//! lanes used by `evaluate_grid`, printing the speedup and the largest per-cell
+//! difference relative to the field's peak. Without the `parallel` feature both run
+//! on one thread.
+//!
+//! ```sh
+//! cargo run --release --example simd_grid
+//! ```
+
+use std::time::Instant;
+
+use inverse_observation_induced_probability_field_interference::{ProbabilityField, QuantumSlamCore};
+
+const LANDMARK_COUNTS: [usize; 5] = [8, 64, 256, 1024, 4096];
+const RESOLUTION: usize = 256;
+const BOUNDS: (f32, f32, f32, f32) = (-5.0, 5.0, -5.0, 5.0);
+
+fn main() {
+    println!("{:>10} {:>12} {:>12} {:>9} {:>12}", "landmarks", "scalar [ms]", "simd [ms]", "speedup", "rel. diff");
+
+    for n in LANDMARK_COUNTS {
+        // 円周上に並べたランドマーク (場が全域で非零になるように減衰を弱める)
+        let mut core = QuantumSlamCore::new(10.0).unwrap();
+        core.set_decay_factor(0.5).unwrap();
+        for i in 0..n {
+            let a = i as f32 * 0.37;
+            core.add_landmark(4.0 * a.cos(), 4.0 * (1.3 * a).sin()).unwrap();
+        }
+        core.observe(0.7, -0.4).unwrap();
+
+        let start = Instant::now();
+        let scalar = ProbabilityField::from_fn(BOUNDS, RESOLUTION, RESOLUTION, |x, y| core.probability_at(x, y));
+        let scalar_ms = start.elapsed().as_secs_f64() * 1e3;
+
+        let start = Instant::now();
+        let simd = core.evaluate_grid(BOUNDS, RESOLUTION, RESOLUTION);
+        let simd_ms = start.elapsed().as_secs_f64() * 1e3;
+
+        let peak = scalar.values.iter().copied().fold(0.0, f64::max);
+        let max_diff = scalar.values.iter()
+            .zip(&simd.values)
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0, f64::max);
+
+        println!("{:>10} {:>12.2} {:>12.2} {:>8.1}x {:>12.2e}", n, scalar_ms, simd_ms, scalar_ms / simd_ms, max_diff / peak);
+    }
+}
@@ -0,0 +1,29 @@
+// ========================================================================
+// Present Blit Shader (Compute Texture -> Surface)
+// ========================================================================
+//
+// コンピュートシェーダーの出力 (リニア値の Rgba8Unorm) をサーフェスへ描画する。
+// サーフェスが sRGB フォーマットの場合、フラグメント出力はハードウェアで
+// linear -> sRGB 変換されるため、コピーと違い色がずれない。
+// Bgra8Unorm 等のチャンネル順の違いもここで吸収される。
+
+@group(0) @binding(0) var field_texture: texture_2d<f32>;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+};
+
+// 画面全体を覆う1枚の三角形 (頂点バッファ不要)
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+    var out: VertexOutput;
+    out.position = vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // フラグメント座標はピクセル中心 (x + 0.5) なので整数化でテクセルと1:1対応
+    return textureLoad(field_texture, vec2<i32>(in.position.xy), 0);
+}
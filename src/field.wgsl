@@ -0,0 +1,105 @@
+// ========================================================================
+// Raw Probability Field Evaluation (GPU counterpart of evaluate_grid)
+// ========================================================================
+//
+// QuantumSlamCore::probability_at と同じ式で |psi|^2 を計算し、可視化せずに
+// f32 のままストレージバッファへ書き出す。並びは ProbabilityField と同じ
+// 行優先 (index = iy * nx + ix, 行0 = y_min)、サンプル点はセル中心。
+
+// shader.wgsl / lib.rs の Landmark と一致させること
+struct Landmark {
+    position: vec2<f32>,
+    observed_dist: f32,
+    confidence: f32,
+    phase_offset: f32,
+    amplitude: f32,
+    last_seen: f32,
+    orientation: f32,
+    aspect: f32,
+    observed: u32,
+    id: u32,
+    observed_bearing: f32,
+    decay_matrix: mat2x2<f32>,
+    wave_number: f32,
+    sigma: f32,
+    velocity: vec2<f32>,
+    radial_velocity: f32,
+    decayed_until: f32,
+};
+
+struct FieldParams {
+    bounds_min: vec2<f32>,   // (x_min, y_min)
+    bounds_max: vec2<f32>,   // (x_max, y_max)
+    grid: vec2<u32>,         // (nx, ny)
+    wave_number: f32,
+    decay_factor: f32,
+    num_landmarks: u32,
+    time: f32,               // QuantumSlamCore::time (ドップラー項 k * v * t と波面の伝播)
+    propagation_speed: f32,  // 波面の伝播速度 (半径 observed_dist + speed * t)
+    _pad: u32,
+};
+
+@group(0) @binding(0) var<uniform> params: FieldParams;
+@group(0) @binding(1) var<storage, read> landmarks: array<Landmark>;
+@group(0) @binding(2) var<storage, read_write> values: array<f32>;
+
+// 形状を考慮した距離 (shader.wgsl の landmark_distance と同じ)
+fn landmark_distance(lm: Landmark, p: vec2<f32>) -> f32 {
+    let d = p - lm.position;
+    let c = cos(lm.orientation);
+    let s = sin(lm.orientation);
+    let u = (c * d.x + s * d.y) / lm.aspect;
+    let v = -s * d.x + c * d.y;
+    return sqrt(u * u + v * v);
+}
+
+// ランドマーク→p の方向 u での減衰率 uᵀ M u (shader.wgsl の matrix_decay と同じ)
+fn matrix_decay(lm: Landmark, p: vec2<f32>, isotropic: f32) -> f32 {
+    let m = lm.decay_matrix;
+    if (all(m[0] == vec2<f32>(0.0)) && all(m[1] == vec2<f32>(0.0))) {
+        return isotropic;
+    }
+    let d = p - lm.position;
+    let rho = length(d);
+    if (rho == 0.0) {
+        return 0.5 * (m[0][0] + m[1][1]);
+    }
+    let u = d / rho;
+    return dot(u, m * u);
+}
+
+// 計測ノイズ sigma で広げた減衰率 rate / sqrt(1 + (sigma rate)^2) (shader.wgsl の landmark_decay と同じ)
+fn landmark_decay(lm: Landmark, p: vec2<f32>, isotropic: f32) -> f32 {
+    let rate = matrix_decay(lm, p, isotropic);
+    let spread = lm.sigma * rate;
+    return rate / sqrt(1.0 + spread * spread);
+}
+
+// 寄与の重み。見逃されたランドマークは 0、sigma で包絡の広がりと同じ比率だけ下げる (shader.wgsl の landmark_weight と同じ)
+fn landmark_weight(lm: Landmark, isotropic: f32) -> f32 {
+    let spread = lm.sigma * isotropic;
+    return select(0.0, lm.amplitude * lm.confidence / sqrt(1.0 + spread * spread), lm.observed != 0u);
+}
+
+@compute @workgroup_size(16, 16)
+fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    if (global_id.x >= params.grid.x || global_id.y >= params.grid.y) {
+        return;
+    }
+
+    let cell = (params.bounds_max - params.bounds_min) / vec2<f32>(params.grid);
+    let p = params.bounds_min + (vec2<f32>(global_id.xy) + 0.5) * cell;
+
+    var psi = vec2<f32>(0.0, 0.0);
+    for (var i = 0u; i < params.num_landmarks; i = i + 1u) {
+        let lm = landmarks[i];
+        let residual = landmark_distance(lm, p) - (lm.observed_dist + params.propagation_speed * params.time);
+        let k = select(params.wave_number, lm.wave_number, lm.wave_number > 0.0);
+        let phase = k * residual + lm.phase_offset + k * lm.radial_velocity * params.time;
+        let weight = landmark_weight(lm, params.decay_factor);
+        let amp = weight * exp(-landmark_decay(lm, p, params.decay_factor) * abs(residual));
+        psi = psi + amp * vec2<f32>(cos(phase), sin(phase));
+    }
+
+    values[global_id.y * params.grid.x + global_id.x] = dot(psi, psi);
+}
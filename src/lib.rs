@@ -1,139 +1,5671 @@
 // Rust 1.78+ と PyO3 0.20 の互換性警告をプロジェクトレベルで抑制
 #![allow(non_local_definitions)]
+// std 無効時は no_std + alloc の純粋な数値コアのみ (組み込み向け)
+#![cfg_attr(not(feature = "std"), no_std)]
 
+extern crate alloc;
+
+#[cfg(feature = "serde")]
 use serde::{Serialize, Deserialize};
 use bytemuck::{Pod, Zeroable};
+use wide::{f32x8, CmpGt};
+use alloc::collections::{BTreeMap, VecDeque};
+use core::ops::ControlFlow;
+use core::sync::atomic::{AtomicBool, Ordering};
+#[cfg(not(feature = "std"))]
+use alloc::{format, string::String, vec, vec::Vec};
+#[cfg(not(feature = "std"))]
+use math::Float;
+
+#[cfg(feature = "std")]
+use std::fs::File;
+#[cfg(feature = "std")]
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+#[cfg(feature = "std")]
+use std::path::Path;
+#[cfg(feature = "std")]
+use std::sync::{Arc, Mutex};
+
+#[cfg(feature = "fft")]
+pub use rustfft::num_complex::Complex;
+
+// 数学関数。no_std では f32/f64 の固有メソッド (sqrt, floor, ...) が無いため libm で補う。
+// 超越関数 (exp, sin, ...) は常に math::exp(x) の形で呼び、no_std と deterministic フィーチャでは
+// libm (どのプラットフォームでも同じビット列)、それ以外は std の固有メソッドを使う
+mod math {
+    #[cfg(not(feature = "std"))]
+    pub trait Float: Sized {
+        fn sqrt(self) -> Self;
+        fn powi(self, n: i32) -> Self;
+        fn floor(self) -> Self;
+        fn rem_euclid(self, rhs: Self) -> Self;
+    }
+
+    pub trait Transcendental: Sized {
+        fn exp(self) -> Self;
+        fn ln(self) -> Self;
+        fn sin(self) -> Self;
+        fn cos(self) -> Self;
+        fn sin_cos(self) -> (Self, Self);
+        fn atan2(self, other: Self) -> Self;
+        fn powf(self, n: Self) -> Self;
+    }
+
+    macro_rules! impl_float {
+        ($t:ty, $sqrt:ident, $exp:ident, $sin:ident, $cos:ident, $atan2:ident, $pow:ident, $floor:ident, $log:ident, $fabs:ident, $fmod:ident) => {
+            #[cfg(not(feature = "std"))]
+            impl Float for $t {
+                fn sqrt(self) -> Self { libm::$sqrt(self) }
+                fn powi(self, n: i32) -> Self { libm::$pow(self, n as Self) }
+                fn floor(self) -> Self { libm::$floor(self) }
+                fn rem_euclid(self, rhs: Self) -> Self {
+                    let r = libm::$fmod(self, rhs);
+                    if r < 0.0 { r + libm::$fabs(rhs) } else { r }
+                }
+            }
+
+            #[cfg(any(not(feature = "std"), feature = "deterministic"))]
+            impl Transcendental for $t {
+                fn exp(self) -> Self { libm::$exp(self) }
+                fn ln(self) -> Self { libm::$log(self) }
+                fn sin(self) -> Self { libm::$sin(self) }
+                fn cos(self) -> Self { libm::$cos(self) }
+                fn sin_cos(self) -> (Self, Self) { (libm::$sin(self), libm::$cos(self)) }
+                fn atan2(self, other: Self) -> Self { libm::$atan2(self, other) }
+                fn powf(self, n: Self) -> Self { libm::$pow(self, n) }
+            }
+
+            #[cfg(all(feature = "std", not(feature = "deterministic")))]
+            impl Transcendental for $t {
+                fn exp(self) -> Self { <$t>::exp(self) }
+                fn ln(self) -> Self { <$t>::ln(self) }
+                fn sin(self) -> Self { <$t>::sin(self) }
+                fn cos(self) -> Self { <$t>::cos(self) }
+                fn sin_cos(self) -> (Self, Self) { <$t>::sin_cos(self) }
+                fn atan2(self, other: Self) -> Self { <$t>::atan2(self, other) }
+                fn powf(self, n: Self) -> Self { <$t>::powf(self, n) }
+            }
+        };
+    }
+
+    impl_float!(f32, sqrtf, expf, sinf, cosf, atan2f, powf, floorf, logf, fabsf, fmodf);
+    impl_float!(f64, sqrt, exp, sin, cos, atan2, pow, floor, log, fabs, fmod);
+
+    pub fn exp<T: Transcendental>(x: T) -> T { x.exp() }
+    pub fn ln<T: Transcendental>(x: T) -> T { x.ln() }
+    pub fn sin<T: Transcendental>(x: T) -> T { x.sin() }
+    pub fn cos<T: Transcendental>(x: T) -> T { x.cos() }
+    pub fn sin_cos<T: Transcendental>(x: T) -> (T, T) { x.sin_cos() }
+    pub fn atan2<T: Transcendental>(y: T, x: T) -> T { y.atan2(x) }
+    pub fn powf<T: Transcendental>(x: T, n: T) -> T { x.powf(n) }
+}
 
 #[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
-// ============================================================================
-//  Shared Data Structures (CPU/GPU Common)
-// ============================================================================
+// ============================================================================
+//  Shared Data Structures (CPU/GPU Common)
+// ============================================================================
+
+/// Errors returned by the fallible parts of the crate.
+#[derive(Debug)]
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
+#[non_exhaustive]
+pub enum Error {
+    /// A wave number that is not finite and positive.
+    #[cfg_attr(feature = "std", error("wave_number must be positive, got {0}"))]
+    InvalidWaveNumber(f64),
+    /// A position with a NaN or infinite coordinate.
+    #[cfg_attr(feature = "std", error("position ({}, {}) is not finite", .0[0], .0[1]))]
+    NonFinitePosition([f32; 2]),
+    /// An operation that needs at least one landmark was given none.
+    #[cfg_attr(feature = "std", error("the map has no landmarks"))]
+    NoLandmarks,
+    /// A buffer whose length does not match the dimensions given with it.
+    #[cfg_attr(feature = "std", error("expected {expected} values, found {found}"))]
+    DimensionMismatch { expected: usize, found: usize },
+    /// More items (landmarks, grid cells) than a buffer holds or the device can allocate.
+    #[cfg_attr(feature = "std", error("{requested} items exceed the buffer capacity of {capacity}"))]
+    CapacityExceeded { requested: usize, capacity: usize },
+    /// No landmark has this handle (it was removed, or belongs to another core).
+    #[cfg_attr(feature = "std", error("no landmark with id {}", .0.0))]
+    UnknownLandmark(LandmarkId),
+    /// A parameter outside its valid range, with a description.
+    #[cfg_attr(feature = "std", error("{0}"))]
+    InvalidParameter(String),
+    /// No usable GPU adapter or device, or a dispatch beyond the device limits.
+    #[cfg_attr(feature = "std", error("GPU: {0}"))]
+    GpuInit(String),
+    /// An embedded shader failed validation.
+    #[cfg_attr(feature = "std", error("shader validation failed: {0}"))]
+    Shader(String),
+    #[cfg(feature = "std")]
+    #[error("{0}")]
+    Io(#[from] io::Error),
+    #[cfg(feature = "image")]
+    #[error("{0}")]
+    Image(#[from] image::ImageError),
+}
+
+// thiserror 1.x は std を必要とするので、no_std では Debug 表現をそのまま表示する
+#[cfg(not(feature = "std"))]
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::Debug::fmt(self, f)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl core::error::Error for Error {}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Pod, Zeroable)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Landmark {
+    pub position: [f32; 2],
+    pub observed_dist: f32,
+    pub confidence: f32,
+    pub phase_offset: f32,
+    // 場への寄与の大きさ (confidence = 観測の信頼度 とは独立)
+    #[cfg_attr(feature = "serde", serde(default = "default_amplitude"))]
+    pub amplitude: f32,
+    // 最後に観測されたシミュレーション時刻 (QuantumSlamCore::time)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub last_seen: f32,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub shape: LandmarkShape,
+    // 1 = observed_dist は有効な観測, 0 = 直近の観測で見逃された (場から除外)
+    #[cfg_attr(feature = "serde", serde(default = "default_observed"))]
+    pub observed: u32,
+    // QuantumSlamCore が割り当てるハンドル (0 = 未割り当て)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub id: LandmarkId,
+    // observe_bearing で得たカメラ座標系での方位 (rad, カメラの向きからの角度)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub observed_bearing: f32,
+    // 振幅包絡の異方性: ランドマーク→仮説位置の方向 û での減衰率 ûᵀ M û
+    // (全て 0 なら QuantumSlamCore::decay_factor による等方的な減衰)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub decay_matrix: [[f32; 2]; 2],
+    // このランドマーク固有の波数 (例: 別チャネルの UWB)。0 なら QuantumSlamCore::wave_number
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub wave_number: f32,
+    // 距離計測の標準偏差 (ワールド単位)。包絡を広げ、寄与の重みを下げる (0 = 正確)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub sigma: f32,
+    // 動くランドマーク (他のロボット・人に付けたタグ) の速度。propagate で位置を進める
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub velocity: [f32; 2],
+    // 視線方向の相対速度 (距離の変化率)。位相に k * v * t (t = QuantumSlamCore::time) を加える
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub radial_velocity: f32,
+    // decay_confidence_by_age で減衰を適用済みの時刻 (構造体サイズを 8byte 境界に揃える位置も兼ねる)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub decayed_until: f32,
+}
+
+/// Stable handle of a landmark in a [`QuantumSlamCore`], unaffected by removing or
+/// merging other landmarks. `LandmarkId(0)` is never assigned.
+#[repr(transparent)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Pod, Zeroable)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LandmarkId(pub u32);
+
+// NaN / 無限大の座標は場全体を NaN にするので入口で弾く
+fn check_position(p: [f32; 2]) -> Result<(), Error> {
+    if !(p[0].is_finite() && p[1].is_finite()) {
+        return Err(Error::NonFinitePosition(p));
+    }
+    Ok(())
+}
+
+// 波数は有限の正の値でなければならない
+fn check_wave_number(wave_number: f64) -> Result<(), Error> {
+    if !(wave_number.is_finite() && wave_number > 0.0) {
+        return Err(Error::InvalidWaveNumber(wave_number));
+    }
+    Ok(())
+}
+
+// 減衰率・半径・時刻など、有限の非負値であるべきパラメータの検証
+fn check_non_negative(name: &str, value: f32) -> Result<(), Error> {
+    if !(value.is_finite() && value >= 0.0) {
+        return Err(Error::InvalidParameter(format!("{} {} must be finite and non-negative", name, value)));
+    }
+    Ok(())
+}
+
+fn check_finite(name: &str, value: f32) -> Result<(), Error> {
+    if !value.is_finite() {
+        return Err(Error::InvalidParameter(format!("{} {} must be finite", name, value)));
+    }
+    Ok(())
+}
+
+fn check_feedback_strength(feedback_strength: f32) -> Result<(), Error> {
+    if !(0.0..=1.0).contains(&feedback_strength) {
+        return Err(Error::InvalidParameter(format!("feedback_strength {} must be within [0, 1]", feedback_strength)));
+    }
+    Ok(())
+}
+
+// 角度を [-π, π) に折り返す
+fn wrap_angle(angle: f32) -> f32 {
+    (angle + core::f32::consts::PI).rem_euclid(core::f32::consts::TAU) - core::f32::consts::PI
+}
+
+fn default_amplitude() -> f32 {
+    1.0
+}
+
+fn default_observed() -> u32 {
+    1
+}
+
+/// Footprint of a landmark: distances are measured in a frame rotated by
+/// `orientation` (radians) and shrunk by `aspect` along that axis, so iso-distance
+/// lines are ellipses elongated along the orientation. The default (`aspect = 1`)
+/// is a point landmark with plain Euclidean distance.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Pod, Zeroable)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LandmarkShape {
+    pub orientation: f32,
+    pub aspect: f32,
+}
+
+impl Default for LandmarkShape {
+    fn default() -> Self {
+        Self { orientation: 0.0, aspect: 1.0 }
+    }
+}
+
+impl Landmark {
+    /// A landmark at `(x, y)` with full confidence and unit amplitude. It counts as
+    /// observed, with a range of 0 (a peak at the landmark itself) until an observation
+    /// sets its distance.
+    pub fn new(x: f32, y: f32) -> Self {
+        Self {
+            position: [x, y],
+            observed_dist: 0.0, // Init
+            confidence: 1.0,
+            phase_offset: 0.0,
+            amplitude: default_amplitude(),
+            last_seen: 0.0,
+            shape: LandmarkShape::default(),
+            observed: default_observed(),
+            id: LandmarkId::default(),
+            observed_bearing: 0.0,
+            decay_matrix: [[0.0; 2]; 2],
+            wave_number: 0.0,
+            sigma: 0.0,
+            velocity: [0.0; 2],
+            radial_velocity: 0.0,
+            decayed_until: 0.0,
+        }
+    }
+
+    /// Gives the landmark its own anisotropic envelope for an extended feature such as
+    /// a wall: the amplitude decays at `along` per unit residual towards hypotheses in
+    /// `direction` (radians) from the landmark, at `across` perpendicular to it, and in
+    /// between following `ûᵀ M û` for the direction `û`. Replaces the core's isotropic
+    /// `decay_factor` for this landmark.
+    pub fn with_directional_decay(mut self, direction: f32, along: f32, across: f32) -> Self {
+        let (sin, cos) = math::sin_cos(direction);
+        // M = along * w wᵀ + across * n nᵀ (w = 向き, n = 直交方向)
+        let off_diagonal = (along - across) * cos * sin;
+        self.decay_matrix = [
+            [along * cos * cos + across * sin * sin, off_diagonal],
+            [off_diagonal, along * sin * sin + across * cos * cos],
+        ];
+        self
+    }
+
+    /// Gives the landmark a velocity (world units per unit time) for
+    /// [`QuantumSlamCore::propagate`], for dynamic beacons such as other robots.
+    pub fn with_velocity(mut self, velocity: [f32; 2]) -> Self {
+        self.velocity = velocity;
+        self
+    }
+
+    /// Gives the landmark a range measurement noise of standard deviation `sigma`
+    /// (world units). Its envelope widens from `1 / decay` to
+    /// `sqrt(1 / decay² + sigma²)` and its weight drops by the same factor, so an
+    /// imprecise sensor blurs the interference pattern instead of pulling it.
+    pub fn with_sigma(mut self, sigma: f32) -> Self {
+        self.sigma = sigma;
+        self
+    }
+
+    // 場への寄与の重み。見逃されたランドマークは 0。計測ノイズ sigma で包絡の広がりと同じ比率だけ下げる
+    fn weight(&self, isotropic: f32) -> f32 {
+        if self.observed == 0 {
+            return 0.0;
+        }
+        let spread = self.sigma * isotropic;
+        self.amplitude * self.confidence / (1.0 + spread * spread).sqrt()
+    }
+
+    // (x, y) にいて heading を向いたカメラから見た方位 ([-π, π))
+    fn bearing_from(&self, x: f32, y: f32, heading: f32) -> f32 {
+        let dx = self.position[0] - x;
+        let dy = self.position[1] - y;
+        wrap_angle(math::atan2(dy, dx) - heading)
+    }
+
+    // 観測方位との角度誤差 ([-π, π))
+    fn bearing_residual(&self, x: f32, y: f32, heading: f32) -> f32 {
+        wrap_angle(self.bearing_from(x, y, heading) - self.observed_bearing)
+    }
+
+    // (x, y) での振幅の減衰率と、その (x, y) についての勾配。decay_matrix が 0 なら isotropic。
+    // sigma があれば包絡幅 1/rate に二乗和で加える: rate' = rate / sqrt(1 + (sigma rate)²)
+    fn decay_and_gradient(&self, x: f32, y: f32, isotropic: f32) -> (f32, [f32; 2]) {
+        let (rate, gradient) = self.matrix_decay_and_gradient(x, y, isotropic);
+        if self.sigma == 0.0 {
+            return (rate, gradient);
+        }
+        let s = 1.0 + (self.sigma * rate) * (self.sigma * rate);
+        // d(rate')/d(rate) = s^{-3/2}
+        let scale = 1.0 / (s * s.sqrt());
+        (rate / s.sqrt(), [gradient[0] * scale, gradient[1] * scale])
+    }
+
+    // decay_matrix による (ノイズで広げる前の) 減衰率と勾配
+    fn matrix_decay_and_gradient(&self, x: f32, y: f32, isotropic: f32) -> (f32, [f32; 2]) {
+        let m = self.decay_matrix;
+        if m == [[0.0; 2]; 2] {
+            return (isotropic, [0.0; 2]);
+        }
+        let dx = x - self.position[0];
+        let dy = y - self.position[1];
+        let rho = (dx * dx + dy * dy).sqrt();
+        if rho == 0.0 {
+            // 方向が定まらないので全方向の平均
+            return (0.5 * (m[0][0] + m[1][1]), [0.0; 2]);
+        }
+        let u = [dx / rho, dy / rho];
+        // 二次形式には対称部分のみが効く
+        let m_xy = 0.5 * (m[0][1] + m[1][0]);
+        let mu = [m[0][0] * u[0] + m_xy * u[1], m_xy * u[0] + m[1][1] * u[1]];
+        let rate = u[0] * mu[0] + u[1] * mu[1];
+        // ∇(ûᵀ M û) = 2 (I - û ûᵀ) M û / ρ
+        let gradient = [2.0 * (mu[0] - rate * u[0]) / rho, 2.0 * (mu[1] - rate * u[1]) / rho];
+        (rate, gradient)
+    }
+
+    // 固有の波数があればそれ、なければ default
+    fn wave_number_or(&self, default: f32) -> f32 {
+        if self.wave_number > 0.0 { self.wave_number } else { default }
+    }
+
+    // 時刻 t での位相オフセット: phase_offset + k v t (ドップラー項)
+    fn phase_at(&self, k: f32, t: f32) -> f32 {
+        self.phase_offset + k * self.radial_velocity * t
+    }
+
+    // 時刻 t での波面の半径: 観測距離から propagation_speed で外へ進む (包絡ごと動く)
+    fn front_at(&self, t: f32, propagation_speed: f32) -> f32 {
+        self.observed_dist + propagation_speed * t
+    }
+
+    fn decay_rate(&self, x: f32, y: f32, isotropic: f32) -> f32 {
+        self.decay_and_gradient(x, y, isotropic).0
+    }
+
+    /// Shape-aware distance from the landmark to `(x, y)` (Euclidean for the default shape).
+    pub fn distance_to(&self, x: f32, y: f32) -> f32 {
+        self.distance_and_gradient(x, y).0
+    }
+
+    // 距離と、その (x, y) についての勾配
+    fn distance_and_gradient(&self, x: f32, y: f32) -> (f32, [f32; 2]) {
+        let dx = x - self.position[0];
+        let dy = y - self.position[1];
+        let (sin, cos) = math::sin_cos(self.shape.orientation);
+        let inv_aspect2 = 1.0 / (self.shape.aspect * self.shape.aspect);
+
+        // ランドマーク座標系: u = 向きに沿った成分, v = 直交成分
+        let u = cos * dx + sin * dy;
+        let v = -sin * dx + cos * dy;
+        let dist = (u * u * inv_aspect2 + v * v).sqrt();
+        if dist == 0.0 {
+            return (0.0, [0.0, 0.0]);
+        }
+
+        let gu = u * inv_aspect2 / dist;
+        let gv = v / dist;
+        (dist, [gu * cos - gv * sin, gu * sin + gv * cos])
+    }
+
+    // probability_at (PlaneWave) の複素項を全て f64 で計算する (probability_at_f64 用)。
+    // 式は distance_and_gradient / decay_and_gradient / weight / phase_at と同じ
+    fn range_term_f64(&self, x: f64, y: f64, k_default: f64, isotropic: f64, t: f64, propagation_speed: f64) -> [f64; 2] {
+        if self.observed == 0 {
+            return [0.0; 2];
+        }
+        let dx = x - self.position[0] as f64;
+        let dy = y - self.position[1] as f64;
+        let (sin, cos) = math::sin_cos(self.shape.orientation as f64);
+        let aspect = self.shape.aspect as f64;
+        let u = (cos * dx + sin * dy) / aspect;
+        let v = -sin * dx + cos * dy;
+        let residual = (u * u + v * v).sqrt() - (self.observed_dist as f64 + propagation_speed * t);
+
+        let m = self.decay_matrix.map(|row| row.map(|m| m as f64));
+        let rho = (dx * dx + dy * dy).sqrt();
+        let mut rate = if self.decay_matrix == [[0.0; 2]; 2] {
+            isotropic
+        } else if rho == 0.0 {
+            0.5 * (m[0][0] + m[1][1])
+        } else {
+            let (ux, uy) = (dx / rho, dy / rho);
+            let m_xy = 0.5 * (m[0][1] + m[1][0]);
+            ux * (m[0][0] * ux + m_xy * uy) + uy * (m_xy * ux + m[1][1] * uy)
+        };
+        let sigma = self.sigma as f64;
+        rate /= (1.0 + (sigma * rate) * (sigma * rate)).sqrt();
+        let weight = (self.amplitude as f64) * (self.confidence as f64) / (1.0 + (sigma * isotropic) * (sigma * isotropic)).sqrt();
+
+        let k = if self.wave_number > 0.0 { self.wave_number as f64 } else { k_default };
+        let phase = k * residual + self.phase_offset as f64 + k * self.radial_velocity as f64 * t;
+        let amp = weight * math::exp(-rate * residual.abs());
+        let (sin, cos) = math::sin_cos(phase);
+        [amp * cos, amp * sin]
+    }
+}
+
+/// A point landmark in `D` dimensions, for the dimension-generic CPU kernel
+/// (e.g. [`Landmark3d`] for aerial or underwater maps). Unlike [`Landmark`] it has no
+/// shape and is not uploaded to the GPU.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PointLandmark<const D: usize> {
+    #[cfg_attr(feature = "serde", serde(with = "serde_array"))]
+    pub position: [f32; D],
+    pub observed_dist: f32,
+    pub confidence: f32,
+    pub amplitude: f32,
+}
+
+// serde は任意長 D の配列を直接扱えないので、長さ付きの列として読み書きする
+#[cfg(feature = "serde")]
+mod serde_array {
+    use alloc::vec::Vec;
+    use serde::{de::Error as _, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer, const D: usize>(array: &[f32; D], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(array)
+    }
+
+    pub fn deserialize<'de, De: Deserializer<'de>, const D: usize>(deserializer: De) -> Result<[f32; D], De::Error> {
+        let values = Vec::<f32>::deserialize(deserializer)?;
+        let len = values.len();
+        values.try_into().map_err(|_| De::Error::invalid_length(len, &"as many coordinates as dimensions"))
+    }
+}
+
+/// A landmark at `[x, y, z]`.
+pub type Landmark3d = PointLandmark<3>;
+
+impl<const D: usize> PointLandmark<D> {
+    /// An unobserved landmark at `position` with full confidence and unit amplitude.
+    pub fn new(position: [f32; D]) -> Self {
+        Self { position, observed_dist: 0.0, confidence: 1.0, amplitude: default_amplitude() }
+    }
+
+    /// Euclidean distance from the landmark to `p`.
+    pub fn distance_to(&self, p: [f32; D]) -> f32 {
+        self.position
+            .iter()
+            .zip(&p)
+            .map(|(a, b)| (b - a) * (b - a))
+            .sum::<f32>()
+            .sqrt()
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub struct Uniforms {
+    pub resolution: [f32; 2],
+    pub time: f32,
+    pub wave_number: f32,
+    pub decay_factor: f32,
+    pub feedback_strength: f32,
+    pub num_landmarks: u32,
+    // 波面の伝播速度 (camera_pos の 8byte アライメント調整も兼ねる)
+    pub propagation_speed: f32,
+    pub camera_pos: [f32; 2],
+    // 描画範囲 (ワールド座標, y上向き)。画面の行0 = view_max.y
+    pub view_min: [f32; 2],
+    pub view_max: [f32; 2],
+    // FeedbackMode as u32
+    pub feedback_mode: u32,
+    pub _pad: u32, // 構造体サイズを WGSL と同じ 64byte に揃える
+}
+
+/// How the temporal feedback blends the previous frame into the current one.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[repr(u32)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum FeedbackMode {
+    /// The same `feedback_strength` everywhere.
+    #[default]
+    Uniform = 0,
+    /// `feedback_strength` scaled by the previous probability: bright regions stay
+    /// stable while dim ones follow the current frame.
+    Confidence = 1,
+}
+
+impl Uniforms {
+    pub fn builder() -> UniformsBuilder {
+        UniformsBuilder::new()
+    }
+}
+
+/// Builds [`Uniforms`] from the renderer's default parameters, keeping padding and the
+/// landmark count consistent.
+#[derive(Copy, Clone, Debug)]
+pub struct UniformsBuilder {
+    uniforms: Uniforms,
+    landmark_capacity: Option<usize>,
+}
+
+impl Default for UniformsBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UniformsBuilder {
+    pub fn new() -> Self {
+        Self {
+            uniforms: Uniforms {
+                resolution: [1.0, 1.0],
+                time: 0.0,
+                wave_number: 80.0,
+                decay_factor: 5.0,
+                feedback_strength: 0.9,
+                num_landmarks: 0,
+                propagation_speed: 0.0,
+                camera_pos: [0.0, 0.0],
+                view_min: [-1.0, -1.0],
+                view_max: [1.0, 1.0],
+                feedback_mode: FeedbackMode::Uniform as u32,
+                _pad: 0,
+            },
+            landmark_capacity: None,
+        }
+    }
+
+    pub fn resolution(mut self, width: u32, height: u32) -> Self {
+        self.uniforms.resolution = [width as f32, height as f32];
+        self
+    }
+
+    pub fn time(mut self, time: f32) -> Self {
+        self.uniforms.time = time;
+        self
+    }
+
+    pub fn wave_number(mut self, wave_number: f32) -> Self {
+        self.uniforms.wave_number = wave_number;
+        self
+    }
+
+    pub fn decay_factor(mut self, decay_factor: f32) -> Self {
+        self.uniforms.decay_factor = decay_factor;
+        self
+    }
+
+    pub fn feedback_strength(mut self, feedback_strength: f32) -> Self {
+        self.uniforms.feedback_strength = feedback_strength;
+        self
+    }
+
+    pub fn feedback_mode(mut self, mode: FeedbackMode) -> Self {
+        self.uniforms.feedback_mode = mode as u32;
+        self
+    }
+
+    pub fn num_landmarks(mut self, num_landmarks: usize) -> Self {
+        self.uniforms.num_landmarks = num_landmarks as u32;
+        self
+    }
+
+    pub fn propagation_speed(mut self, propagation_speed: f32) -> Self {
+        self.uniforms.propagation_speed = propagation_speed;
+        self
+    }
+
+    pub fn camera_pos(mut self, camera_pos: [f32; 2]) -> Self {
+        self.uniforms.camera_pos = camera_pos;
+        self
+    }
+
+    /// World-space region shown by the render (default `(-1, 1, -1, 1)`); see [`world_to_pixel`].
+    pub fn viewport(mut self, bounds: Bounds) -> Self {
+        let (x_min, x_max, y_min, y_max) = bounds;
+        self.uniforms.view_min = [x_min, y_min];
+        self.uniforms.view_max = [x_max, y_max];
+        self
+    }
+
+    /// Landmark buffer capacity that `num_landmarks` is checked against in `build`.
+    pub fn landmark_capacity(mut self, capacity: usize) -> Self {
+        self.landmark_capacity = Some(capacity);
+        self
+    }
+
+    /// Validates the parameters: non-empty resolution, `feedback_strength` in `[0, 1]`
+    /// and `num_landmarks` within the landmark capacity, if one was given.
+    pub fn build(self) -> Result<Uniforms, Error> {
+        let u = self.uniforms;
+        if u.resolution[0] < 1.0 || u.resolution[1] < 1.0 {
+            return Err(Error::InvalidParameter(format!("Resolution {}x{} must be at least 1x1", u.resolution[0], u.resolution[1])));
+        }
+        if !(u.view_max[0] > u.view_min[0] && u.view_max[1] > u.view_min[1]) {
+            return Err(Error::InvalidParameter(format!("Viewport {:?}..{:?} is empty", u.view_min, u.view_max)));
+        }
+        check_feedback_strength(u.feedback_strength)?;
+        if let Some(capacity) = self.landmark_capacity {
+            if u.num_landmarks as usize > capacity {
+                return Err(Error::CapacityExceeded { requested: u.num_landmarks as usize, capacity });
+            }
+        }
+        Ok(u)
+    }
+}
+
+// ============================================================================
+//  1. Physics Core (Pure Rust - CPU Implementation)
+// ============================================================================
+
+/// Which measurement `probability_at` and `complex_field_at` build the field from.
+/// `observe`, `observe_bearing` and `observe_range_bearing` select the model they
+/// record; the renderer, `wave_at_time` and the gradient-based
+/// methods always use ranges.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SensorModel {
+    /// The residual is the hypothesised minus the observed distance (`observed_dist`).
+    #[default]
+    Range,
+    /// The residual is the angular error between the bearing seen from the hypothesis
+    /// with the core's `heading` and `observed_bearing`, wrapped to `[-π, π)`.
+    Bearing,
+    /// Both: each landmark's phase is `wave_number * range residual +
+    /// bearing_wave_number * bearing residual`, and its amplitude decays with the sum
+    /// of both residuals, so only hypotheses matching range and bearing stay bright.
+    RangeBearing,
+}
+
+/// How landmarks that are not re-observed lose coherence in [`QuantumSlamCore::step`].
+/// The default (all zero) disables both effects.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Decoherence {
+    /// Confidence decays as `e^{-confidence_rate * dt}`.
+    pub confidence_rate: f32,
+    /// Phase offsets random-walk with variance `2 * phase_diffusion * dt` (rad²).
+    pub phase_diffusion: f32,
+}
+
+/// What a [`WaveKernel`] sees of one landmark for one camera hypothesis.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct KernelInput {
+    /// Hypothesised minus observed distance.
+    pub residual: f32,
+    /// Hypothesised distance from the landmark.
+    pub distance: f32,
+    pub wave_number: f32,
+    /// The landmark's amplitude decay rate: the core's `decay_factor`, or its own
+    /// directional decay.
+    pub decay: f32,
+}
+
+/// Per-landmark contribution to the field: each landmark adds
+/// `weight * envelope(input) * e^{i (phase(input) + phase_offset)}`. Pass a custom
+/// implementation to [`QuantumSlamCore::probability_with_kernel`] to change the wave
+/// model without rewriting the summation.
+pub trait WaveKernel {
+    fn phase(&self, input: &KernelInput) -> f32;
+    fn envelope(&self, input: &KernelInput) -> f32;
+}
+
+/// The default kernel (`probability_at`): phase `k r`, envelope `e^{-decay |r|}`.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct PlaneWave;
+
+impl WaveKernel for PlaneWave {
+    fn phase(&self, input: &KernelInput) -> f32 {
+        input.wave_number * input.residual
+    }
+
+    fn envelope(&self, input: &KernelInput) -> f32 {
+        math::exp(-input.decay * input.residual.abs())
+    }
+}
+
+/// [`PlaneWave`] with the `1 / distance` falloff of a spherical wave, so far
+/// landmarks count less. Distances below `min_distance` are clamped to it.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SphericalWave {
+    pub min_distance: f32,
+}
+
+impl Default for SphericalWave {
+    fn default() -> Self {
+        Self { min_distance: 0.1 }
+    }
+}
+
+impl WaveKernel for SphericalWave {
+    fn phase(&self, input: &KernelInput) -> f32 {
+        PlaneWave.phase(input)
+    }
+
+    fn envelope(&self, input: &KernelInput) -> f32 {
+        PlaneWave.envelope(input) / input.distance.max(self.min_distance)
+    }
+}
+
+/// Phase `k r` under a Gaussian envelope `e^{-r² / 2σ²}` in the residual, smooth at
+/// `r = 0` unlike the exponential (the landmark decay rate is not used).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GaussianWave {
+    pub sigma: f32,
+}
+
+impl WaveKernel for GaussianWave {
+    fn phase(&self, input: &KernelInput) -> f32 {
+        PlaneWave.phase(input)
+    }
+
+    fn envelope(&self, input: &KernelInput) -> f32 {
+        let z = input.residual / self.sigma;
+        math::exp(-0.5 * z * z)
+    }
+}
+
+/// Rigid 2D transform: rotation by `theta` (radians, counter-clockwise) followed by a
+/// translation of `(x, y)`, e.g. the pose of one map's frame in another's.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Pose2 {
+    pub x: f32,
+    pub y: f32,
+    pub theta: f32,
+}
+
+impl Pose2 {
+    pub fn new(x: f32, y: f32, theta: f32) -> Self {
+        Self { x, y, theta }
+    }
+
+    /// `p` mapped from the local frame into the parent frame.
+    pub fn apply(&self, p: [f32; 2]) -> [f32; 2] {
+        let (sin, cos) = math::sin_cos(self.theta);
+        [cos * p[0] - sin * p[1] + self.x, sin * p[0] + cos * p[1] + self.y]
+    }
+
+    // ランドマークを親座標系へ: 位置・形状の向き・速度・異方的減衰 (R M Rᵀ) を回転
+    fn apply_to_landmark(&self, mut lm: Landmark) -> Landmark {
+        lm.position = self.apply(lm.position);
+        lm.shape.orientation += self.theta;
+        let (sin, cos) = math::sin_cos(self.theta);
+        let [vx, vy] = lm.velocity;
+        lm.velocity = [cos * vx - sin * vy, sin * vx + cos * vy];
+        let r = [[cos, -sin], [sin, cos]];
+        let m = lm.decay_matrix;
+        let rm = [
+            [r[0][0] * m[0][0] + r[0][1] * m[1][0], r[0][0] * m[0][1] + r[0][1] * m[1][1]],
+            [r[1][0] * m[0][0] + r[1][1] * m[1][0], r[1][0] * m[0][1] + r[1][1] * m[1][1]],
+        ];
+        lm.decay_matrix = [
+            [rm[0][0] * r[0][0] + rm[0][1] * r[0][1], rm[0][0] * r[1][0] + rm[0][1] * r[1][1]],
+            [rm[1][0] * r[0][0] + rm[1][1] * r[0][1], rm[1][0] * r[1][0] + rm[1][1] * r[1][1]],
+        ];
+        lm
+    }
+}
+
+/// A camera estimate recorded by [`QuantumSlamCore::record_pose`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TimedPose {
+    /// The core's `time` when recorded.
+    pub time: f32,
+    pub position: [f32; 2],
+    /// Field value at `position`.
+    pub probability: f64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct QuantumSlamCore {
+    pub landmarks: Vec<Landmark>,
+    pub wave_number: f64,
+    // 残差に対する振幅の減衰率 exp(-decay_factor * |residual|)
+    pub decay_factor: f32,
+    // observe_accumulate での過去観測の重み (GPU側の feedback_strength に対応)
+    pub feedback_strength: f32,
+    // 波面の伝播速度 (wave_at_time の波面の半径 observed_dist + speed * t)
+    pub propagation_speed: f32,
+    // シミュレーション時刻 (observe 時に各ランドマークの last_seen へ記録)
+    pub time: f32,
+    // step で観測されなかったランドマークに適用する減衰と位相拡散
+    pub decoherence: Decoherence,
+    // probability_at が使う観測の種類
+    pub sensor_model: SensorModel,
+    // 方位残差に対する波数 (SensorModel::Bearing / RangeBearing)
+    pub bearing_wave_number: f64,
+    // observe_bearing 時のカメラの向き (rad)。仮説位置でも同じ向きを仮定する
+    pub heading: f32,
+    // simulate_scan でランドマークを円盤とみなすときの半径
+    pub landmark_radius: f32,
+    // predict で移動させるカメラ位置の事前分布 (posterior で現在の場と掛け合わせる)
+    prior: Option<ProbabilityField>,
+    // record_pose で記録した推定軌跡
+    trajectory: Vec<TimedPose>,
+    // observe_aperture の各ポーズ: (最初のポーズからの基線, ランドマークごとの観測距離)
+    aperture: Vec<([f32; 2], Vec<f32>)>,
+    // observe_for_agent: エージェントごとの観測距離 (landmarks と同じ並び)
+    agents: BTreeMap<u32, Vec<f32>>,
+    // 高さを持つマップ用 (probability_at_3d)。2D の landmarks とは独立
+    pub landmarks_3d: Vec<Landmark3d>,
+    // 次に割り当てる LandmarkId
+    next_id: u32,
+}
+
+/// Version written by [`QuantumSlamCore::to_json`] and [`QuantumSlamCore::to_bincode`].
+/// Loaders reject snapshots from a newer version instead of misreading them.
+#[cfg(feature = "persist")]
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+// 保存形式: 先頭にバージョン (bincode では最初の 4 byte)。書き込みは C = &QuantumSlamCore
+#[cfg(feature = "persist")]
+#[derive(Serialize, Deserialize)]
+struct Snapshot<C> {
+    version: u32,
+    core: C,
+}
+
+#[cfg(feature = "persist")]
+#[derive(Deserialize)]
+struct SnapshotVersion {
+    version: u32,
+}
+
+#[cfg(feature = "persist")]
+fn check_snapshot_version(version: u32) -> Result<(), Error> {
+    if version > SNAPSHOT_VERSION {
+        return Err(Error::InvalidParameter(format!(
+            "Snapshot version {} is newer than the supported version {}",
+            version, SNAPSHOT_VERSION
+        )));
+    }
+    Ok(())
+}
+
+// estimate_pose / find_peaks の粗探索: auto_bounds の余白と格子解像度
+const SEARCH_PADDING: f32 = 0.5;
+const SEARCH_RESOLUTION: usize = 128;
+
+// save_png / save_npy で bounds を省略したときの auto_bounds の余白
+#[cfg(feature = "std")]
+const EXPORT_PADDING: f32 = 0.1;
+
+// reinforce の対数オッズ更新で confidence を [LIMIT, 1 - LIMIT] に収める
+// (0 / 1 に張り付くと以後の証拠で動かなくなるため)
+const CONFIDENCE_LIMIT: f32 = 1e-3;
+
+// plane_wave_sums で一度に処理するランドマーク数 (f32x8 の幅)
+const LANES: usize = 8;
+
+// f32x8 の exp / sin_cos。wide の多項式近似は FMA のある環境で丸めが変わるので、
+// deterministic ではレーンごとに math (libm) で求める
+#[cfg(not(feature = "deterministic"))]
+fn exp_lanes(v: f32x8) -> f32x8 {
+    v.exp()
+}
+
+#[cfg(feature = "deterministic")]
+fn exp_lanes(v: f32x8) -> f32x8 {
+    v.to_array().map(math::exp).into()
+}
+
+#[cfg(not(feature = "deterministic"))]
+fn sin_cos_lanes(v: f32x8) -> (f32x8, f32x8) {
+    v.sin_cos()
+}
+
+#[cfg(feature = "deterministic")]
+fn sin_cos_lanes(v: f32x8) -> (f32x8, f32x8) {
+    let (sin, cos) = (v.to_array().map(math::sin), v.to_array().map(math::cos));
+    (sin.into(), cos.into())
+}
+
+// LANES 個のランドマークの、位置に依らない値 (QuantumSlamCore::landmark_lanes)
+#[derive(Clone, Debug)]
+struct LandmarkLanes {
+    position: [f32x8; 2],
+    // 形状の向きの [sin, cos]
+    orientation: [f32x8; 2],
+    inv_aspect: f32x8,
+    observed_dist: f32x8,
+    wave_number: f32x8,
+    // 時刻 (ドップラー・伝播) を含めた位相オフセット
+    phase_offset: f32x8,
+    weight: f32x8,
+    sigma: f32x8,
+    // 対称化した [m00, m01, m11]
+    decay_matrix: [f32x8; 3],
+    // decay_matrix を使うレーンは全ビット 1 (blend のマスク)
+    anisotropic: f32x8,
+}
+
+/// Uniform grid over `bounds` listing, for each cell, the landmarks whose range
+/// envelope can exceed `epsilon` somewhere in it, from [`QuantumSlamCore::spatial_index`].
+/// It snapshots the landmarks and the clock, so rebuild it after either changes.
+#[derive(Clone, Debug)]
+pub struct SpatialIndex {
+    bounds: Bounds,
+    nx: usize,
+    ny: usize,
+    // セルごとの候補 (行優先, 行0 = y_min)
+    cells: Vec<(Vec<LandmarkId>, Vec<LandmarkLanes>)>,
+    // bounds の外では全ランドマーク
+    all: (Vec<LandmarkId>, Vec<LandmarkLanes>),
+}
+
+impl SpatialIndex {
+    // (x, y) を含むセルの候補
+    fn cell_at(&self, x: f32, y: f32) -> &(Vec<LandmarkId>, Vec<LandmarkLanes>) {
+        let (x_min, x_max, y_min, y_max) = self.bounds;
+        let fx = (x - x_min) / (x_max - x_min) * self.nx as f32;
+        let fy = (y - y_min) / (y_max - y_min) * self.ny as f32;
+        if !(fx >= 0.0 && fx < self.nx as f32 && fy >= 0.0 && fy < self.ny as f32) {
+            return &self.all;
+        }
+        &self.cells[fy as usize * self.nx + fx as usize]
+    }
+
+    /// Landmarks evaluated at `(x, y)`: those that can reach `epsilon` in its cell, or
+    /// every landmark outside `bounds`.
+    pub fn landmarks_near(&self, x: f32, y: f32) -> &[LandmarkId] {
+        &self.cell_at(x, y).0
+    }
+}
+
+// 区間 [lo, hi) (bounds の最小端からの距離) と重なるセル番号 (0..n に収める)
+fn cell_span(lo: f32, hi: f32, cell: f32, n: usize) -> core::ops::Range<usize> {
+    let first = (lo / cell).floor().max(0.0);
+    let last = (hi / cell).floor() + 1.0;
+    if last <= 0.0 || first >= n as f32 {
+        return 0..0;
+    }
+    first as usize..(last as usize).min(n)
+}
+
+// (波数, 残差, 重み, 位相オフセット, 減衰率) の組から ψ = Σ w e^{-decay|r|} e^{i(kr + φ)} を求める
+// (2D/3D 共通のカーネル)
+fn superpose(terms: impl IntoIterator<Item = (f32, f32, f32, f32, f32)>) -> [f64; 2] {
+    let mut re_sum = 0.0;
+    let mut im_sum = 0.0;
+
+    for (k, residual, weight, phase_offset, decay) in terms {
+        let phase = k * residual + phase_offset;
+        let amp = weight * math::exp(-decay * residual.abs());
+
+        re_sum += amp * math::cos(phase);
+        im_sum += amp * math::sin(phase);
+    }
+
+    [re_sum as f64, im_sum as f64]
+}
+
+impl QuantumSlamCore {
+    /// An empty core with the default parameters. A wave number that is not finite and
+    /// positive is rejected.
+    pub fn new(wave_number: f64) -> Result<Self, Error> {
+        check_wave_number(wave_number)?;
+        Ok(Self {
+            landmarks: Vec::new(),
+            wave_number,
+            decay_factor: 2.0,
+            feedback_strength: 0.9,
+            propagation_speed: 0.0,
+            time: 0.0,
+            decoherence: Decoherence::default(),
+            sensor_model: SensorModel::Range,
+            bearing_wave_number: 10.0,
+            heading: 0.0,
+            landmark_radius: 0.1,
+            prior: None,
+            trajectory: Vec::new(),
+            aperture: Vec::new(),
+            agents: BTreeMap::new(),
+            landmarks_3d: Vec::new(),
+            next_id: 1,
+        })
+    }
+
+    /// Alias of [`Self::new`], which now validates the wave number itself.
+    #[deprecated(note = "`new` returns a `Result`; use it instead")]
+    pub fn try_new(wave_number: f64) -> Result<Self, Error> {
+        Self::new(wave_number)
+    }
+
+    /// A core over an existing, non-empty landmark map with finite positions.
+    pub fn from_landmarks(wave_number: f64, landmarks: Vec<Landmark>) -> Result<Self, Error> {
+        if landmarks.is_empty() {
+            return Err(Error::NoLandmarks);
+        }
+        let mut core = Self::new(wave_number)?;
+        for lm in landmarks {
+            check_position(lm.position)?;
+            core.push_landmark(lm);
+        }
+        Ok(core)
+    }
+
+    /// `new` with room for `capacity` landmarks before reallocating.
+    pub fn with_capacity(wave_number: f64, capacity: usize) -> Result<Self, Error> {
+        let mut core = Self::new(wave_number)?;
+        core.landmarks.reserve(capacity);
+        Ok(core)
+    }
+
+    /// Reserves room for at least `additional` more landmarks.
+    pub fn reserve(&mut self, additional: usize) {
+        self.landmarks.reserve(additional);
+    }
+
+    /// Sets `wave_number`, which must be finite and positive.
+    pub fn set_wave_number(&mut self, wave_number: f64) -> Result<(), Error> {
+        check_wave_number(wave_number)?;
+        self.wave_number = wave_number;
+        Ok(())
+    }
+
+    /// Sets `bearing_wave_number`, which must be finite and positive.
+    pub fn set_bearing_wave_number(&mut self, wave_number: f64) -> Result<(), Error> {
+        check_wave_number(wave_number)?;
+        self.bearing_wave_number = wave_number;
+        Ok(())
+    }
+
+    /// Sets `decay_factor`, which must be finite and non-negative.
+    pub fn set_decay_factor(&mut self, decay_factor: f32) -> Result<(), Error> {
+        check_non_negative("decay_factor", decay_factor)?;
+        self.decay_factor = decay_factor;
+        Ok(())
+    }
+
+    /// Sets `feedback_strength`, which must be within `[0, 1]`.
+    pub fn set_feedback_strength(&mut self, feedback_strength: f32) -> Result<(), Error> {
+        check_feedback_strength(feedback_strength)?;
+        self.feedback_strength = feedback_strength;
+        Ok(())
+    }
+
+    /// Sets `propagation_speed`, which must be finite.
+    pub fn set_propagation_speed(&mut self, propagation_speed: f32) -> Result<(), Error> {
+        check_finite("propagation_speed", propagation_speed)?;
+        self.propagation_speed = propagation_speed;
+        Ok(())
+    }
+
+    /// Sets the core clock `time`, which must be finite and non-negative.
+    pub fn set_time(&mut self, time: f32) -> Result<(), Error> {
+        check_non_negative("time", time)?;
+        self.time = time;
+        Ok(())
+    }
+
+    /// Sets `landmark_radius`, which must be finite and non-negative.
+    pub fn set_landmark_radius(&mut self, landmark_radius: f32) -> Result<(), Error> {
+        check_non_negative("landmark_radius", landmark_radius)?;
+        self.landmark_radius = landmark_radius;
+        Ok(())
+    }
+
+    /// Sets `decoherence`, whose rates must be finite and non-negative.
+    pub fn set_decoherence(&mut self, decoherence: Decoherence) -> Result<(), Error> {
+        check_non_negative("confidence_rate", decoherence.confidence_rate)?;
+        check_non_negative("phase_diffusion", decoherence.phase_diffusion)?;
+        self.decoherence = decoherence;
+        Ok(())
+    }
+
+    /// Adds a landmark at `(x, y)` and returns its handle. A NaN or infinite position,
+    /// which would otherwise turn the whole field into NaN, is rejected.
+    pub fn add_landmark(&mut self, x: f32, y: f32) -> Result<LandmarkId, Error> {
+        check_position([x, y])?;
+        Ok(self.push_landmark(Landmark::new(x, y)))
+    }
+
+    // 新しい ID を割り当てて追加する
+    fn push_landmark(&mut self, mut lm: Landmark) -> LandmarkId {
+        lm.id = LandmarkId(self.next_id);
+        self.next_id += 1;
+        self.landmarks.push(lm);
+        lm.id
+    }
+
+    /// Current index in `landmarks` of the landmark with handle `id`.
+    pub fn landmark_index(&self, id: LandmarkId) -> Option<usize> {
+        self.landmarks.iter().position(|lm| lm.id == id)
+    }
+
+    /// Removes the landmark with handle `id` and returns it; the other handles stay valid.
+    pub fn remove_landmark(&mut self, id: LandmarkId) -> Result<Landmark, Error> {
+        let index = self.landmark_index(id).ok_or(Error::UnknownLandmark(id))?;
+        let per_pose = self.aperture.iter_mut().map(|(_, dists)| dists);
+        for dists in per_pose.chain(self.agents.values_mut()) {
+            if index < dists.len() {
+                dists.remove(index);
+            }
+        }
+        Ok(self.landmarks.remove(index))
+    }
+
+    /// Sets the velocity of the landmark with handle `id` (see [`Landmark::with_velocity`]).
+    pub fn set_landmark_velocity(&mut self, id: LandmarkId, velocity: [f32; 2]) -> Result<(), Error> {
+        let index = self.landmark_index(id).ok_or(Error::UnknownLandmark(id))?;
+        self.landmarks[index].velocity = velocity;
+        Ok(())
+    }
+
+    /// Advances every landmark by its velocity over `dt`, so moving beacons are tracked
+    /// instead of assumed static. The observed ranges are kept; `time` is not advanced
+    /// (see [`Self::step`]). `dt` must be finite and non-negative.
+    pub fn propagate(&mut self, dt: f32) -> Result<(), Error> {
+        check_non_negative("dt", dt)?;
+        for lm in &mut self.landmarks {
+            lm.position[0] += lm.velocity[0] * dt;
+            lm.position[1] += lm.velocity[1] * dt;
+        }
+        Ok(())
+    }
+
+    /// Sets the radial velocity (range rate) of the landmark with handle `id`, as measured
+    /// by an FMCW-style sensor: its phase advances by `k * radial_velocity * time`.
+    pub fn set_landmark_radial_velocity(&mut self, id: LandmarkId, radial_velocity: f32) -> Result<(), Error> {
+        let index = self.landmark_index(id).ok_or(Error::UnknownLandmark(id))?;
+        self.landmarks[index].radial_velocity = radial_velocity;
+        Ok(())
+    }
+
+    /// Sets the range noise of the landmark with handle `id` (see [`Landmark::with_sigma`]);
+    /// 0 treats its measurements as exact.
+    pub fn set_landmark_sigma(&mut self, id: LandmarkId, sigma: f32) -> Result<(), Error> {
+        let index = self.landmark_index(id).ok_or(Error::UnknownLandmark(id))?;
+        check_non_negative("Measurement sigma", sigma)?;
+        self.landmarks[index].sigma = sigma;
+        Ok(())
+    }
+
+    /// Pruning pass for long runs, where the map otherwise grows without bound and
+    /// `probability_at` slows down linearly: drops landmarks whose confidence is below
+    /// `min_confidence`, then, with `max_landmarks`, keeps only that many of the
+    /// strongest contributors (largest `amplitude * confidence`). Survivors keep their
+    /// order and handles. Returns the number of landmarks removed.
+    pub fn prune(&mut self, min_confidence: f32, max_landmarks: Option<usize>) -> usize {
+        let mut keep: Vec<bool> = self.landmarks.iter().map(|lm| lm.confidence >= min_confidence).collect();
+
+        if let Some(budget) = max_landmarks {
+            let mut ranked: Vec<usize> = (0..self.landmarks.len()).filter(|&i| keep[i]).collect();
+            let strength = |i: usize| self.landmarks[i].amplitude * self.landmarks[i].confidence;
+            ranked.sort_by(|&a, &b| strength(b).total_cmp(&strength(a)));
+            for &i in ranked.iter().skip(budget) {
+                keep[i] = false;
+            }
+        }
+
+        // aperture とエージェントの観測距離も landmarks と同じ並びで間引く
+        let per_pose = self.aperture.iter_mut().map(|(_, dists)| dists);
+        for dists in per_pose.chain(self.agents.values_mut()) {
+            let mut flags = keep.iter();
+            dists.retain(|_| *flags.next().unwrap_or(&true));
+        }
+        let before = self.landmarks.len();
+        let mut flags = keep.iter();
+        self.landmarks.retain(|_| *flags.next().unwrap_or(&true));
+        before - self.landmarks.len()
+    }
+
+    /// Moves the landmark with handle `id` to `(x, y)`. Its observed distance no longer
+    /// applies and is cleared, so it leaves the field until it is observed again. A
+    /// non-finite position is rejected.
+    pub fn update_landmark(&mut self, id: LandmarkId, x: f32, y: f32) -> Result<(), Error> {
+        let index = self.landmark_index(id).ok_or(Error::UnknownLandmark(id))?;
+        check_position([x, y])?;
+        let lm = &mut self.landmarks[index];
+        lm.position = [x, y];
+        lm.observed_dist = 0.0;
+        lm.observed = 0;
+        Ok(())
+    }
+
+    /// Gives the landmark with handle `id` its own wave number (e.g. a beacon on another
+    /// UWB channel), or with `None` returns it to the core's `wave_number`.
+    pub fn set_landmark_wave_number(&mut self, id: LandmarkId, wave_number: Option<f32>) -> Result<(), Error> {
+        let index = self.landmark_index(id).ok_or(Error::UnknownLandmark(id))?;
+        let wave_number = match wave_number {
+            Some(k) => {
+                check_wave_number(k as f64)?;
+                k
+            }
+            None => 0.0,
+        };
+        self.landmarks[index].wave_number = wave_number;
+        Ok(())
+    }
+
+    /// Removes every landmark (and the aperture and agent observations of them).
+    /// Handles are not reused.
+    pub fn clear_landmarks(&mut self) {
+        self.landmarks.clear();
+        self.aperture.clear();
+        self.agents.clear();
+    }
+
+    /// Simulates a range observation of every landmark from the camera at
+    /// `(true_cam_x, true_cam_y)`. A NaN or infinite camera position is rejected instead
+    /// of storing NaN distances.
+    pub fn observe(&mut self, true_cam_x: f32, true_cam_y: f32) -> Result<(), Error> {
+        self.observe_ranges([true_cam_x, true_cam_y])
+    }
+
+    // observe の本体。observe_* の全てがここを通るので、カメラ位置もここで検証する
+    fn observe_ranges(&mut self, cam: [f32; 2]) -> Result<(), Error> {
+        check_position(cam)?;
+        self.sensor_model = SensorModel::Range;
+        for lm in &mut self.landmarks {
+            lm.observed_dist = lm.distance_to(cam[0], cam[1]);
+            lm.last_seen = self.time;
+            lm.observed = 1;
+        }
+        Ok(())
+    }
+
+    /// `observe` from a moving platform at `true_cam` with velocity `velocity`: each
+    /// landmark also gets the range rate `d|L - cam|/dt = -velocity · û` (`û` pointing
+    /// from the camera to the landmark) as its radial velocity, the Doppler shift an
+    /// FMCW sensor reports. A non-finite camera position or velocity is rejected.
+    pub fn observe_with_velocity(&mut self, true_cam: [f32; 2], velocity: [f32; 2]) -> Result<(), Error> {
+        if !(velocity[0].is_finite() && velocity[1].is_finite()) {
+            return Err(Error::InvalidParameter(format!("Velocity {:?} must be finite", velocity)));
+        }
+        self.observe_ranges(true_cam)?;
+        for lm in &mut self.landmarks {
+            let dx = lm.position[0] - true_cam[0];
+            let dy = lm.position[1] - true_cam[1];
+            let range = (dx * dx + dy * dy).sqrt();
+            lm.radial_velocity = if range > 0.0 { -(velocity[0] * dx + velocity[1] * dy) / range } else { 0.0 };
+        }
+        Ok(())
+    }
+
+    /// `observe` for the single landmark with handle `id` (e.g. when only one beacon
+    /// responded this cycle); the other landmarks keep their previous observations.
+    pub fn observe_one(&mut self, id: LandmarkId, true_cam: [f32; 2]) -> Result<(), Error> {
+        let index = self.landmark_index(id).ok_or(Error::UnknownLandmark(id))?;
+        check_position(true_cam)?;
+        self.sensor_model = SensorModel::Range;
+        let lm = &mut self.landmarks[index];
+        lm.observed_dist = lm.distance_to(true_cam[0], true_cam[1]);
+        lm.last_seen = self.time;
+        lm.observed = 1;
+        Ok(())
+    }
+
+    /// Feeds a measured range (e.g. UWB or lidar) to the landmark with handle `id`,
+    /// instead of simulating it from a known camera position like `observe` does. The
+    /// range must be finite and non-negative.
+    pub fn set_observation(&mut self, id: LandmarkId, measured_dist: f32) -> Result<(), Error> {
+        let index = self.landmark_index(id).ok_or(Error::UnknownLandmark(id))?;
+        check_non_negative("Measured distance", measured_dist)?;
+        let lm = &mut self.landmarks[index];
+        lm.observed_dist = measured_dist;
+        lm.last_seen = self.time;
+        lm.observed = 1;
+        Ok(())
+    }
+
+    /// [`Self::set_observation`] for a batch of `(id, measured_dist)`. Stops at the first
+    /// unknown handle or invalid range; the measurements before it are kept.
+    pub fn set_observations(&mut self, measurements: &[(LandmarkId, f32)]) -> Result<(), Error> {
+        for &(id, measured_dist) in measurements {
+            self.set_observation(id, measured_dist)?;
+        }
+        Ok(())
+    }
+
+    /// Data association for a scan of unlabeled ranges. Each range is compared with
+    /// the distance of every landmark from the current [`Self::estimate_pose`], and
+    /// pairs within half a fringe (`π / wave_number`) are matched greedily, closest
+    /// first; matched landmarks get the range as by `set_observation` and the others
+    /// are marked unobserved.
+    ///
+    /// Returns the landmark each range was matched to, or `Err(range)` for a range that
+    /// gates with no landmark or whose every candidate was claimed by a closer one (e.g.
+    /// a landmark not yet in the map, for the caller to add once it is positioned). No
+    /// landmark is created here. Every range is unmatched when there is no pose
+    /// estimate (no landmarks).
+    pub fn associate(&mut self, measured_ranges: &[f32]) -> Vec<Result<LandmarkId, f32>> {
+        let Some((pose, _)) = self.estimate_pose() else {
+            return measured_ranges.iter().map(|&range| Err(range)).collect();
+        };
+        let gate = core::f32::consts::PI / self.wave_number as f32;
+
+        // ゲート内の (残差, 計測 index, ランドマーク index) を残差の小さい順に貪欲に割り当てる
+        let mut pairs = Vec::new();
+        for (mi, &range) in measured_ranges.iter().enumerate() {
+            for (li, lm) in self.landmarks.iter().enumerate() {
+                let residual = (range - lm.distance_to(pose[0], pose[1])).abs();
+                if residual <= gate {
+                    pairs.push((residual, mi, li));
+                }
+            }
+        }
+        pairs.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut assigned = vec![None; measured_ranges.len()];
+        let mut claimed = vec![false; self.landmarks.len()];
+        for &(_, mi, li) in &pairs {
+            if assigned[mi].is_none() && !claimed[li] {
+                assigned[mi] = Some(li);
+                claimed[li] = true;
+            }
+        }
+
+        for (lm, claimed) in self.landmarks.iter_mut().zip(&claimed) {
+            lm.observed = u32::from(*claimed);
+        }
+
+        measured_ranges
+            .iter()
+            .zip(assigned)
+            .map(|(&range, assigned)| {
+                let lm = &mut self.landmarks[assigned.ok_or(range)?];
+                lm.observed_dist = range;
+                lm.last_seen = self.time;
+                Ok(lm.id)
+            })
+            .collect()
+    }
+
+    /// Records the observed distance of every landmark from each of `poses`, a short
+    /// camera path with known relative motion (a synthetic aperture), for
+    /// [`Self::probability_aperture_at`]. Replaces any previous aperture; the landmarks'
+    /// own `observed_dist` is left untouched. A non-finite pose is rejected before
+    /// anything is recorded.
+    pub fn observe_aperture(&mut self, poses: &[[f32; 2]]) -> Result<(), Error> {
+        for &pose in poses {
+            check_position(pose)?;
+        }
+        let Some(&origin) = poses.first() else {
+            self.aperture.clear();
+            return Ok(());
+        };
+        self.aperture = poses
+            .iter()
+            .map(|&[px, py]| {
+                let baseline = [px - origin[0], py - origin[1]];
+                let dists = self.landmarks.iter().map(|lm| lm.distance_to(px, py)).collect();
+                (baseline, dists)
+            })
+            .collect();
+        Ok(())
+    }
+
+    /// `|ψ|²` of the aperture recorded by [`Self::observe_aperture`] for the first pose
+    /// being at `(x, y)`: the complex terms of every landmark from every pose, each
+    /// shifted by its baseline, are summed coherently. Only the true position puts all
+    /// of them in phase, so the peak is much tighter than from a single pose. Zero
+    /// without an aperture.
+    pub fn probability_aperture_at(&self, x: f32, y: f32) -> f64 {
+        let (mut re, mut im) = (0.0, 0.0);
+        for ([bx, by], dists) in &self.aperture {
+            let [r, i] = self.complex_field_with(x + bx, y + by, 1.0, dists.iter().copied());
+            re += r;
+            im += i;
+        }
+        re * re + im * im
+    }
+
+    /// `observe` for one camera of a team sharing this landmark map: the ranges from
+    /// `(true_cam_x, true_cam_y)` are stored for `agent_id` alone (replacing its
+    /// previous ones), leaving the landmarks' own `observed_dist` and the other agents
+    /// untouched. Landmarks added later are not part of this agent's field until it
+    /// observes again. A non-finite camera position is rejected.
+    pub fn observe_for_agent(&mut self, agent_id: u32, true_cam_x: f32, true_cam_y: f32) -> Result<(), Error> {
+        check_position([true_cam_x, true_cam_y])?;
+        let dists = self.landmarks.iter().map(|lm| lm.distance_to(true_cam_x, true_cam_y)).collect();
+        self.agents.insert(agent_id, dists);
+        Ok(())
+    }
+
+    /// `probability_at` for the camera hypothesis of `agent_id`, from its own observed
+    /// ranges and the shared landmarks. Zero for an agent that has not observed.
+    pub fn probability_at_for_agent(&self, agent_id: u32, x: f32, y: f32) -> f64 {
+        let Some(dists) = self.agents.get(&agent_id) else {
+            return 0.0;
+        };
+        let [re, im] = self.complex_field_with(x, y, 1.0, dists.iter().copied());
+        re * re + im * im
+    }
+
+    /// Ids of the agents that have observed, in ascending order.
+    pub fn agent_ids(&self) -> impl Iterator<Item = u32> + '_ {
+        self.agents.keys().copied()
+    }
+
+    /// Forgets the observations of `agent_id`; returns whether it had any.
+    pub fn remove_agent(&mut self, agent_id: u32) -> bool {
+        self.agents.remove(&agent_id).is_some()
+    }
+
+    /// `observe` with each simulated range corrupted by `noise`. Seed the generator (e.g.
+    /// `StdRng::seed_from_u64`) for reproducible benchmarks. A non-finite camera position
+    /// is rejected.
+    #[cfg(feature = "rand")]
+    pub fn observe_noisy(&mut self, cam: [f32; 2], noise: &NoiseModel, rng: &mut impl rand::Rng) -> Result<(), Error> {
+        self.observe_ranges(cam)?;
+        for lm in &mut self.landmarks {
+            lm.observed_dist = noise.corrupt(lm.observed_dist, rng);
+        }
+        Ok(())
+    }
+
+    /// `observe` with detector dropout: each landmark is detected with probability
+    /// `detection_prob` and updated as by `observe`; the others are marked unobserved
+    /// and left out of the field until a later observation detects them. A non-finite
+    /// camera position or a `detection_prob` outside `[0, 1]` is rejected.
+    #[cfg(feature = "rand")]
+    pub fn observe_with_detection(&mut self, cam: [f32; 2], detection_prob: f32, rng: &mut impl rand::Rng) -> Result<(), Error> {
+        check_position(cam)?;
+        if !(0.0..=1.0).contains(&detection_prob) {
+            return Err(Error::InvalidParameter(format!("detection_prob {} must be within [0, 1]", detection_prob)));
+        }
+        for lm in &mut self.landmarks {
+            if rng.gen::<f32>() < detection_prob {
+                lm.observed_dist = lm.distance_to(cam[0], cam[1]);
+                lm.last_seen = self.time;
+                lm.observed = 1;
+            } else {
+                lm.observed = 0;
+            }
+        }
+        Ok(())
+    }
+
+    /// Advances the core clock `time` by `dt`, evolving the phases the way the renderer's
+    /// shader does over its `time` uniform (wavefront propagation and Doppler terms),
+    /// without the decoherence applied by [`Self::step`]. `dt` must be finite and
+    /// non-negative.
+    pub fn advance_time(&mut self, dt: f32) -> Result<(), Error> {
+        check_non_negative("dt", dt)?;
+        self.time += dt;
+        Ok(())
+    }
+
+    /// Advances `time` by `dt` and applies [`Self::decoherence`] to every landmark not
+    /// observed at the current time (`last_seen < time`): its confidence decays and its
+    /// phase offset takes a Gaussian random-walk step, so stale landmarks fade and
+    /// stop interfering coherently, like the renderer's feedback decay. The steps are
+    /// pseudo-random but reproducible: they depend only on the landmark id and time.
+    /// `dt` must be finite and non-negative.
+    pub fn step(&mut self, dt: f32) -> Result<(), Error> {
+        check_non_negative("dt", dt)?;
+        let Decoherence { confidence_rate, phase_diffusion } = self.decoherence;
+        let confidence_scale = math::exp(-confidence_rate * dt);
+        let phase_sigma = (2.0 * phase_diffusion * dt).sqrt();
+
+        for lm in &mut self.landmarks {
+            if lm.last_seen >= self.time {
+                continue;
+            }
+            lm.confidence *= confidence_scale;
+            if phase_sigma > 0.0 {
+                let phase = lm.phase_offset + phase_sigma * hashed_normal(lm.id, self.time);
+                lm.phase_offset = phase.rem_euclid(core::f32::consts::TAU);
+            }
+        }
+        self.time += dt;
+        Ok(())
+    }
+
+    /// Blends a new observation from the camera position into the current observed
+    /// distances: `observed = mix(new, previous, feedback_strength)`, the CPU
+    /// counterpart of the renderer's temporal feedback. Call `observe` first to
+    /// initialize the observations. Every landmark is marked observed, as by `observe`.
+    /// A non-finite camera position is rejected.
+    pub fn observe_accumulate(&mut self, cam_x: f32, cam_y: f32) -> Result<(), Error> {
+        check_position([cam_x, cam_y])?;
+        let alpha = self.feedback_strength;
+        for lm in &mut self.landmarks {
+            let dist = lm.distance_to(cam_x, cam_y);
+            lm.observed_dist = dist * (1.0 - alpha) + lm.observed_dist * alpha;
+            lm.last_seen = self.time;
+            lm.observed = 1;
+        }
+        Ok(())
+    }
+
+    /// Replays a recorded run from a CSV file of `time,cam_x,cam_y` rows: the first pose
+    /// is observed, later ones are blended in with `observe_accumulate`.
+    ///
+    /// A leading header row, blank lines and `#` comments are skipped. Returns the
+    /// camera path, or an `InvalidData` error naming the first malformed row.
+    #[cfg(feature = "std")]
+    pub fn replay_trajectory_csv(&mut self, path: impl AsRef<Path>) -> Result<Vec<[f32; 2]>, Error> {
+        let reader = BufReader::new(File::open(path)?);
+        let mut trajectory: Vec<[f32; 2]> = Vec::new();
+        let mut first_row = true;
+
+        for (line_no, line) in reader.lines().enumerate() {
+            let line = line?;
+            let row = line.trim();
+            if row.is_empty() || row.starts_with('#') {
+                continue;
+            }
+            let is_first_row = std::mem::replace(&mut first_row, false);
+
+            let fields: Vec<&str> = row.split(',').map(str::trim).collect();
+            let parsed: Result<Vec<f32>, _> = fields.iter().map(|f| f.parse::<f32>()).collect();
+
+            let cam = match (parsed, fields.len()) {
+                (Ok(values), 3) => [values[1], values[2]],
+                // ヘッダー行 (time,cam_x,cam_y) は最初の行のみ許容
+                (Err(_), 3) if is_first_row && fields[0].parse::<f32>().is_err() => continue,
+                _ => {
+                    return Err(Error::Io(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("line {}: expected `time,cam_x,cam_y`, got {:?}", line_no + 1, row),
+                    )))
+                }
+            };
+
+            if trajectory.is_empty() {
+                self.observe(cam[0], cam[1])?;
+            } else {
+                self.observe_accumulate(cam[0], cam[1])?;
+            }
+            trajectory.push(cam);
+        }
+
+        Ok(trajectory)
+    }
+
+    /// Appends the current [`Self::estimate_pose`] stamped with `time` to the recorded
+    /// trajectory and returns it; call it after each observation to track the path.
+    /// `None` (nothing recorded) without landmarks.
+    pub fn record_pose(&mut self) -> Option<TimedPose> {
+        let (position, probability) = self.estimate_pose()?;
+        let pose = TimedPose { time: self.time, position, probability };
+        self.trajectory.push(pose);
+        Some(pose)
+    }
+
+    /// Poses recorded by [`Self::record_pose`], oldest first.
+    pub fn trajectory(&self) -> &[TimedPose] {
+        &self.trajectory
+    }
+
+    pub fn clear_trajectory(&mut self) {
+        self.trajectory.clear();
+    }
+
+    /// Writes the recorded trajectory as `time,cam_x,cam_y` rows under a header, the
+    /// format read back by [`Self::replay_trajectory_csv`].
+    #[cfg(feature = "std")]
+    pub fn write_trajectory_csv(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "time,cam_x,cam_y")?;
+        for pose in &self.trajectory {
+            writeln!(writer, "{},{},{}", pose.time, pose.position[0], pose.position[1])?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Serializes the whole state (landmarks, parameters, prior, trajectory and
+    /// per-agent observations) to JSON, tagged with [`SNAPSHOT_VERSION`].
+    #[cfg(feature = "persist")]
+    pub fn to_json(&self) -> String {
+        let snapshot = Snapshot { version: SNAPSHOT_VERSION, core: self };
+        serde_json::to_string(&snapshot).expect("core is always serializable")
+    }
+
+    /// Restores a core saved by [`Self::to_json`].
+    #[cfg(feature = "persist")]
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let SnapshotVersion { version } = serde_json::from_str(json).map_err(|e| Error::InvalidParameter(e.to_string()))?;
+        check_snapshot_version(version)?;
+        let snapshot: Snapshot<Self> = serde_json::from_str(json).map_err(|e| Error::InvalidParameter(e.to_string()))?;
+        Ok(snapshot.core)
+    }
+
+    /// [`Self::to_json`] in the compact bincode format, for large maps.
+    #[cfg(feature = "persist")]
+    pub fn to_bincode(&self) -> Vec<u8> {
+        let snapshot = Snapshot { version: SNAPSHOT_VERSION, core: self };
+        bincode::serialize(&snapshot).expect("core is always serializable")
+    }
+
+    /// Restores a core saved by [`Self::to_bincode`].
+    #[cfg(feature = "persist")]
+    pub fn from_bincode(bytes: &[u8]) -> Result<Self, Error> {
+        let version: u32 = bincode::deserialize(bytes).map_err(|e| Error::InvalidParameter(e.to_string()))?;
+        check_snapshot_version(version)?;
+        let snapshot: Snapshot<Self> = bincode::deserialize(bytes).map_err(|e| Error::InvalidParameter(e.to_string()))?;
+        Ok(snapshot.core)
+    }
+
+    /// Adds a clone of `other`'s landmarks (map merge, e.g. from another robot). With
+    /// `merge_radius`, landmarks that end up closer than it are then fused by
+    /// [`Self::merge_close_landmarks`]. `self`'s wave number and other parameters are
+    /// kept, and the added landmarks get new handles.
+    ///
+    /// Like landmarks from `add_landmark`, the added ones have no aperture or per-agent
+    /// distances and stay out of those fields until the next `observe_aperture` or
+    /// `observe_for_agent`; a fused pair keeps the distances of its existing landmark.
+    pub fn append(&mut self, other: &QuantumSlamCore, merge_radius: Option<f32>) {
+        // 距離ベクトルは landmarks より短いまま (末尾の追加分は zip で除外される)
+        for &lm in &other.landmarks {
+            self.push_landmark(lm);
+        }
+        if let Some(radius) = merge_radius {
+            self.merge_close_landmarks(radius);
+        }
+    }
+
+    /// Imports `other`'s map (another session or robot) whose frame sits at `transform`
+    /// in this one. Each imported landmark within `merge_radius` of one of `self`'s
+    /// existing landmarks is fused into the nearest: the position becomes the
+    /// confidence-weighted mean and the confidences combine as independent evidence,
+    /// `1 - (1 - a)(1 - b)`; the kept landmark's handle and observation are unchanged.
+    /// The others are added with new handles. Returns the number of fused landmarks.
+    pub fn merge(&mut self, other: &QuantumSlamCore, transform: Pose2, merge_radius: f32) -> usize {
+        let existing = self.landmarks.len();
+        let mut fused = 0;
+
+        for &lm in &other.landmarks {
+            let lm = transform.apply_to_landmark(lm);
+            // 取り込み済みのランドマーク同士は融合しない
+            let nearest = self.landmarks[..existing]
+                .iter_mut()
+                .map(|kept| {
+                    let dx = kept.position[0] - lm.position[0];
+                    let dy = kept.position[1] - lm.position[1];
+                    (dx * dx + dy * dy, kept)
+                })
+                .filter(|(d2, _)| *d2 < merge_radius * merge_radius)
+                .min_by(|a, b| a.0.total_cmp(&b.0));
+
+            match nearest {
+                Some((_, kept)) => {
+                    let total = kept.confidence + lm.confidence;
+                    let w = if total > 0.0 { lm.confidence / total } else { 0.5 };
+                    kept.position[0] += (lm.position[0] - kept.position[0]) * w;
+                    kept.position[1] += (lm.position[1] - kept.position[1]) * w;
+                    kept.confidence = 1.0 - (1.0 - kept.confidence) * (1.0 - lm.confidence);
+                    kept.last_seen = kept.last_seen.max(lm.last_seen);
+                    fused += 1;
+                }
+                None => {
+                    self.push_landmark(lm);
+                }
+            }
+        }
+
+        fused
+    }
+
+    /// Fuses landmarks closer than `radius` to an earlier one into it: position and
+    /// observed distance become confidence-weighted means, confidence and `last_seen`
+    /// the larger of the two. The aperture and per-agent distances of the fused
+    /// landmarks are combined the same way. Returns the number of landmarks removed.
+    pub fn merge_close_landmarks(&mut self, radius: f32) -> usize {
+        let before = self.landmarks.len();
+        let mut merged: Vec<Landmark> = Vec::with_capacity(before);
+        // 元の index ごとの (融合先の index, 重み)。重み None はそのまま残ったもの
+        let mut sources: Vec<(usize, Option<f32>)> = Vec::with_capacity(before);
+
+        for lm in self.landmarks.drain(..) {
+            let target = merged.iter_mut().enumerate().find(|(_, kept)| {
+                let dx = kept.position[0] - lm.position[0];
+                let dy = kept.position[1] - lm.position[1];
+                dx * dx + dy * dy < radius * radius
+            });
+
+            match target {
+                Some((index, kept)) => {
+                    let total = kept.confidence + lm.confidence;
+                    // 両方の信頼度が0なら単純平均
+                    let w = if total > 0.0 { lm.confidence / total } else { 0.5 };
+                    kept.position[0] += (lm.position[0] - kept.position[0]) * w;
+                    kept.position[1] += (lm.position[1] - kept.position[1]) * w;
+                    kept.observed_dist += (lm.observed_dist - kept.observed_dist) * w;
+                    kept.confidence = kept.confidence.max(lm.confidence);
+                    kept.last_seen = kept.last_seen.max(lm.last_seen);
+                    sources.push((index, Some(w)));
+                }
+                None => {
+                    sources.push((merged.len(), None));
+                    merged.push(lm);
+                }
+            }
+        }
+
+        // aperture とエージェントの観測距離も同じ順序・同じ重みで融合する。
+        // 観測後に追加されたランドマーク (距離の無い末尾) は融合先にも距離を与えない
+        let per_pose = self.aperture.iter_mut().map(|(_, dists)| dists);
+        for dists in per_pose.chain(self.agents.values_mut()) {
+            let mut fused: Vec<f32> = Vec::with_capacity(merged.len());
+            for (&dist, &(index, w)) in dists.iter().zip(&sources) {
+                match w {
+                    Some(w) => fused[index] += (dist - fused[index]) * w,
+                    None => fused.push(dist),
+                }
+            }
+            *dists = fused;
+        }
+
+        self.landmarks = merged;
+        before - self.landmarks.len()
+    }
+
+    /// Moves every landmark to `f(position)`, e.g. to bring a map into another frame.
+    /// The observed distances no longer match the new positions and are cleared as by
+    /// [`Self::reset_observations`].
+    pub fn map_landmarks(&mut self, f: impl Fn([f32; 2]) -> [f32; 2]) {
+        for lm in &mut self.landmarks {
+            lm.position = f(lm.position);
+        }
+        self.reset_observations();
+    }
+
+    /// [`Self::map_landmarks`] with `p' = matrix * p + offset` (`matrix` row-major).
+    /// Landmark shapes are turned by the matrix's rotation, which is exact for
+    /// rotations with uniform scale.
+    pub fn transform_affine(&mut self, matrix: [[f32; 2]; 2], offset: [f32; 2]) {
+        self.map_landmarks(|[x, y]| [
+            matrix[0][0] * x + matrix[0][1] * y + offset[0],
+            matrix[1][0] * x + matrix[1][1] * y + offset[1],
+        ]);
+
+        let rotation = math::atan2(matrix[1][0], matrix[0][0]);
+        for lm in &mut self.landmarks {
+            lm.shape.orientation += rotation;
+        }
+    }
+
+    /// Seeds landmarks from a raster map (e.g. a floorplan): samples the image every
+    /// `step` pixels and adds a landmark wherever the luminance (`0.0..=1.0`) is below
+    /// `threshold`, at that pixel's center mapped into `bounds` (top row = `y_max`, see
+    /// [`pixel_to_world`]). Returns the number of landmarks added.
+    #[cfg(feature = "image")]
+    pub fn landmarks_from_image(
+        &mut self,
+        path: impl AsRef<Path>,
+        threshold: f32,
+        step: usize,
+        bounds: Bounds,
+    ) -> Result<usize, Error> {
+        let img = image::open(path)?.into_luma8();
+        let (width, height) = img.dimensions();
+        let before = self.landmarks.len();
+
+        for row in (0..height).step_by(step.max(1)) {
+            for col in (0..width).step_by(step.max(1)) {
+                let luminance = img.get_pixel(col, row)[0] as f32 / 255.0;
+                if luminance < threshold {
+                    let [x, y] = pixel_to_world(bounds, width, height, col, row);
+                    self.add_landmark(x, y)?;
+                }
+            }
+        }
+
+        Ok(self.landmarks.len() - before)
+    }
+
+    /// Clears every observed distance and marks every landmark unobserved while keeping
+    /// the landmark map, for a fresh localization pass.
+    pub fn reset_observations(&mut self) {
+        for lm in &mut self.landmarks {
+            lm.observed_dist = 0.0;
+            lm.observed = 0;
+        }
+    }
+
+    /// Time-based forgetting: each landmark's confidence is halved for every
+    /// `half_life` elapsed since `last_seen`, `0.5^((now - last_seen) / half_life)` in
+    /// total. The decay already applied is remembered per landmark, so calling this
+    /// again for the same `now` changes nothing and many small steps equal one large
+    /// one; an observation restarts the clock at its `last_seen`. `now` must be finite
+    /// and `half_life` finite and positive.
+    pub fn decay_confidence_by_age(&mut self, now: f32, half_life: f32) -> Result<(), Error> {
+        check_finite("now", now)?;
+        if !(half_life.is_finite() && half_life > 0.0) {
+            return Err(Error::InvalidParameter(format!("half_life {} must be finite and positive", half_life)));
+        }
+        for lm in &mut self.landmarks {
+            // 前回の減衰 (その後に観測されていれば観測) 以降の経過時間だけ減衰させる
+            let since = lm.last_seen.max(lm.decayed_until);
+            if now > since {
+                lm.confidence *= math::powf(0.5f32, (now - since) / half_life);
+                lm.decayed_until = now;
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets (or with `None` clears) the camera position prior moved by [`Self::predict`].
+    pub fn set_prior(&mut self, prior: Option<ProbabilityField>) {
+        self.prior = prior;
+    }
+
+    pub fn prior(&self) -> Option<&ProbabilityField> {
+        self.prior.as_ref()
+    }
+
+    /// Prediction step from odometry: `(dx, dy)` is the motion in the camera frame
+    /// (x along `heading`) and `dtheta` the turn, applied to `heading` after the move.
+    /// The prior, if set, is shifted by the motion and blurred by a Gaussian of
+    /// standard deviation `noise` (world units; 0 shifts without blurring). Mass moved
+    /// outside the prior's bounds is lost. The motion must be finite and `noise`
+    /// finite and non-negative.
+    pub fn predict(&mut self, dx: f32, dy: f32, dtheta: f32, noise: f32) -> Result<(), Error> {
+        check_finite("dx", dx)?;
+        check_finite("dy", dy)?;
+        check_finite("dtheta", dtheta)?;
+        check_non_negative("noise", noise)?;
+        let (sin, cos) = math::sin_cos(self.heading);
+        let motion = [cos * dx - sin * dy, sin * dx + cos * dy];
+        self.heading = wrap_angle(self.heading + dtheta);
+
+        if let Some(prior) = &mut self.prior {
+            *prior = prior.translated(motion).blurred(noise);
+        }
+        Ok(())
+    }
+
+    /// Update step: the prior times the current field (the likelihood) on the prior's
+    /// grid, normalized to sum to 1. Feed it back with `set_prior` to keep filtering.
+    /// `None` without a prior.
+    pub fn posterior(&self) -> Option<ProbabilityField> {
+        let prior = self.prior.as_ref()?;
+        let mut posterior = ProbabilityField::from_fn(prior.bounds, prior.nx, prior.ny, |x, y| self.probability_at(x, y));
+        for (p, prior) in posterior.values.iter_mut().zip(&prior.values) {
+            *p *= prior;
+        }
+        Some(posterior.normalized())
+    }
+
+    /// Bayesian update of the confidence of the landmark with handle `id`, read as the
+    /// probability that it is real: `weight` (a log-likelihood ratio) is added to its
+    /// log-odds. Positive weights (consistent observations) raise it towards 1, negative
+    /// ones (contradictions) lower it towards 0. The result stays within
+    /// `[0.001, 0.999]` so later evidence can still move it.
+    pub fn reinforce(&mut self, id: LandmarkId, weight: f32) -> Result<(), Error> {
+        let index = self.landmark_index(id).ok_or(Error::UnknownLandmark(id))?;
+        let lm = &mut self.landmarks[index];
+        let p = lm.confidence.clamp(CONFIDENCE_LIMIT, 1.0 - CONFIDENCE_LIMIT);
+        let log_odds = math::ln(p / (1.0 - p)) + weight;
+        lm.confidence = (1.0 / (1.0 + math::exp(-log_odds))).clamp(CONFIDENCE_LIMIT, 1.0 - CONFIDENCE_LIMIT);
+        Ok(())
+    }
+
+    /// Scales every landmark's confidence by `1 - rate` (`rate` in `[0, 1]`), so
+    /// landmarks that are not reinforced fade out of the interference pattern.
+    pub fn decay_all(&mut self, rate: f32) -> Result<(), Error> {
+        if !(0.0..=1.0).contains(&rate) {
+            return Err(Error::InvalidParameter(format!("Decay rate {} must be within [0, 1]", rate)));
+        }
+        for lm in &mut self.landmarks {
+            lm.confidence *= 1.0 - rate;
+        }
+        Ok(())
+    }
+
+    /// `observe` for a directional sensor facing `heading` (radians).
+    ///
+    /// Each landmark's `phase_offset` is set from its bearing relative to the heading:
+    /// 0 straight ahead, rising to π directly behind, which yields a lobed field. A
+    /// non-finite camera position or heading is rejected.
+    pub fn observe_directional(&mut self, true_cam_x: f32, true_cam_y: f32, heading: f32) -> Result<(), Error> {
+        check_finite("heading", heading)?;
+        self.observe_ranges([true_cam_x, true_cam_y])?;
+
+        for lm in &mut self.landmarks {
+            let dx = lm.position[0] - true_cam_x;
+            let dy = lm.position[1] - true_cam_y;
+            let relative_bearing = math::atan2(dy, dx) - heading;
+            lm.phase_offset = 0.5 * core::f32::consts::PI * (1.0 - math::cos(relative_bearing));
+        }
+        Ok(())
+    }
+
+    /// Bearing-only observation from a camera at `true_cam` facing `heading` (radians),
+    /// for sensors that give directions but no ranges: records each landmark's bearing
+    /// relative to the heading in `observed_bearing` and switches the field to
+    /// [`SensorModel::Bearing`]. The observed ranges are left untouched. A non-finite
+    /// camera position or heading is rejected.
+    pub fn observe_bearing(&mut self, true_cam: [f32; 2], heading: f32) -> Result<(), Error> {
+        check_position(true_cam)?;
+        check_finite("heading", heading)?;
+        self.sensor_model = SensorModel::Bearing;
+        self.heading = heading;
+        for lm in &mut self.landmarks {
+            lm.observed_bearing = lm.bearing_from(true_cam[0], true_cam[1], heading);
+            lm.last_seen = self.time;
+            lm.observed = 1;
+        }
+        Ok(())
+    }
+
+    /// Range-bearing observation, the usual 2D SLAM sensor: `observe` and
+    /// `observe_bearing` from the same pose, with the field switched to
+    /// [`SensorModel::RangeBearing`]. A non-finite camera position or heading is rejected.
+    pub fn observe_range_bearing(&mut self, true_cam: [f32; 2], heading: f32) -> Result<(), Error> {
+        check_finite("heading", heading)?;
+        self.observe_ranges(true_cam)?;
+        self.observe_bearing(true_cam, heading)?;
+        self.sensor_model = SensorModel::RangeBearing;
+        Ok(())
+    }
+
+    /// Simulated 2D lidar scan from `cam` facing `heading` (radians): `n_beams` rays
+    /// spaced evenly over a full turn counter-clockwise from the heading, each cast
+    /// against the landmarks as discs of [`Self::landmark_radius`]. Returns the range
+    /// to the nearest hit per beam, or `max_range` for beams that hit nothing.
+    pub fn simulate_scan(&self, cam: [f32; 2], heading: f32, n_beams: usize, max_range: f32) -> Vec<f32> {
+        let r2 = self.landmark_radius * self.landmark_radius;
+        (0..n_beams)
+            .map(|i| {
+                let angle = heading + core::f32::consts::TAU * i as f32 / n_beams as f32;
+                let (sin, cos) = math::sin_cos(angle);
+                self.landmarks
+                    .iter()
+                    .filter_map(|lm| {
+                        // 光線 cam + t (cos, sin) と円盤の交差: t² + 2bt + c = 0
+                        let fx = cam[0] - lm.position[0];
+                        let fy = cam[1] - lm.position[1];
+                        let b = fx * cos + fy * sin;
+                        let c = fx * fx + fy * fy - r2;
+                        if c <= 0.0 {
+                            return Some(0.0); // 円盤の内側
+                        }
+                        let disc = b * b - c;
+                        let t = -b - disc.sqrt();
+                        (disc >= 0.0 && t >= 0.0).then_some(t)
+                    })
+                    .fold(max_range, f32::min)
+            })
+            .collect()
+    }
+
+    /// Feeds a scan laid out as by [`Self::simulate_scan`] back as range observations,
+    /// closing the simulation loop: each beam shorter than `max_range` is attributed to
+    /// the landmark whose disc its end point touches, and that landmark's range is
+    /// its shortest beam plus [`Self::landmark_radius`]. Landmarks no beam reached
+    /// (occluded or out of range) are marked unobserved.
+    ///
+    /// The camera position, heading and `max_range` must be finite, and each range
+    /// non-negative (infinite ranges count as misses).
+    pub fn observe_scan(&mut self, cam: [f32; 2], heading: f32, ranges: &[f32], max_range: f32) -> Result<(), Error> {
+        check_position(cam)?;
+        check_finite("heading", heading)?;
+        check_non_negative("max_range", max_range)?;
+        if let Some(&range) = ranges.iter().find(|r| r.is_nan() || **r < 0.0) {
+            return Err(Error::InvalidParameter(format!("Scan range {} must be non-negative", range)));
+        }
+        self.sensor_model = SensorModel::Range;
+        let mut nearest = vec![f32::INFINITY; self.landmarks.len()];
+        // 円盤の表面上の点でも丸め誤差で半径をわずかに超えるため余裕を持たせる
+        let tolerance = 1e-3 * self.landmark_radius + 1e-4;
+
+        for (i, &range) in ranges.iter().enumerate() {
+            if range >= max_range {
+                continue;
+            }
+            let angle = heading + core::f32::consts::TAU * i as f32 / ranges.len() as f32;
+            let (sin, cos) = math::sin_cos(angle);
+            let end = [cam[0] + range * cos, cam[1] + range * sin];
+            let hit = self
+                .landmarks
+                .iter()
+                .map(|lm| {
+                    let dx = end[0] - lm.position[0];
+                    let dy = end[1] - lm.position[1];
+                    (dx * dx + dy * dy).sqrt()
+                })
+                .enumerate()
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+            if let Some((index, dist)) = hit {
+                if dist <= self.landmark_radius + tolerance {
+                    nearest[index] = nearest[index].min(range);
+                }
+            }
+        }
+
+        for (lm, range) in self.landmarks.iter_mut().zip(nearest) {
+            if range.is_finite() {
+                lm.observed_dist = range + self.landmark_radius;
+                lm.last_seen = self.time;
+                lm.observed = 1;
+            } else {
+                lm.observed = 0;
+            }
+        }
+        Ok(())
+    }
+
+    /// `observe` that also sets a range-dependent carrier phase on each landmark:
+    /// `phase_offset = (k * observed_dist) mod 2π`.
+    ///
+    /// The carrier phase is not compensated, so unlike after `observe` the landmarks are
+    /// generally out of phase at the true pose (zero residual): each term keeps its own
+    /// offset there, `|ψ|²` at the camera is `|Σ w e^{i k d}|²` rather than `(Σ w)²`, and
+    /// the peak can move off the camera. Use `observe` when the field should peak at the
+    /// observed position. A non-finite camera position is rejected.
+    pub fn observe_with_phase(&mut self, cam: [f32; 2]) -> Result<(), Error> {
+        self.observe_ranges(cam)?;
+
+        let k = self.wave_number as f32;
+        for lm in &mut self.landmarks {
+            lm.phase_offset = (lm.wave_number_or(k) * lm.observed_dist).rem_euclid(core::f32::consts::TAU);
+        }
+        Ok(())
+    }
+
+    /// Indices of the landmarks within `max_range` of `cam` (sensor range gating).
+    pub fn visible_landmarks(&self, cam: [f32; 2], max_range: f32) -> Vec<usize> {
+        self.landmarks
+            .iter()
+            .enumerate()
+            .filter(|(_, lm)| {
+                let dx = lm.position[0] - cam[0];
+                let dy = lm.position[1] - cam[1];
+                dx * dx + dy * dy <= max_range * max_range
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// `|ψ|²` at `(x, y)`, with each landmark contributing
+    /// `amplitude * confidence * e^{-decay_factor |r|} e^{i (k r + phase_offset)}` for the
+    /// range residual `r` (with the Doppler term `k * radial_velocity * time` added to the
+    /// phase); the same field as the renderer's compute shader.
+    pub fn probability_at(&self, x: f32, y: f32) -> f64 {
+        if self.sensor_model != SensorModel::Range {
+            let [re, im] = self.complex_field_at(x, y);
+            return re * re + im * im;
+        }
+
+        // 1点だけならランドマークを詰め直すより逐次ループの方が速い (格子評価は field_sampler)
+        self.probability_with_kernel(x, y, &PlaneWave)
+    }
+
+    // probability_at (PlaneWave) の、位置に依らないランドマークごとの値を LANES 個ずつ詰めたもの
+    fn landmark_lanes(&self) -> Vec<LandmarkLanes> {
+        self.pack_lanes(&self.landmarks.iter().collect::<Vec<_>>())
+    }
+
+    fn pack_lanes(&self, landmarks: &[&Landmark]) -> Vec<LandmarkLanes> {
+        let k_default = self.wave_number as f32;
+        landmarks
+            .chunks(LANES)
+            .map(|chunk| {
+                // 余ったレーンは weight = 0 で寄与しない
+                let mut position = [[0.0; LANES]; 2];
+                let mut angle = [0.0; LANES];
+                let mut inv_aspect = [1.0; LANES];
+                let mut observed_dist = [0.0; LANES];
+                let mut wave_number = [0.0; LANES];
+                let mut phase_offset = [0.0; LANES];
+                let mut weight = [0.0; LANES];
+                let mut sigma = [0.0; LANES];
+                let mut decay_matrix = [[0.0; LANES]; 3];
+                let mut anisotropic = [0.0; LANES];
+                for (i, lm) in chunk.iter().enumerate() {
+                    position[0][i] = lm.position[0];
+                    position[1][i] = lm.position[1];
+                    angle[i] = lm.shape.orientation;
+                    inv_aspect[i] = 1.0 / lm.shape.aspect;
+                    observed_dist[i] = lm.front_at(self.time, self.propagation_speed);
+                    let k = lm.wave_number_or(k_default);
+                    wave_number[i] = k;
+                    phase_offset[i] = lm.phase_at(k, self.time);
+                    weight[i] = lm.weight(self.decay_factor);
+                    sigma[i] = lm.sigma;
+                    let m = lm.decay_matrix;
+                    decay_matrix[0][i] = m[0][0];
+                    decay_matrix[1][i] = 0.5 * (m[0][1] + m[1][0]);
+                    decay_matrix[2][i] = m[1][1];
+                    anisotropic[i] = if m != [[0.0; 2]; 2] { f32::from_bits(u32::MAX) } else { 0.0 };
+                }
+                let (sin, cos) = sin_cos_lanes(angle.into());
+                LandmarkLanes {
+                    position: position.map(f32x8::from),
+                    orientation: [sin, cos],
+                    inv_aspect: inv_aspect.into(),
+                    observed_dist: observed_dist.into(),
+                    wave_number: wave_number.into(),
+                    phase_offset: phase_offset.into(),
+                    weight: weight.into(),
+                    sigma: sigma.into(),
+                    decay_matrix: decay_matrix.map(f32x8::from),
+                    anisotropic: anisotropic.into(),
+                }
+            })
+            .collect()
+    }
+
+    // kernel_sums(PlaneWave) を LANES 個のランドマークずつ SIMD で求める
+    fn plane_wave_sums(&self, lanes: &[LandmarkLanes], x: f32, y: f32) -> [f32; 2] {
+        let (x, y) = (f32x8::splat(x), f32x8::splat(y));
+        let isotropic = f32x8::splat(self.decay_factor);
+        let one = f32x8::ONE;
+        let mut re_acc = f32x8::ZERO;
+        let mut im_acc = f32x8::ZERO;
+
+        for chunk in lanes {
+            let dx = x - chunk.position[0];
+            let dy = y - chunk.position[1];
+            let [sin_o, cos_o] = chunk.orientation;
+            // distance_and_gradient と同じ楕円距離
+            let u = (cos_o * dx + sin_o * dy) * chunk.inv_aspect;
+            let v = cos_o * dy - sin_o * dx;
+            let residual = (u * u + v * v).sqrt() - chunk.observed_dist;
+
+            // decay_and_gradient と同じ減衰率 (ûᵀ M û、sigma で広げる)
+            let [m00, m01, m11] = chunk.decay_matrix;
+            let rho2 = dx * dx + dy * dy;
+            let quadratic = (m00 * dx * dx + f32x8::splat(2.0) * m01 * dx * dy + m11 * dy * dy) / rho2;
+            let at_landmark = f32x8::splat(0.5) * (m00 + m11);
+            let quadratic = rho2.cmp_gt(f32x8::ZERO).blend(quadratic, at_landmark);
+            let rate = chunk.anisotropic.blend(quadratic, isotropic);
+            let spread = chunk.sigma * rate;
+            let rate = rate / (one + spread * spread).sqrt();
+
+            let amp = chunk.weight * exp_lanes(-rate * residual.abs());
+            let (sin, cos) = sin_cos_lanes(chunk.wave_number * residual + chunk.phase_offset);
+            re_acc += amp * cos;
+            im_acc += amp * sin;
+        }
+
+        // レーンの和は固定の順序で (reduce_add の順序は SIMD の実装ごとに異なる)
+        let sum = |v: f32x8| v.to_array().iter().sum();
+        [sum(re_acc), sum(im_acc)]
+    }
+
+    // 格子評価用の probability_at。Range ではランドマークの詰め直しを一度だけ行う
+    fn field_sampler(&self) -> impl Fn(f32, f32) -> f64 + '_ {
+        let lanes = if self.sensor_model == SensorModel::Range { self.landmark_lanes() } else { Vec::new() };
+        move |x, y| {
+            if self.sensor_model != SensorModel::Range {
+                return self.probability_at(x, y);
+            }
+            let [re_sum, im_sum] = self.plane_wave_sums(&lanes, x, y);
+            (re_sum * re_sum + im_sum * im_sum) as f64
+        }
+    }
+
+    /// Builds a [`SpatialIndex`] of cells at most `cell_size` wide over `bounds` for
+    /// [`Self::probability_at_indexed`]. A landmark's term `w e^{-rate |residual|}` is
+    /// only large near the ring at its observed distance, so each cell keeps just the
+    /// landmarks whose ring, widened until the envelope falls to `epsilon`, crosses it;
+    /// the skipped terms change the amplitude by at most `epsilon` each.
+    pub fn spatial_index(&self, bounds: Bounds, cell_size: f32, epsilon: f32) -> Result<SpatialIndex, Error> {
+        let (x_min, x_max, y_min, y_max) = bounds;
+        if !(cell_size.is_finite() && cell_size > 0.0) {
+            return Err(Error::InvalidParameter(format!("Cell size {} must be finite and positive", cell_size)));
+        }
+        if !(epsilon.is_finite() && epsilon > 0.0) {
+            return Err(Error::InvalidParameter(format!("Culling epsilon {} must be finite and positive", epsilon)));
+        }
+        if !(x_max > x_min && y_max > y_min) {
+            return Err(Error::InvalidParameter(format!("Bounds {:?} are empty", bounds)));
+        }
+        // セルの辺は cell_size 以下
+        let nx = ((x_max - x_min) / cell_size).floor() as usize + 1;
+        let ny = ((y_max - y_min) / cell_size).floor() as usize + 1;
+        let (cell_w, cell_h) = ((x_max - x_min) / nx as f32, (y_max - y_min) / ny as f32);
+
+        let mut members: Vec<Vec<usize>> = vec![Vec::new(); nx * ny];
+        for (i, lm) in self.landmarks.iter().enumerate() {
+            let weight = lm.weight(self.decay_factor);
+            if weight <= epsilon {
+                continue;
+            }
+            // 包絡が epsilon を超える残差の幅 (最も緩い方向の減衰率で)
+            let m = lm.decay_matrix;
+            let rate = if m == [[0.0; 2]; 2] {
+                self.decay_factor
+            } else {
+                let (mean, half) = (0.5 * (m[0][0] + m[1][1]), 0.5 * (m[0][0] - m[1][1]));
+                let m_xy = 0.5 * (m[0][1] + m[1][0]);
+                mean - (half * half + m_xy * m_xy).sqrt()
+            };
+            let rate = rate / (1.0 + (lm.sigma * rate) * (lm.sigma * rate)).sqrt();
+            let reach = if rate > 0.0 { math::ln(weight / epsilon) / rate } else { f32::INFINITY };
+
+            // 楕円距離はユークリッド距離の [min(1, 1/aspect), max(1, 1/aspect)] 倍
+            let inv_aspect = 1.0 / lm.shape.aspect;
+            let (scale_lo, scale_hi) = (inv_aspect.min(1.0), inv_aspect.max(1.0));
+            let front = lm.front_at(self.time, self.propagation_speed);
+            let (inner, outer) = (front - reach, front + reach);
+            let radius = outer / scale_lo;
+            let [px, py] = lm.position;
+            let ix_range = cell_span(px - radius - x_min, px + radius - x_min, cell_w, nx);
+            let iy_range = cell_span(py - radius - y_min, py + radius - y_min, cell_h, ny);
+            for iy in iy_range {
+                let (y0, y1) = (y_min + iy as f32 * cell_h, y_min + (iy + 1) as f32 * cell_h);
+                for ix in ix_range.clone() {
+                    let (x0, x1) = (x_min + ix as f32 * cell_w, x_min + (ix + 1) as f32 * cell_w);
+                    let near = [(px.clamp(x0, x1) - px), (py.clamp(y0, y1) - py)];
+                    let far = [(x0 - px).abs().max((x1 - px).abs()), (y0 - py).abs().max((y1 - py).abs())];
+                    let rho_min = (near[0] * near[0] + near[1] * near[1]).sqrt();
+                    let rho_max = (far[0] * far[0] + far[1] * far[1]).sqrt();
+                    if rho_max * scale_hi >= inner && rho_min * scale_lo <= outer {
+                        members[iy * nx + ix].push(i);
+                    }
+                }
+            }
+        }
+
+        let pack = |indices: &[usize]| {
+            let landmarks: Vec<&Landmark> = indices.iter().map(|&i| &self.landmarks[i]).collect();
+            (landmarks.iter().map(|lm| lm.id).collect(), self.pack_lanes(&landmarks))
+        };
+        Ok(SpatialIndex {
+            bounds,
+            nx,
+            ny,
+            cells: members.iter().map(|indices| pack(indices)).collect(),
+            all: pack(&(0..self.landmarks.len()).collect::<Vec<_>>()),
+        })
+    }
+
+    /// `probability_at` summing only the landmarks `index` keeps for the cell around
+    /// `(x, y)`. Other sensor models ignore the index.
+    pub fn probability_at_indexed(&self, index: &SpatialIndex, x: f32, y: f32) -> f64 {
+        if self.sensor_model != SensorModel::Range {
+            return self.probability_at(x, y);
+        }
+        let [re_sum, im_sum] = self.plane_wave_sums(&index.cell_at(x, y).1, x, y);
+        (re_sum * re_sum + im_sum * im_sum) as f64
+    }
+
+    /// `evaluate_grid` through [`Self::probability_at_indexed`].
+    pub fn evaluate_grid_indexed(&self, index: &SpatialIndex, bounds: Bounds, nx: usize, ny: usize) -> ProbabilityField {
+        ProbabilityField::from_fn(bounds, nx, ny, |x, y| self.probability_at_indexed(index, x, y))
+    }
+
+    /// The range-residual field of `probability_at` (the [`SensorModel::Range`] field)
+    /// with every intermediate value in f64. At large wave numbers the f32 phase
+    /// `k * residual` loses enough precision to show as artifacts; this is the
+    /// reference to validate the f32 kernels and the GPU against.
+    pub fn probability_at_f64(&self, x: f64, y: f64) -> f64 {
+        let (k, decay, t, speed) = (self.wave_number, self.decay_factor as f64, self.time as f64, self.propagation_speed as f64);
+        let [re, im] = self
+            .landmarks
+            .iter()
+            .map(|lm| lm.range_term_f64(x, y, k, decay, t, speed))
+            .fold([0.0; 2], |[re, im], [r, i]| [re + r, im + i]);
+        re * re + im * im
+    }
+
+    /// `evaluate_grid` through [`Self::probability_at_f64`], with the cell centers also
+    /// computed in f64.
+    pub fn evaluate_grid_f64(&self, bounds: Bounds, nx: usize, ny: usize) -> ProbabilityField {
+        let (x_min, x_max, y_min, y_max) = bounds;
+        let cell_w = (x_max as f64 - x_min as f64) / nx as f64;
+        let cell_h = (y_max as f64 - y_min as f64) / ny as f64;
+        let mut values = Vec::with_capacity(nx * ny);
+        for iy in 0..ny {
+            let y = y_min as f64 + (iy as f64 + 0.5) * cell_h;
+            for ix in 0..nx {
+                values.push(self.probability_at_f64(x_min as f64 + (ix as f64 + 0.5) * cell_w, y));
+            }
+        }
+        ProbabilityField { bounds, nx, ny, values }
+    }
+
+    /// `probability_at` (range residuals) with each landmark's term given by `kernel`.
+    pub fn probability_with_kernel(&self, x: f32, y: f32, kernel: &(impl WaveKernel + ?Sized)) -> f64 {
+        let [re_sum, im_sum] = self.kernel_sums(x, y, kernel);
+        (re_sum * re_sum + im_sum * im_sum) as f64
+    }
+
+    /// Complex field `[re, im]` at `(x, y)` with each landmark's term given by `kernel`.
+    pub fn complex_field_with_kernel(&self, x: f32, y: f32, kernel: &(impl WaveKernel + ?Sized)) -> [f64; 2] {
+        let [re_sum, im_sum] = self.kernel_sums(x, y, kernel);
+        [re_sum as f64, im_sum as f64]
+    }
+
+    // Σ weight * envelope * e^{i (phase + phase_offset)} (f32 のまま合計する)
+    fn kernel_sums(&self, x: f32, y: f32, kernel: &(impl WaveKernel + ?Sized)) -> [f32; 2] {
+        let mut re_sum = 0.0;
+        let mut im_sum = 0.0;
+
+        for lm in &self.landmarks {
+            let distance = lm.distance_to(x, y);
+            let input = KernelInput {
+                residual: distance - lm.front_at(self.time, self.propagation_speed),
+                distance,
+                wave_number: lm.wave_number_or(self.wave_number as f32),
+                decay: lm.decay_rate(x, y, self.decay_factor),
+            };
+            let phase = kernel.phase(&input) + lm.phase_at(input.wave_number, self.time);
+            let amp = lm.weight(self.decay_factor) * kernel.envelope(&input);
+
+            re_sum += amp * math::cos(phase);
+            im_sum += amp * math::sin(phase);
+        }
+
+        [re_sum, im_sum]
+    }
+
+    /// Complex wave `[re, im]` at `(x, y)` and time `t`. Each landmark's ring sits at
+    /// `observed_dist + propagation_speed * t`, with the residual to it driving both the
+    /// phase and the envelope, so the fronts travel outward from the observed ranges and
+    /// `|ψ|²` moves with them. `probability_at` and the other field methods use the core
+    /// clock `time` for `t`, as the renderer uses its `time` uniform.
+    pub fn wave_at_time(&self, x: f32, y: f32, t: f32) -> [f64; 2] {
+        let mut re_sum = 0.0;
+        let mut im_sum = 0.0;
+
+        for lm in &self.landmarks {
+            let hypo_dist = lm.distance_to(x, y);
+
+            let k = lm.wave_number_or(self.wave_number as f32);
+            let residual = hypo_dist - lm.front_at(t, self.propagation_speed);
+            let phase = k * residual + lm.phase_at(k, t);
+            let amp = lm.weight(self.decay_factor) * math::exp(-lm.decay_rate(x, y, self.decay_factor) * residual.abs());
+
+            re_sum += amp * math::cos(phase);
+            im_sum += amp * math::sin(phase);
+        }
+
+        [re_sum as f64, im_sum as f64]
+    }
+
+    /// Complex field `ψ = [re, im]` at `(x, y)`; `probability_at` is `|ψ|²`.
+    pub fn complex_field_at(&self, x: f32, y: f32) -> [f64; 2] {
+        match self.sensor_model {
+            SensorModel::Range => {
+                self.complex_field_with(x, y, 1.0, self.landmarks.iter().map(|lm| lm.observed_dist))
+            }
+            SensorModel::Bearing => {
+                let terms = self.landmarks
+                    .iter()
+                    .map(|lm| {
+                        let decay = lm.decay_rate(x, y, self.decay_factor);
+                        let phase_offset = lm.phase_at(lm.wave_number_or(self.wave_number as f32), self.time);
+                        (self.bearing_wave_number as f32, lm.bearing_residual(x, y, self.heading), lm.weight(self.decay_factor), phase_offset, decay)
+                    });
+                superpose(terms)
+            }
+            SensorModel::RangeBearing => {
+                let k_range = self.wave_number as f32;
+                let k_bearing = self.bearing_wave_number as f32;
+                let mut re_sum = 0.0;
+                let mut im_sum = 0.0;
+                for lm in &self.landmarks {
+                    let range_residual = lm.distance_to(x, y) - lm.front_at(self.time, self.propagation_speed);
+                    let bearing_residual = lm.bearing_residual(x, y, self.heading);
+
+                    // 位相は両成分の和、振幅は両残差の和で減衰
+                    let k = lm.wave_number_or(k_range);
+                    let phase = k * range_residual + k_bearing * bearing_residual + lm.phase_at(k, self.time);
+                    let decay = lm.decay_rate(x, y, self.decay_factor);
+                    let amp = lm.weight(self.decay_factor) * math::exp(-decay * (range_residual.abs() + bearing_residual.abs()));
+
+                    re_sum += amp * math::cos(phase);
+                    im_sum += amp * math::sin(phase);
+                }
+                [re_sum as f64, im_sum as f64]
+            }
+        }
+    }
+
+    // 観測距離を差し替えて ψ を計算する (aperture / エージェント / TemporalCore のスナップショット用)
+    // 各ランドマークの波数 (固有の値または wave_number) は k_scale 倍する (チャープの掃引)
+    fn complex_field_with(&self, x: f32, y: f32, k_scale: f32, observed: impl IntoIterator<Item = f32>) -> [f64; 2] {
+        let k_core = self.wave_number as f32;
+        let terms = self.landmarks
+            .iter()
+            .zip(observed)
+            .map(|(lm, observed_dist)| {
+                let decay = lm.decay_rate(x, y, self.decay_factor);
+                let k = lm.wave_number_or(k_core) * k_scale;
+                let front = observed_dist + self.propagation_speed * self.time;
+                (k, lm.distance_to(x, y) - front, lm.weight(self.decay_factor), lm.phase_at(k, self.time), decay)
+            });
+        superpose(terms)
+    }
+
+    /// Adds a 3D landmark at `(x, y, z)`; a non-finite coordinate is rejected.
+    pub fn add_landmark_3d(&mut self, x: f32, y: f32, z: f32) -> Result<(), Error> {
+        check_position([x, y])?;
+        if !z.is_finite() {
+            return Err(Error::InvalidParameter(format!("Landmark height {} must be finite", z)));
+        }
+        self.landmarks_3d.push(Landmark3d::new([x, y, z]));
+        Ok(())
+    }
+
+    /// `observe` for the 3D landmarks from a camera at `cam = [x, y, z]`; a non-finite
+    /// coordinate is rejected.
+    pub fn observe_3d(&mut self, cam: [f32; 3]) -> Result<(), Error> {
+        check_position([cam[0], cam[1]])?;
+        check_finite("Camera height", cam[2])?;
+        for lm in &mut self.landmarks_3d {
+            lm.observed_dist = lm.distance_to(cam);
+        }
+        Ok(())
+    }
+
+    /// `probability_at` over the 3D landmarks for a camera hypothesis at `(x, y, z)`,
+    /// with the same kernel, wave number and decay as the 2D field.
+    pub fn probability_at_3d(&self, x: f32, y: f32, z: f32) -> f64 {
+        let terms = self.landmarks_3d
+            .iter()
+            .map(|lm| {
+                let residual = lm.distance_to([x, y, z]) - lm.observed_dist;
+                (self.wave_number as f32, residual, lm.amplitude * lm.confidence, 0.0, self.decay_factor)
+            });
+        let [re, im] = superpose(terms);
+        re * re + im * im
+    }
+
+    /// `|ψ|²` averaged over `n_steps` wave numbers swept linearly from `f_start` to
+    /// `f_end` (a chirp).
+    ///
+    /// A single wave number puts a ridge of equal height every `2π / k` of residual, so
+    /// the field has ambiguous side peaks. Those sit at different places for every
+    /// wave number while the true solution (zero residual) is in phase for all of them,
+    /// so averaging over the sweep keeps the main peak and suppresses the rest; the
+    /// remaining peak width is about `2π / (f_end - f_start)`, i.e. set by the
+    /// bandwidth of the sweep rather than by any single wave number.
+    ///
+    /// The sweep applies to `wave_number`; a landmark with its own wave number `k_i`
+    /// (see [`Self::set_landmark_wave_number`]) is swept in proportion, using
+    /// `k_i * k / wave_number` at each step `k`.
+    pub fn probability_chirp_at(&self, x: f32, y: f32, f_start: f64, f_end: f64, n_steps: usize) -> f64 {
+        if n_steps == 0 {
+            return 0.0;
+        }
+        let step = if n_steps > 1 { (f_end - f_start) / (n_steps - 1) as f64 } else { 0.0 };
+
+        let total: f64 = (0..n_steps)
+            .map(|i| {
+                let k_scale = ((f_start + step * i as f64) / self.wave_number) as f32;
+                let [re, im] = self.complex_field_with(x, y, k_scale, self.landmarks.iter().map(|lm| lm.observed_dist));
+                re * re + im * im
+            })
+            .sum();
+        total / n_steps as f64
+    }
+
+    /// `|ψ|²` of [`Self::wave_at_time`].
+    pub fn probability_at_time(&self, x: f32, y: f32, t: f32) -> f64 {
+        let [re, im] = self.wave_at_time(x, y, t);
+        re * re + im * im
+    }
+
+    /// `probability_at` together with its spatial gradient `[∂P/∂x, ∂P/∂y]`, sharing
+    /// one pass over the landmarks. The gradient term of a landmark is dropped at its
+    /// exact position, where the distance is not differentiable.
+    pub fn value_and_gradient_at(&self, x: f32, y: f32) -> (f64, [f64; 2]) {
+        let mut re_sum = 0.0;
+        let mut im_sum = 0.0;
+        // d(re)/dx, d(re)/dy, d(im)/dx, d(im)/dy
+        let mut d_re = [0.0f32; 2];
+        let mut d_im = [0.0f32; 2];
+
+        for lm in &self.landmarks {
+            let (hypo_dist, [ux, uy]) = lm.distance_and_gradient(x, y);
+            let k = lm.wave_number_or(self.wave_number as f32);
+
+            let (decay, [cx, cy]) = lm.decay_and_gradient(x, y, self.decay_factor);
+
+            let residual = hypo_dist - lm.front_at(self.time, self.propagation_speed);
+            let phase = k * residual + lm.phase_at(k, self.time);
+            let amp = lm.weight(self.decay_factor) * math::exp(-decay * residual.abs());
+            let (sin, cos) = math::sin_cos(phase);
+
+            re_sum += amp * cos;
+            im_sum += amp * sin;
+
+            if hypo_dist > 0.0 {
+                // d/d(hypo_dist) of amp * e^{i*phase}
+                let d_amp = -decay * residual.signum() * amp;
+                let d_re_dh = d_amp * cos - amp * k * sin;
+                let d_im_dh = d_amp * sin + amp * k * cos;
+                // 異方的な減衰率の方向依存: d(amp)/d(p) に -|r| amp ∇decay が加わる
+                let d_amp_dc = -residual.abs() * amp;
+                d_re[0] += d_re_dh * ux + d_amp_dc * cx * cos;
+                d_re[1] += d_re_dh * uy + d_amp_dc * cy * cos;
+                d_im[0] += d_im_dh * ux + d_amp_dc * cx * sin;
+                d_im[1] += d_im_dh * uy + d_amp_dc * cy * sin;
+            }
+        }
+
+        let value = (re_sum * re_sum + im_sum * im_sum) as f64;
+        let gradient = [
+            (2.0 * (re_sum * d_re[0] + im_sum * d_im[0])) as f64,
+            (2.0 * (re_sum * d_re[1] + im_sum * d_im[1])) as f64,
+        ];
+        (value, gradient)
+    }
+
+    /// Closed-form gradient `[∂P/∂x, ∂P/∂y]` of `probability_at`, e.g. for gradient
+    /// ascent without finite differences. See [`Self::value_and_gradient_at`].
+    pub fn gradient_at(&self, x: f32, y: f32) -> [f64; 2] {
+        self.value_and_gradient_at(x, y).1
+    }
+
+    /// Gaussian covariance of a peak at `(x, y)`: the inverse Hessian of `-ln P`
+    /// (Laplace approximation), e.g. to hand an estimate to a Kalman filter. The Hessian
+    /// is a central difference of the analytic gradient over a small fraction of the
+    /// fringe spacing `2π / k`. `None` where `P` is zero or the point is not a maximum
+    /// (Hessian not positive definite).
+    pub fn pose_covariance_at(&self, x: f32, y: f32) -> Option<[[f64; 2]; 2]> {
+        let h = (0.01 * core::f64::consts::TAU / self.wave_number) as f32;
+
+        // ∇(-ln P) = -∇P / P
+        let grad_nll = |x: f32, y: f32| -> Option<[f64; 2]> {
+            let (p, [gx, gy]) = self.value_and_gradient_at(x, y);
+            (p > 0.0).then(|| [-gx / p, -gy / p])
+        };
+        let [gx_px, gy_px] = grad_nll(x + h, y)?;
+        let [gx_mx, gy_mx] = grad_nll(x - h, y)?;
+        let [gx_py, gy_py] = grad_nll(x, y + h)?;
+        let [gx_my, gy_my] = grad_nll(x, y - h)?;
+
+        let two_h = 2.0 * h as f64;
+        let hxx = (gx_px - gx_mx) / two_h;
+        let hyy = (gy_py - gy_my) / two_h;
+        let hxy = 0.5 * ((gx_py - gx_my) + (gy_px - gy_mx)) / two_h;
+
+        let det = hxx * hyy - hxy * hxy;
+        if !(hxx > 0.0 && det > 0.0) {
+            return None;
+        }
+        Some([[hyy / det, -hxy / det], [-hxy / det, hxx / det]])
+    }
+
+    /// Bounding box of the landmarks expanded by `padding` (a fraction of the larger
+    /// extent) on every side, or `None` when there are no landmarks.
+    pub fn auto_bounds(&self, padding: f32) -> Option<Bounds> {
+        let first = self.landmarks.first()?.position;
+        let (mut x_min, mut x_max, mut y_min, mut y_max) = (first[0], first[0], first[1], first[1]);
+        for lm in &self.landmarks {
+            x_min = x_min.min(lm.position[0]);
+            x_max = x_max.max(lm.position[0]);
+            y_min = y_min.min(lm.position[1]);
+            y_max = y_max.max(lm.position[1]);
+        }
+
+        // ランドマークが1点に重なる場合は単位長を基準にする
+        let extent = (x_max - x_min).max(y_max - y_min);
+        let pad = padding * if extent > 0.0 { extent } else { 1.0 };
+        Some((x_min - pad, x_max + pad, y_min - pad, y_max + pad))
+    }
+
+    // 明示されなければ landmark の外接矩形 (EXPORT_PADDING の余白付き)
+    #[cfg(feature = "std")]
+    fn export_bounds(&self, bounds: Option<Bounds>) -> Result<Bounds, Error> {
+        match bounds {
+            Some(bounds) => Ok(bounds),
+            None => self.auto_bounds(EXPORT_PADDING).ok_or(Error::NoLandmarks),
+        }
+    }
+
+    /// Writes the field on an `nx` x `ny` grid to a `.npy` file (see
+    /// [`ProbabilityField::save_npy`]). With `bounds` = `None` the grid covers
+    /// [`Self::auto_bounds`] padded by 10%, which fails with `NoLandmarks` on an empty map.
+    #[cfg(feature = "std")]
+    pub fn save_npy(&self, path: impl AsRef<Path>, bounds: Option<Bounds>, nx: usize, ny: usize) -> Result<(), Error> {
+        self.evaluate_grid(self.export_bounds(bounds)?, nx, ny).save_npy(path)
+    }
+
+    /// Renders the field on a `width` x `height` grid to a grayscale PNG (see
+    /// [`ProbabilityField::save_png`]), with the same `bounds` default as [`Self::save_npy`].
+    #[cfg(feature = "image")]
+    pub fn save_png(&self, path: impl AsRef<Path>, bounds: Option<Bounds>, width: usize, height: usize) -> Result<(), Error> {
+        self.evaluate_grid(self.export_bounds(bounds)?, width, height).save_png(path)
+    }
+
+    /// Evaluates the probability density at the cell centers of an `nx` x `ny` grid over `bounds`.
+    /// With the `parallel` feature the rows are evaluated across threads.
+    pub fn evaluate_grid(&self, bounds: Bounds, nx: usize, ny: usize) -> ProbabilityField {
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+
+            let mut field = ProbabilityField { bounds, nx, ny, values: Vec::new() };
+            let sample = &self.field_sampler();
+            // 行ごとに分割 (collect は行の順序を保つ)
+            let grid = &field;
+            let values = (0..ny)
+                .into_par_iter()
+                .flat_map_iter(|iy| {
+                    (0..nx).map(move |ix| {
+                        let [x, y] = grid.cell_center(ix, iy);
+                        sample(x, y)
+                    })
+                })
+                .collect();
+            field.values = values;
+            field
+        }
+        #[cfg(not(feature = "parallel"))]
+        self.evaluate_grid_with_progress(bounds, nx, ny, |_| {})
+    }
+
+    /// `evaluate_grid` divided by its sum, so the cells are a discrete distribution
+    /// over `bounds` (the raw `|ψ|²` is an unnormalized intensity). All zeros if the
+    /// field vanishes there.
+    pub fn normalized_grid(&self, bounds: Bounds, nx: usize, ny: usize) -> ProbabilityField {
+        self.evaluate_grid(bounds, nx, ny).normalized()
+    }
+
+    /// `evaluate_grid` that reports the completed fraction (`0.0..=1.0`) to `progress`
+    /// after each batch of rows, for long exports.
+    pub fn evaluate_grid_with_progress(
+        &self,
+        bounds: Bounds,
+        nx: usize,
+        ny: usize,
+        mut progress: impl FnMut(f32),
+    ) -> ProbabilityField {
+        let mut field = ProbabilityField {
+            bounds,
+            nx,
+            ny,
+            values: Vec::with_capacity(nx * ny),
+        };
+
+        // 約1%ごとに通知 (内側のループには入れない)
+        let rows_per_report = (ny / 100).max(1);
+        let sample = self.field_sampler();
+
+        for iy in 0..ny {
+            for ix in 0..nx {
+                let [x, y] = field.cell_center(ix, iy);
+                field.values.push(sample(x, y));
+            }
+
+            if (iy + 1) % rows_per_report == 0 || iy + 1 == ny {
+                progress((iy + 1) as f32 / ny as f32);
+            }
+        }
+
+        field
+    }
+
+    /// Field produced by the landmark with handle `id` alone (`|c|²` of its complex
+    /// term), to isolate one landmark's footprint. It is a [`ProbabilityField`] on the
+    /// same grid as [`Self::evaluate_grid`], so it can be compared cell by cell with the
+    /// full field or saved and overlaid the same way.
+    pub fn contribution_field_of(&self, id: LandmarkId, bounds: Bounds, nx: usize, ny: usize) -> Result<ProbabilityField, Error> {
+        let lm = &self.landmarks[self.landmark_index(id).ok_or(Error::UnknownLandmark(id))?];
+        Ok(ProbabilityField::from_fn(bounds, nx, ny, |x, y| {
+            let residual = lm.distance_to(x, y) - lm.front_at(self.time, self.propagation_speed);
+            let amp = lm.weight(self.decay_factor) * math::exp(-lm.decay_rate(x, y, self.decay_factor) * residual.abs());
+            (amp * amp) as f64
+        }))
+    }
+
+    /// Samples the field on a polar grid of `n_radial` radii up to `max_radius` and
+    /// `n_angular` evenly spaced angles around `center`; the center itself is not sampled.
+    pub fn evaluate_radial_grid(
+        &self,
+        center: [f32; 2],
+        max_radius: f32,
+        n_radial: usize,
+        n_angular: usize,
+        spacing: RadialSpacing,
+    ) -> RadialField {
+        let inner = max_radius / n_radial as f32;
+        let radii: Vec<f32> = (0..n_radial)
+            .map(|i| match spacing {
+                RadialSpacing::Linear => inner * (i + 1) as f32,
+                RadialSpacing::Log if n_radial > 1 => {
+                    inner * math::powf(max_radius / inner, i as f32 / (n_radial - 1) as f32)
+                }
+                RadialSpacing::Log => max_radius,
+            })
+            .collect();
+        let angles: Vec<f32> = (0..n_angular)
+            .map(|i| core::f32::consts::TAU * i as f32 / n_angular as f32)
+            .collect();
+
+        let mut field = RadialField {
+            center,
+            radii,
+            angles,
+            values: Vec::with_capacity(n_radial * n_angular),
+        };
+        for ir in 0..n_radial {
+            for ia in 0..n_angular {
+                let [x, y] = field.position(ir, ia);
+                field.values.push(self.probability_at(x, y));
+            }
+        }
+        field
+    }
+
+    /// `evaluate_grid` that checks `cancel` before each row and returns `None` once it
+    /// is set, so an interactive caller can drop a stale evaluation.
+    pub fn evaluate_grid_cancellable(&self, bounds: Bounds, nx: usize, ny: usize, cancel: &AtomicBool) -> Option<ProbabilityField> {
+        let mut field = ProbabilityField {
+            bounds,
+            nx,
+            ny,
+            values: Vec::with_capacity(nx * ny),
+        };
+
+        let sample = self.field_sampler();
+        for iy in 0..ny {
+            if cancel.load(Ordering::Relaxed) {
+                return None;
+            }
+            for ix in 0..nx {
+                let [x, y] = field.cell_center(ix, iy);
+                field.values.push(sample(x, y));
+            }
+        }
+
+        Some(field)
+    }
+
+    /// `evaluate_grid` for grids too large to hold or wait for: evaluates blocks of at
+    /// most `tile_size` x `tile_size` cells in row-major order and passes each to
+    /// `callback` with the completed fraction (`0.0..=1.0`). The callback can stream the
+    /// tile out and return [`ControlFlow::Break`] to stop before the next one.
+    pub fn evaluate_grid_tiled(
+        &self,
+        bounds: Bounds,
+        nx: usize,
+        ny: usize,
+        tile_size: usize,
+        mut callback: impl FnMut(GridTile, f32) -> ControlFlow<()>,
+    ) -> Result<(), Error> {
+        if tile_size == 0 {
+            return Err(Error::InvalidParameter("Tile size must be at least 1".into()));
+        }
+        let (x_min, x_max, y_min, y_max) = bounds;
+        let cell_w = (x_max - x_min) / nx as f32;
+        let cell_h = (y_max - y_min) / ny as f32;
+        let sample = self.field_sampler();
+
+        let mut done = 0;
+        for iy0 in (0..ny).step_by(tile_size) {
+            for ix0 in (0..nx).step_by(tile_size) {
+                let (tile_nx, tile_ny) = (tile_size.min(nx - ix0), tile_size.min(ny - iy0));
+                let tile_bounds = (
+                    x_min + ix0 as f32 * cell_w,
+                    x_min + (ix0 + tile_nx) as f32 * cell_w,
+                    y_min + iy0 as f32 * cell_h,
+                    y_min + (iy0 + tile_ny) as f32 * cell_h,
+                );
+                let field = ProbabilityField::from_fn(tile_bounds, tile_nx, tile_ny, &sample);
+                done += tile_nx * tile_ny;
+                let tile = GridTile { origin: [ix0, iy0], field };
+                if callback(tile, done as f32 / (nx * ny) as f32).is_break() {
+                    return Ok(());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Most likely camera position on a `resolution` x `resolution` grid over `bounds`,
+    /// with its probability density.
+    pub fn estimate_position(&self, bounds: Bounds, resolution: usize) -> ([f32; 2], f64) {
+        self.evaluate_grid(bounds, resolution, resolution).argmax()
+    }
+
+    /// [`Self::estimate_position`] interpolated to sub-cell precision; see
+    /// [`ProbabilityField::argmax_subpixel`].
+    pub fn estimate_position_subpixel(&self, bounds: Bounds, resolution: usize) -> ([f32; 2], f64) {
+        self.evaluate_grid(bounds, resolution, resolution).argmax_subpixel()
+    }
+
+    /// MAP camera position and its probability density, searched without a user grid:
+    /// a coarse grid over [`Self::auto_bounds`] finds the global peak, which is then
+    /// refined by repeatedly re-gridding a small window around it. `None` without landmarks.
+    pub fn estimate_pose(&self) -> Option<([f32; 2], f64)> {
+        // 局所精密化 (窓 ±2セル を 16x16 で再探索) の回数
+        const REFINE_RESOLUTION: usize = 16;
+        const REFINE_STEPS: usize = 4;
+
+        let bounds = self.auto_bounds(SEARCH_PADDING)?;
+        let (mut best, mut value) = self.estimate_position(bounds, SEARCH_RESOLUTION);
+
+        let (x_min, x_max, y_min, y_max) = bounds;
+        let mut half = [
+            2.0 * (x_max - x_min) / SEARCH_RESOLUTION as f32,
+            2.0 * (y_max - y_min) / SEARCH_RESOLUTION as f32,
+        ];
+        for _ in 0..REFINE_STEPS {
+            let window = (best[0] - half[0], best[0] + half[0], best[1] - half[1], best[1] + half[1]);
+            let (candidate, candidate_value) = self.estimate_position(window, REFINE_RESOLUTION);
+            if candidate_value >= value {
+                (best, value) = (candidate, candidate_value);
+            }
+            half = [4.0 * half[0] / REFINE_RESOLUTION as f32, 4.0 * half[1] / REFINE_RESOLUTION as f32];
+        }
+        Some((best, value))
+    }
+
+    /// The `k` strongest local maxima of the field as `(position, probability)`, strongest
+    /// first, at least `min_separation` apart, searched on the same coarse grid as
+    /// [`Self::estimate_pose`]. Empty without landmarks.
+    pub fn find_peaks(&self, k: usize, min_separation: f32) -> Vec<([f32; 2], f64)> {
+        match self.auto_bounds(SEARCH_PADDING) {
+            Some(bounds) => self.evaluate_grid(bounds, SEARCH_RESOLUTION, SEARCH_RESOLUTION).find_peaks(k, min_separation),
+            None => Vec::new(),
+        }
+    }
+
+    /// Observes from `true_cam`, estimates the position back and returns the estimate
+    /// together with its localization error (distance from `true_cam`). A non-finite
+    /// `true_cam` is rejected.
+    pub fn observe_and_estimate(&mut self, true_cam: [f32; 2], bounds: Bounds, resolution: usize) -> Result<([f32; 2], f64), Error> {
+        self.observe_ranges(true_cam)?;
+        let (estimate, _) = self.estimate_position(bounds, resolution);
+
+        let dx = estimate[0] - true_cam[0];
+        let dy = estimate[1] - true_cam[1];
+        Ok((estimate, (dx * dx + dy * dy).sqrt() as f64))
+    }
+
+    /// Localization error (see [`Self::observe_and_estimate`]) for each of
+    /// `test_positions`, e.g. for a localizability heatmap. The current observations
+    /// and sensor model are restored afterwards, also when a non-finite position is
+    /// rejected.
+    pub fn localization_error_map(&mut self, test_positions: &[[f32; 2]], bounds: Bounds, resolution: usize) -> Result<Vec<f32>, Error> {
+        let saved = (self.landmarks.clone(), self.sensor_model);
+        let errors = test_positions
+            .iter()
+            .map(|&cam| Ok(self.observe_and_estimate(cam, bounds, resolution)?.1 as f32))
+            .collect();
+        (self.landmarks, self.sensor_model) = saved;
+        errors
+    }
+
+    /// [`ProbabilityField::entropy`] of the field on a `resolution` x `resolution` grid:
+    /// a scalar measure of how uncertain the current localization is.
+    pub fn field_entropy(&self, bounds: Bounds, resolution: usize) -> f64 {
+        self.evaluate_grid(bounds, resolution, resolution).entropy()
+    }
+
+    /// Compares this core's field with `other`'s over `bounds` on a `resolution` x
+    /// `resolution` grid (see [`ProbabilityField::compare`]), e.g. to detect map drift
+    /// between sessions. Panics if `resolution` is 0.
+    pub fn compare(&self, other: &QuantumSlamCore, bounds: Bounds, resolution: usize) -> FieldComparison {
+        let a = self.evaluate_grid(bounds, resolution, resolution);
+        let b = other.evaluate_grid(bounds, resolution, resolution);
+        a.compare(&b).expect("both fields are sampled on the same grid")
+    }
+
+    /// [`ProbabilityField::concentration`] of the field on a `resolution` x `resolution`
+    /// grid: near 1 when the camera is pinned to a few cells, falling towards
+    /// `1 / resolution²` as the field spreads out (a cue to re-observe).
+    pub fn field_concentration(&self, bounds: Bounds, resolution: usize) -> f64 {
+        self.evaluate_grid(bounds, resolution, resolution).concentration()
+    }
+
+    /// Expected drop in [`Self::field_entropy`] from driving to `candidate_cam` and
+    /// observing there, for next-best-view selection.
+    ///
+    /// The current field over `bounds` (on a `resolution` x `resolution` grid) is the
+    /// prior. The camera is steered by the move from the prior's peak to
+    /// `candidate_cam`, so a hypothesis `h` of the current position ends up at
+    /// `h + candidate_cam - peak` and the prior moves with it. `n_hypotheses` such
+    /// positions are drawn from the prior (deterministic systematic sampling); for
+    /// each, the ranges observed from there give a likelihood field, and the posterior
+    /// is its cell-wise product with the moved prior. Returns the moved prior's
+    /// entropy minus the mean posterior entropy: larger means the viewpoint is
+    /// expected to disambiguate more of the current hypotheses, whichever is true.
+    ///
+    /// `candidate_cam` must be finite and `n_hypotheses` positive. Zero when the
+    /// current field is zero everywhere.
+    pub fn expected_information_gain(
+        &self,
+        candidate_cam: [f32; 2],
+        bounds: Bounds,
+        resolution: usize,
+        n_hypotheses: usize,
+    ) -> Result<f64, Error> {
+        check_position(candidate_cam)?;
+        if n_hypotheses == 0 {
+            return Err(Error::InvalidParameter("n_hypotheses must be positive".into()));
+        }
+        let prior = self.evaluate_grid(bounds, resolution, resolution);
+        let total: f64 = prior.values.iter().sum();
+        if total <= 0.0 {
+            return Ok(0.0);
+        }
+        let (peak, _) = prior.argmax();
+        let motion = [candidate_cam[0] - peak[0], candidate_cam[1] - peak[1]];
+        let moved = prior.translated(motion);
+
+        // 累積分布を等間隔 (i + 0.5) / n で切る系統サンプリング
+        let mut hypotheses = Vec::with_capacity(n_hypotheses);
+        let mut cumulative = 0.0;
+        let mut cells = prior.values.iter().enumerate();
+        let mut index = 0;
+        for i in 0..n_hypotheses {
+            let u = (i as f64 + 0.5) / n_hypotheses as f64 * total;
+            while cumulative < u {
+                let Some((j, &v)) = cells.next() else { break };
+                cumulative += v;
+                index = j;
+            }
+            let [x, y] = prior.cell_center(index % prior.nx, index / prior.nx);
+            hypotheses.push([x + motion[0], y + motion[1]]);
+        }
+
+        let mut predicted = self.clone();
+        let mut posterior_entropy = 0.0;
+        for cam in hypotheses {
+            predicted.observe_ranges(cam)?;
+            let likelihood = predicted.evaluate_grid(bounds, resolution, resolution);
+            let posterior = ProbabilityField {
+                values: moved.values.iter().zip(&likelihood.values).map(|(p, l)| p * l).collect(),
+                ..moved.clone()
+            };
+            posterior_entropy += posterior.entropy();
+        }
+        Ok(moved.entropy() - posterior_entropy / n_hypotheses as f64)
+    }
+
+    /// Iso-probability contours of the field over an `nx` x `ny` grid, as world-space
+    /// polylines. See [`ProbabilityField::iso_contours`].
+    pub fn iso_contours(&self, bounds: Bounds, nx: usize, ny: usize, levels: &[f64]) -> Vec<Vec<[f32; 2]>> {
+        self.evaluate_grid(bounds, nx, ny).iso_contours(levels)
+    }
+
+    /// Expected camera position: the field's centroid over an `nx` x `ny` grid. Less
+    /// sensitive to a single sharp peak than `estimate_position`. See [`ProbabilityField::centroid`].
+    pub fn expected_position(&self, bounds: Bounds, nx: usize, ny: usize) -> Option<[f32; 2]> {
+        self.evaluate_grid(bounds, nx, ny).centroid()
+    }
+
+    /// Otsu threshold of the field over an `nx` x `ny` grid. See [`ProbabilityField::otsu_threshold`].
+    pub fn otsu_threshold(&self, bounds: Bounds, nx: usize, ny: usize) -> f64 {
+        self.evaluate_grid(bounds, nx, ny).otsu_threshold()
+    }
+
+    /// Binary occupancy of an `nx` x `ny` grid (`true` = above threshold), row-major like
+    /// [`ProbabilityField::values`]. Uses the Otsu threshold when `threshold` is `None`.
+    pub fn occupancy_grid(&self, bounds: Bounds, nx: usize, ny: usize, threshold: Option<f64>) -> Vec<bool> {
+        self.evaluate_grid(bounds, nx, ny).occupancy(threshold)
+    }
+
+    /// The field over `bounds` on a `resolution` x `resolution` grid, thresholded into
+    /// an [`OccupancyGrid`] for costmaps and path planners (Otsu's threshold when
+    /// `threshold` is `None`).
+    pub fn to_occupancy_grid(&self, bounds: Bounds, resolution: usize, threshold: Option<f64>) -> OccupancyGrid {
+        self.evaluate_grid(bounds, resolution, resolution).to_occupancy_grid(threshold)
+    }
+
+    /// 2D FFT of the field over an `nx` x `ny` grid. See [`ProbabilityField::fft`].
+    #[cfg(feature = "fft")]
+    pub fn field_fft(&self, bounds: Bounds, nx: usize, ny: usize) -> Vec<Complex<f64>> {
+        self.evaluate_grid(bounds, nx, ny).fft()
+    }
+}
+
+// ----------------------------------------------------------------------------
+//  Temporal Window (Sliding-Window Estimator)
+// ----------------------------------------------------------------------------
+
+// 1回分の観測: 各ランドマークの観測距離と観測時刻
+#[derive(Clone, Debug, PartialEq)]
+struct ObservationSnapshot {
+    time: f32,
+    observed_dist: Vec<f32>,
+}
+
+// 指数カーネルの時定数は有限の正の値
+fn check_time_constant(time_constant: f32) -> Result<(), Error> {
+    if !(time_constant.is_finite() && time_constant > 0.0) {
+        return Err(Error::InvalidParameter(format!("time_constant {} must be finite and positive", time_constant)));
+    }
+    Ok(())
+}
+
+/// Sliding-window estimator over the last `capacity` observations of a core's map.
+///
+/// The field is the mean of each snapshot's `|ψ|²`, weighted by
+/// `exp(-(newest_time - snapshot_time) / time_constant)`, so recent observations count most.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TemporalCore {
+    pub core: QuantumSlamCore,
+    // 有限の正の値 (set_time_constant で検証)
+    time_constant: f32,
+    snapshots: VecDeque<ObservationSnapshot>,
+    capacity: usize,
+}
+
+impl TemporalCore {
+    /// A window of `capacity` snapshots over `core`. `time_constant` must be finite and
+    /// positive.
+    pub fn new(core: QuantumSlamCore, capacity: usize, time_constant: f32) -> Result<Self, Error> {
+        check_time_constant(time_constant)?;
+        Ok(Self {
+            core,
+            time_constant,
+            snapshots: VecDeque::with_capacity(capacity),
+            capacity,
+        })
+    }
+
+    pub fn time_constant(&self) -> f32 {
+        self.time_constant
+    }
+
+    /// Sets the kernel's `time_constant`, which must be finite and positive.
+    pub fn set_time_constant(&mut self, time_constant: f32) -> Result<(), Error> {
+        check_time_constant(time_constant)?;
+        self.time_constant = time_constant;
+        Ok(())
+    }
+
+    /// Observes from the camera position at `core.time` and records the result,
+    /// dropping the oldest snapshot once the window is full.
+    pub fn observe(&mut self, cam_x: f32, cam_y: f32) -> Result<(), Error> {
+        self.core.observe(cam_x, cam_y)?;
+        self.push_snapshot();
+        Ok(())
+    }
+
+    /// Records the core's current observed distances as a snapshot at `core.time`.
+    pub fn push_snapshot(&mut self) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.snapshots.len() == self.capacity {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(ObservationSnapshot {
+            time: self.core.time,
+            observed_dist: self.core.landmarks.iter().map(|lm| lm.observed_dist).collect(),
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// Kernel-weighted field at `(x, y)`; 0 with no snapshots. Landmarks added after a
+    /// snapshot do not contribute to it.
+    pub fn probability_at(&self, x: f32, y: f32) -> f64 {
+        let Some(newest) = self.snapshots.back() else {
+            return 0.0;
+        };
+
+        let mut weighted = 0.0;
+        let mut total_weight = 0.0;
+        for snapshot in &self.snapshots {
+            let age = (newest.time - snapshot.time) as f64;
+            let weight = math::exp(-age / self.time_constant as f64);
+            let [re, im] = self.core.complex_field_with(x, y, 1.0, snapshot.observed_dist.iter().copied());
+            weighted += weight * (re * re + im * im);
+            total_weight += weight;
+        }
+        weighted / total_weight
+    }
+
+    pub fn evaluate_grid(&self, bounds: Bounds, nx: usize, ny: usize) -> ProbabilityField {
+        ProbabilityField::from_fn(bounds, nx, ny, |x, y| self.probability_at(x, y))
+    }
+}
+
+// ----------------------------------------------------------------------------
+//  Particle Filter (Sampling Estimator on the Field)
+// ----------------------------------------------------------------------------
+
+/// Weighted particle set over camera positions, with `probability_at` as the
+/// measurement likelihood.
+///
+/// One step is `predict` (motion model), `update` (re-weight by the field) and,
+/// when [`Self::effective_sample_size`] drops, `resample`.
+#[cfg(feature = "rand")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParticleFilter {
+    pub particles: Vec<[f32; 2]>,
+    // 正規化済み (合計 1)
+    pub weights: Vec<f64>,
+}
+
+#[cfg(feature = "rand")]
+impl ParticleFilter {
+    /// `n` particles drawn uniformly over `bounds`, with equal weights.
+    pub fn new(n: usize, bounds: Bounds, rng: &mut impl rand::Rng) -> Self {
+        let (x_min, x_max, y_min, y_max) = bounds;
+        let particles = (0..n)
+            .map(|_| [rng.gen_range(x_min..=x_max), rng.gen_range(y_min..=y_max)])
+            .collect();
+        Self::from_particles(particles)
+    }
+
+    /// Equally weighted particles at the given positions.
+    pub fn from_particles(particles: Vec<[f32; 2]>) -> Self {
+        let weights = vec![1.0 / particles.len() as f64; particles.len()];
+        Self { particles, weights }
+    }
+
+    pub fn len(&self) -> usize {
+        self.particles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.particles.is_empty()
+    }
+
+    /// Moves every particle by `motion` plus isotropic Gaussian noise of `noise_std`.
+    pub fn predict(&mut self, motion: [f32; 2], noise_std: f32, rng: &mut impl rand::Rng) {
+        self.predict_with(|[x, y], rng| {
+            let [nx, ny] = gaussian_pair(rng);
+            [x + motion[0] + noise_std * nx, y + motion[1] + noise_std * ny]
+        }, rng);
+    }
+
+    /// Applies an arbitrary (stochastic) motion model to every particle.
+    pub fn predict_with<R: rand::Rng>(&mut self, mut model: impl FnMut([f32; 2], &mut R) -> [f32; 2], rng: &mut R) {
+        for p in &mut self.particles {
+            *p = model(*p, rng);
+        }
+    }
+
+    /// Multiplies each weight by the field at its particle and renormalizes. If every
+    /// particle lands on zero probability the weights are reset to uniform.
+    pub fn update(&mut self, core: &QuantumSlamCore) {
+        for (w, p) in self.weights.iter_mut().zip(&self.particles) {
+            *w *= core.probability_at(p[0], p[1]);
+        }
+        let total: f64 = self.weights.iter().sum();
+        let n = self.weights.len() as f64;
+        for w in &mut self.weights {
+            *w = if total > 0.0 { *w / total } else { 1.0 / n };
+        }
+    }
+
+    /// `1 / Σ w²`: `len()` for equal weights, 1 when one particle carries all weight.
+    pub fn effective_sample_size(&self) -> f64 {
+        1.0 / self.weights.iter().map(|w| w * w).sum::<f64>()
+    }
+
+    /// Systematic resampling to equally weighted particles.
+    pub fn resample(&mut self, rng: &mut impl rand::Rng) {
+        let n = self.particles.len();
+        if n == 0 {
+            return;
+        }
+        let step = 1.0 / n as f64;
+        let mut target = rng.gen::<f64>() * step;
+        let mut cumulative = self.weights[0];
+        let mut i = 0;
+
+        let mut resampled = Vec::with_capacity(n);
+        for _ in 0..n {
+            while target > cumulative && i + 1 < n {
+                i += 1;
+                cumulative += self.weights[i];
+            }
+            resampled.push(self.particles[i]);
+            target += step;
+        }
+        *self = Self::from_particles(resampled);
+    }
+
+    /// Weighted mean position.
+    pub fn mean(&self) -> [f32; 2] {
+        let (mut mx, mut my) = (0.0, 0.0);
+        for (w, p) in self.weights.iter().zip(&self.particles) {
+            mx += w * p[0] as f64;
+            my += w * p[1] as f64;
+        }
+        [mx as f32, my as f32]
+    }
+
+    /// Weighted covariance of the particle positions about [`Self::mean`].
+    pub fn covariance(&self) -> [[f64; 2]; 2] {
+        let [mx, my] = self.mean();
+        let (mut sxx, mut sxy, mut syy) = (0.0, 0.0, 0.0);
+        for (w, p) in self.weights.iter().zip(&self.particles) {
+            let dx = (p[0] - mx) as f64;
+            let dy = (p[1] - my) as f64;
+            sxx += w * dx * dx;
+            sxy += w * dx * dy;
+            syy += w * dy * dy;
+        }
+        [[sxx, sxy], [sxy, syy]]
+    }
+}
+
+/// Range noise for simulated observations: Gaussian with standard deviation `sigma`,
+/// and with probability `outlier_rate` an outlier drawn uniformly from `[0, outlier_range]`
+/// in place of the measurement.
+#[cfg(feature = "rand")]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct NoiseModel {
+    pub sigma: f32,
+    pub outlier_rate: f32,
+    pub outlier_range: f32,
+}
+
+#[cfg(feature = "rand")]
+impl NoiseModel {
+    pub fn gaussian(sigma: f32) -> Self {
+        Self { sigma, ..Self::default() }
+    }
+
+    pub fn with_outliers(self, outlier_rate: f32, outlier_range: f32) -> Self {
+        Self { outlier_rate, outlier_range, ..self }
+    }
+
+    /// A noisy measurement of the true range `dist` (never negative).
+    pub fn corrupt(&self, dist: f32, rng: &mut impl rand::Rng) -> f32 {
+        if self.outlier_rate > 0.0 && rng.gen::<f32>() < self.outlier_rate {
+            return rng.gen::<f32>() * self.outlier_range;
+        }
+        (dist + self.sigma * gaussian_pair(rng)[0]).max(0.0)
+    }
+}
+
+// 標準正規分布の独立な2標本 (Box-Muller 法)
+#[cfg(feature = "rand")]
+fn gaussian_pair(rng: &mut impl rand::Rng) -> [f32; 2] {
+    let u1: f32 = rng.gen_range(f32::EPSILON..1.0);
+    let u2: f32 = rng.gen();
+    let r = (-2.0 * math::ln(u1)).sqrt();
+    let (sin, cos) = math::sin_cos(core::f32::consts::TAU * u2);
+    [r * cos, r * sin]
+}
+
+// step の位相拡散用の標準正規乱数。ランドマーク ID と時刻から splitmix64 で決定的に生成する
+// (rand feature なし・no_std でも使えるように)
+fn hashed_normal(id: LandmarkId, time: f32) -> f32 {
+    let mut state = (u64::from(id.0) << 32) | u64::from(time.to_bits());
+    let mut next_uniform = || {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        // 上位 24bit から [0, 1) の一様乱数
+        ((z ^ (z >> 31)) >> 40) as f32 / (1u32 << 24) as f32
+    };
+    let u1 = 1.0 - next_uniform();
+    let u2 = next_uniform();
+    (-2.0 * math::ln(u1)).sqrt() * math::cos(core::f32::consts::TAU * u2)
+}
+
+// ----------------------------------------------------------------------------
+//  Probability Field (Grid Evaluation Result)
+// ----------------------------------------------------------------------------
+
+/// World-space rectangle `(x_min, x_max, y_min, y_max)`.
+pub type Bounds = (f32, f32, f32, f32);
+
+// 座標系の規約: ワールド座標は y 上向き。CPU の ProbabilityField は行0 = y_min、
+// GPU の描画 (ビューポート = bounds) は画面の行0 = y_max。どちらもセル/ピクセル中心でサンプルする
+
+/// World coordinate of the center of pixel `(col, row)` when `bounds` is rendered at
+/// `width` x `height`. Row 0 is the top of the image (`y_max`), as on screen.
+pub fn pixel_to_world(bounds: Bounds, width: u32, height: u32, col: u32, row: u32) -> [f32; 2] {
+    let (x_min, x_max, y_min, y_max) = bounds;
+    [
+        x_min + (col as f32 + 0.5) / width as f32 * (x_max - x_min),
+        y_max - (row as f32 + 0.5) / height as f32 * (y_max - y_min),
+    ]
+}
+
+/// Pixel `[col, row]` containing world point `p` when `bounds` is rendered at
+/// `width` x `height`, or `None` outside the viewport. Inverse of [`pixel_to_world`].
+pub fn world_to_pixel(bounds: Bounds, width: u32, height: u32, p: [f32; 2]) -> Option<[u32; 2]> {
+    let (x_min, x_max, y_min, y_max) = bounds;
+    let col = ((p[0] - x_min) / (x_max - x_min) * width as f32).floor();
+    let row = ((y_max - p[1]) / (y_max - y_min) * height as f32).floor();
+    let inside = (0.0..width as f32).contains(&col) && (0.0..height as f32).contains(&row);
+    inside.then_some([col as u32, row as u32])
+}
+
+/// Thresholded field in the layout navigation stacks expect (as in a ROS
+/// `nav_msgs/OccupancyGrid`): `data` is row-major with row 0 at `origin[1]` (the
+/// bottom edge), each cell [`OccupancyGrid::OCCUPIED`] or [`OccupancyGrid::FREE`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OccupancyGrid {
+    /// World position of the bottom-left corner of cell `(0, 0)`.
+    pub origin: [f32; 2],
+    /// Cell width and height in world units.
+    pub cell_size: [f32; 2],
+    pub width: usize,
+    pub height: usize,
+    pub data: Vec<u8>,
+}
+
+impl OccupancyGrid {
+    pub const FREE: u8 = 0;
+    pub const OCCUPIED: u8 = 100;
+
+    /// `true` for occupied cells, in the same order as `data`.
+    pub fn occupied(&self) -> Vec<bool> {
+        self.data.iter().map(|&v| v == Self::OCCUPIED).collect()
+    }
+}
+
+/// How two fields on the same grid differ, from [`ProbabilityField::compare`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FieldComparison {
+    /// `KL(self ‖ other)` in nats between the fields normalized to distributions
+    /// (cells where `other` is 0 are floored to a tiny probability).
+    pub kl_divergence: f64,
+    /// Euclidean distance between the normalized fields.
+    pub l2_distance: f64,
+    /// Pearson correlation of the raw values (0 if either field is constant).
+    pub correlation: f64,
+    /// World distance between the two fields' highest cells.
+    pub peak_offset: f32,
+}
+
+/// Block of cells handed out by [`QuantumSlamCore::evaluate_grid_tiled`]: `field`
+/// holds cells `origin[0]..origin[0] + field.nx` by `origin[1]..origin[1] + field.ny`
+/// of the full grid, with its `bounds` covering exactly those cells.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GridTile {
+    pub origin: [usize; 2],
+    pub field: ProbabilityField,
+}
+
+/// A probability field sampled at the cell centers of a regular grid.
+///
+/// `values` is row-major: index `iy * nx + ix`, with row 0 at `y_min`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProbabilityField {
+    pub bounds: Bounds,
+    pub nx: usize,
+    pub ny: usize,
+    pub values: Vec<f64>,
+}
+
+impl ProbabilityField {
+    /// Wraps row-major `values` (row 0 at `y_min`) checking that there are `nx * ny` of them.
+    pub fn from_values(bounds: Bounds, nx: usize, ny: usize, values: Vec<f64>) -> Result<Self, Error> {
+        if values.len() != nx * ny {
+            return Err(Error::DimensionMismatch { expected: nx * ny, found: values.len() });
+        }
+        Ok(Self { bounds, nx, ny, values })
+    }
+
+    /// Samples `f(x, y)` at every cell center of an `nx` x `ny` grid over `bounds`.
+    pub fn from_fn(bounds: Bounds, nx: usize, ny: usize, mut f: impl FnMut(f32, f32) -> f64) -> Self {
+        let mut field = ProbabilityField {
+            bounds,
+            nx,
+            ny,
+            values: Vec::with_capacity(nx * ny),
+        };
+        for iy in 0..ny {
+            for ix in 0..nx {
+                let [x, y] = field.cell_center(ix, iy);
+                field.values.push(f(x, y));
+            }
+        }
+        field
+    }
+
+    /// World coordinate of the center of cell `(ix, iy)`.
+    pub fn cell_center(&self, ix: usize, iy: usize) -> [f32; 2] {
+        let (x_min, x_max, y_min, y_max) = self.bounds;
+        let dx = (x_max - x_min) / self.nx as f32;
+        let dy = (y_max - y_min) / self.ny as f32;
+        [
+            x_min + (ix as f32 + 0.5) * dx,
+            y_min + (iy as f32 + 0.5) * dy,
+        ]
+    }
+
+    pub fn get(&self, ix: usize, iy: usize) -> f64 {
+        self.values[iy * self.nx + ix]
+    }
+
+    /// Same grid (dimensions and bounds) and every value within `tol` of `other`'s.
+    pub fn approx_eq(&self, other: &ProbabilityField, tol: f64) -> bool {
+        self.nx == other.nx
+            && self.ny == other.ny
+            && self.bounds == other.bounds
+            && self.values.len() == other.values.len()
+            && self.values.iter().zip(&other.values).all(|(a, b)| (a - b).abs() <= tol)
+    }
+
+    /// Position and value of the highest cell. Panics on an empty field.
+    pub fn argmax(&self) -> ([f32; 2], f64) {
+        self.extremum(|candidate, best| candidate > best)
+    }
+
+    /// Position and value of the lowest cell. Panics on an empty field.
+    pub fn argmin(&self) -> ([f32; 2], f64) {
+        self.extremum(|candidate, best| candidate < best)
+    }
+
+    /// Probability-weighted mean of the cell centers, or `None` if the field sums to zero.
+    pub fn centroid(&self) -> Option<[f32; 2]> {
+        let (mut sx, mut sy, mut total) = (0.0f64, 0.0f64, 0.0f64);
+        for iy in 0..self.ny {
+            for ix in 0..self.nx {
+                let p = self.get(ix, iy);
+                let [x, y] = self.cell_center(ix, iy);
+                sx += p * x as f64;
+                sy += p * y as f64;
+                total += p;
+            }
+        }
+        (total > 0.0).then(|| [(sx / total) as f32, (sy / total) as f32])
+    }
+
+    fn extremum(&self, better: impl Fn(f64, f64) -> bool) -> ([f32; 2], f64) {
+        let best_idx = self.extremum_index(better);
+        let pos = self.cell_center(best_idx % self.nx, best_idx / self.nx);
+        (pos, self.values[best_idx])
+    }
+
+    fn extremum_index(&self, better: impl Fn(f64, f64) -> bool) -> usize {
+        assert!(!self.values.is_empty(), "ProbabilityField is empty");
+
+        let mut best_idx = 0;
+        for (i, &v) in self.values.iter().enumerate() {
+            if better(v, self.values[best_idx]) {
+                best_idx = i;
+            }
+        }
+        best_idx
+    }
+
+    /// [`Self::argmax`] refined below the cell size: a parabola through the best cell
+    /// and its neighbours is fitted along x and along y, and the peak of each fit gives
+    /// the offset (at most half a cell) and the interpolated value. Cells on the edge
+    /// of the grid are not refined along the missing side's axis. Panics on an empty field.
+    pub fn argmax_subpixel(&self) -> ([f32; 2], f64) {
+        let best_idx = self.extremum_index(|candidate, best| candidate > best);
+        let (ix, iy) = (best_idx % self.nx, best_idx / self.nx);
+        let center = self.values[best_idx];
+
+        // 3点 (l, c, r) を通る放物線の頂点: オフセット (セル単位) と値の増分
+        let refine = |l: f64, r: f64| {
+            let curvature = l - 2.0 * center + r;
+            if curvature >= 0.0 {
+                return (0.0, 0.0);
+            }
+            let offset = (0.5 * (l - r) / curvature).clamp(-0.5, 0.5);
+            (offset, -0.25 * (l - r) * offset)
+        };
+        let (ox, gain_x) = if ix > 0 && ix + 1 < self.nx {
+            refine(self.get(ix - 1, iy), self.get(ix + 1, iy))
+        } else {
+            (0.0, 0.0)
+        };
+        let (oy, gain_y) = if iy > 0 && iy + 1 < self.ny {
+            refine(self.get(ix, iy - 1), self.get(ix, iy + 1))
+        } else {
+            (0.0, 0.0)
+        };
+
+        let (x_min, x_max, y_min, y_max) = self.bounds;
+        let [cx, cy] = self.cell_center(ix, iy);
+        let x = cx + ox as f32 * (x_max - x_min) / self.nx as f32;
+        let y = cy + oy as f32 * (y_max - y_min) / self.ny as f32;
+        ([x, y], center + gain_x + gain_y)
+    }
+
+    /// The field divided by its sum, so the values add up to 1. A field that sums to
+    /// zero is returned unchanged.
+    pub fn normalized(&self) -> Self {
+        let mut field = self.clone();
+        let total: f64 = field.values.iter().sum();
+        if total > 0.0 {
+            field.values.iter_mut().for_each(|v| *v /= total);
+        }
+        field
+    }
+
+    /// Fraction of the field's total in the cells whose centers lie inside `rect`
+    /// (`(x_min, x_max, y_min, y_max)`, inclusive): the probability that the camera is
+    /// there. 0 if the field sums to zero.
+    pub fn mass_in_rect(&self, rect: Bounds) -> f64 {
+        let (x_min, x_max, y_min, y_max) = rect;
+        let total: f64 = self.values.iter().sum();
+        if total <= 0.0 {
+            return 0.0;
+        }
+        let mut inside = 0.0;
+        for iy in 0..self.ny {
+            for ix in 0..self.nx {
+                let [x, y] = self.cell_center(ix, iy);
+                if (x_min..=x_max).contains(&x) && (y_min..=y_max).contains(&y) {
+                    inside += self.get(ix, iy);
+                }
+            }
+        }
+        inside / total
+    }
+
+    /// Bilinear interpolation between cell centers at `(x, y)` (clamped to the
+    /// outermost centers near the edges); 0 outside the bounds.
+    pub fn sample(&self, x: f32, y: f32) -> f64 {
+        let (x_min, x_max, y_min, y_max) = self.bounds;
+        if self.values.is_empty() || !(x_min..=x_max).contains(&x) || !(y_min..=y_max).contains(&y) {
+            return 0.0;
+        }
+        // セル中心を格子点とみなした連続インデックス
+        let fx = ((x - x_min) / (x_max - x_min) * self.nx as f32 - 0.5).clamp(0.0, (self.nx - 1) as f32);
+        let fy = ((y - y_min) / (y_max - y_min) * self.ny as f32 - 0.5).clamp(0.0, (self.ny - 1) as f32);
+        let (ix, iy) = (fx.floor() as usize, fy.floor() as usize);
+        let (ix1, iy1) = ((ix + 1).min(self.nx - 1), (iy + 1).min(self.ny - 1));
+        let (tx, ty) = ((fx - ix as f32) as f64, (fy - iy as f32) as f64);
+
+        let bottom = self.get(ix, iy) * (1.0 - tx) + self.get(ix1, iy) * tx;
+        let top = self.get(ix, iy1) * (1.0 - tx) + self.get(ix1, iy1) * tx;
+        bottom * (1.0 - ty) + top * ty
+    }
+
+    /// The field moved by `offset` in world units on the same grid; what moves out of
+    /// the bounds is dropped and what moves in is 0.
+    pub fn translated(&self, offset: [f32; 2]) -> Self {
+        Self::from_fn(self.bounds, self.nx, self.ny, |x, y| self.sample(x - offset[0], y - offset[1]))
+    }
+
+    /// The field convolved with an isotropic Gaussian of standard deviation `sigma`
+    /// (world units), truncated at 3σ. Mass blurred past the edges is lost; a
+    /// non-positive `sigma` returns the field unchanged.
+    pub fn blurred(&self, sigma: f32) -> Self {
+        if sigma.is_nan() || sigma <= 0.0 {
+            return self.clone();
+        }
+        let (x_min, x_max, y_min, y_max) = self.bounds;
+        let cell = [(x_max - x_min) / self.nx as f32, (y_max - y_min) / self.ny as f32];
+
+        // 軸ごとの正規化済みカーネル (セル単位)
+        let kernel = |cell: f32| -> Vec<f64> {
+            let s = sigma / cell;
+            let radius = (3.0 * s).floor() as i64 + 1;
+            let weights: Vec<f64> = (-radius..=radius)
+                .map(|i| math::exp(-0.5 * (i as f64 / s as f64).powi(2)))
+                .collect();
+            let total: f64 = weights.iter().sum();
+            weights.into_iter().map(|w| w / total).collect()
+        };
+        let (kx, ky) = (kernel(cell[0]), kernel(cell[1]));
+        let (rx, ry) = ((kx.len() / 2) as i64, (ky.len() / 2) as i64);
+
+        let mut rows = vec![0.0; self.values.len()];
+        for iy in 0..self.ny {
+            for ix in 0..self.nx {
+                rows[iy * self.nx + ix] = kx.iter().enumerate().map(|(j, w)| {
+                    let sx = ix as i64 + j as i64 - rx;
+                    if (0..self.nx as i64).contains(&sx) { w * self.get(sx as usize, iy) } else { 0.0 }
+                }).sum();
+            }
+        }
+
+        let mut blurred = self.clone();
+        for iy in 0..self.ny {
+            for ix in 0..self.nx {
+                blurred.values[iy * self.nx + ix] = ky.iter().enumerate().map(|(j, w)| {
+                    let sy = iy as i64 + j as i64 - ry;
+                    if (0..self.ny as i64).contains(&sy) { w * rows[sy as usize * self.nx + ix] } else { 0.0 }
+                }).sum();
+            }
+        }
+        blurred
+    }
+
+    /// Shannon entropy (nats) of the field normalized to a distribution over its cells:
+    /// 0 for a single peak cell, `ln(nx * ny)` for a flat field. 0 if the field sums to zero.
+    pub fn entropy(&self) -> f64 {
+        let total: f64 = self.values.iter().sum();
+        if total <= 0.0 {
+            return 0.0;
+        }
+        -self.values
+            .iter()
+            .filter(|&&v| v > 0.0)
+            .map(|&v| {
+                let p = v / total;
+                p * math::ln(p)
+            })
+            .sum::<f64>()
+    }
+
+    /// Compares this field with `other` sampled on the same grid (e.g. the CPU and GPU
+    /// evaluations, or the same region in two sessions). `DimensionMismatch` if the
+    /// grids differ in size; panics if they are empty.
+    pub fn compare(&self, other: &ProbabilityField) -> Result<FieldComparison, Error> {
+        // KL で other が 0 のセルに使う確率の下限
+        const KL_FLOOR: f64 = 1e-12;
+
+        if self.values.len() != other.values.len() {
+            return Err(Error::DimensionMismatch { expected: self.values.len(), found: other.values.len() });
+        }
+        let (p, q) = (self.normalized(), other.normalized());
+
+        let kl_divergence = p.values
+            .iter()
+            .zip(&q.values)
+            .filter(|(&pi, _)| pi > 0.0)
+            .map(|(&pi, &qi)| pi * math::ln(pi / qi.max(KL_FLOOR)))
+            .sum();
+        let l2_distance = p.values.iter().zip(&q.values).map(|(a, b)| (a - b) * (a - b)).sum::<f64>().sqrt();
+
+        let n = self.values.len() as f64;
+        let mean_a = self.values.iter().sum::<f64>() / n;
+        let mean_b = other.values.iter().sum::<f64>() / n;
+        let (mut cov, mut var_a, mut var_b) = (0.0, 0.0, 0.0);
+        for (a, b) in self.values.iter().zip(&other.values) {
+            cov += (a - mean_a) * (b - mean_b);
+            var_a += (a - mean_a) * (a - mean_a);
+            var_b += (b - mean_b) * (b - mean_b);
+        }
+        let correlation = if var_a > 0.0 && var_b > 0.0 { cov / (var_a * var_b).sqrt() } else { 0.0 };
+
+        let (peak_a, _) = self.argmax();
+        let (peak_b, _) = other.argmax();
+        let peak_offset = ((peak_a[0] - peak_b[0]).powi(2) + (peak_a[1] - peak_b[1]).powi(2)).sqrt();
+
+        Ok(FieldComparison { kl_divergence, l2_distance, correlation, peak_offset })
+    }
+
+    /// Inverse participation ratio `Σ v² / (Σ v)²`: 1 when all the mass is in one cell,
+    /// `1 / (nx * ny)` for a flat field. Its reciprocal is the effective number of
+    /// occupied cells. 0 if the field sums to zero.
+    pub fn concentration(&self) -> f64 {
+        let total: f64 = self.values.iter().sum();
+        if total <= 0.0 {
+            return 0.0;
+        }
+        self.values.iter().map(|v| v * v).sum::<f64>() / (total * total)
+    }
+
+    /// The `k` highest local maxima (cells above or equal to all 8 neighbours and above
+    /// zero) as `(cell center, value)`, highest first. Non-maximum suppression drops a
+    /// peak within `min_separation` of a higher one already taken.
+    pub fn find_peaks(&self, k: usize, min_separation: f32) -> Vec<([f32; 2], f64)> {
+        let mut candidates = Vec::new();
+        for iy in 0..self.ny {
+            for ix in 0..self.nx {
+                let v = self.get(ix, iy);
+                if v <= 0.0 {
+                    continue;
+                }
+                let is_max = (iy.saturating_sub(1)..(iy + 2).min(self.ny))
+                    .all(|ny| (ix.saturating_sub(1)..(ix + 2).min(self.nx)).all(|nx| self.get(nx, ny) <= v));
+                if is_max {
+                    candidates.push((self.cell_center(ix, iy), v));
+                }
+            }
+        }
+        candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        let mut peaks: Vec<([f32; 2], f64)> = Vec::with_capacity(k);
+        for (p, v) in candidates {
+            if peaks.len() == k {
+                break;
+            }
+            let separated = peaks.iter().all(|(q, _)| {
+                let (dx, dy) = (p[0] - q[0], p[1] - q[1]);
+                dx * dx + dy * dy >= min_separation * min_separation
+            });
+            if separated {
+                peaks.push((p, v));
+            }
+        }
+        peaks
+    }
+
+    /// Writes the values as a NumPy `.npy` file of little-endian `f64` with shape
+    /// `(ny, nx)`, row 0 = `y_min` as in `values`.
+    #[cfg(feature = "std")]
+    pub fn save_npy(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        // ヘッダーは magic (6) + 版 (2) + 長さ (2) を含めて 64 バイト境界まで空白で埋め、改行で終える
+        let mut header = format!("{{'descr': '<f8', 'fortran_order': False, 'shape': ({}, {}), }}", self.ny, self.nx);
+        let unpadded = 10 + header.len() + 1;
+        header.push_str(&" ".repeat((64 - unpadded % 64) % 64));
+        header.push('\n');
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(b"\x93NUMPY\x01\x00")?;
+        writer.write_all(&(header.len() as u16).to_le_bytes())?;
+        writer.write_all(header.as_bytes())?;
+        for v in &self.values {
+            writer.write_all(&v.to_le_bytes())?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Writes the field as an 8-bit grayscale PNG scaled so that its maximum is white,
+    /// with the top row at `y_max` (see [`world_to_pixel`]).
+    #[cfg(feature = "image")]
+    pub fn save_png(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let max = self.value_range().map_or(0.0, |(_, hi)| hi);
+        let scale = if max > 0.0 { 255.0 / max } else { 0.0 };
+        let img = image::GrayImage::from_fn(self.nx as u32, self.ny as u32, |col, row| {
+            let v = self.values[(self.ny - 1 - row as usize) * self.nx + col as usize];
+            image::Luma([(v * scale).round().clamp(0.0, 255.0) as u8])
+        });
+        img.save(path)?;
+        Ok(())
+    }
+
+    /// Value range `(min, max)` of the field, or `None` when it is empty.
+    pub fn value_range(&self) -> Option<(f64, f64)> {
+        let first = *self.values.first()?;
+        Some(self.values.iter().fold((first, first), |(lo, hi), &v| (lo.min(v), hi.max(v))))
+    }
+
+    /// Counts of field values in `bins` equal-width bins spanning [`Self::value_range`].
+    pub fn histogram(&self, bins: usize) -> Vec<usize> {
+        let mut counts = vec![0; bins];
+        let Some((lo, hi)) = self.value_range() else {
+            return counts;
+        };
+        if bins == 0 {
+            return counts;
+        }
+
+        let width = (hi - lo) / bins as f64;
+        for &v in &self.values {
+            let bin = if width > 0.0 { ((v - lo) / width) as usize } else { 0 };
+            counts[bin.min(bins - 1)] += 1;
+        }
+        counts
+    }
+
+    /// Threshold maximizing the between-class variance of the value histogram (Otsu's method).
+    pub fn otsu_threshold(&self) -> f64 {
+        const BINS: usize = 256;
+
+        let Some((lo, hi)) = self.value_range() else {
+            return 0.0;
+        };
+        if hi <= lo {
+            return lo;
+        }
+
+        let hist = self.histogram(BINS);
+        let width = (hi - lo) / BINS as f64;
+        let total = self.values.len() as f64;
+        let sum_all: f64 = hist.iter().enumerate().map(|(i, &c)| i as f64 * c as f64).sum();
+
+        let mut weight_bg = 0.0;
+        let mut sum_bg = 0.0;
+        let mut best = (0.0, 0);
+
+        for (t, &count) in hist.iter().enumerate() {
+            weight_bg += count as f64;
+            sum_bg += t as f64 * count as f64;
+
+            let weight_fg = total - weight_bg;
+            if weight_bg == 0.0 || weight_fg == 0.0 {
+                continue;
+            }
+
+            let mean_bg = sum_bg / weight_bg;
+            let mean_fg = (sum_all - sum_bg) / weight_fg;
+            let between = weight_bg * weight_fg * (mean_bg - mean_fg).powi(2);
+            if between > best.0 {
+                best = (between, t);
+            }
+        }
+
+        // 背景クラスの上端 (ビン境界) を閾値とする
+        lo + (best.1 + 1) as f64 * width
+    }
+
+    /// `true` where the value exceeds `threshold` (Otsu's threshold when `None`).
+    pub fn occupancy(&self, threshold: Option<f64>) -> Vec<bool> {
+        let threshold = threshold.unwrap_or_else(|| self.otsu_threshold());
+        self.values.iter().map(|&v| v > threshold).collect()
+    }
+
+    /// [`Self::occupancy`] packaged with its grid geometry as an [`OccupancyGrid`].
+    pub fn to_occupancy_grid(&self, threshold: Option<f64>) -> OccupancyGrid {
+        let (x_min, x_max, y_min, y_max) = self.bounds;
+        OccupancyGrid {
+            origin: [x_min, y_min],
+            cell_size: [(x_max - x_min) / self.nx as f32, (y_max - y_min) / self.ny as f32],
+            width: self.nx,
+            height: self.ny,
+            data: self
+                .occupancy(threshold)
+                .into_iter()
+                .map(|occupied| if occupied { OccupancyGrid::OCCUPIED } else { OccupancyGrid::FREE })
+                .collect(),
+        }
+    }
+
+    /// Unnormalized 2D DFT of the field values.
+    ///
+    /// Bins use the same row-major layout as `values` (`ky * nx + kx`) and are not
+    /// shifted: bin `kx` is spatial frequency `kx / width` cycles per world unit for
+    /// `kx < nx / 2` and `(kx - nx) / width` above that (likewise for `ky`), so DC is at 0.
+    #[cfg(feature = "fft")]
+    pub fn fft(&self) -> Vec<Complex<f64>> {
+        let (nx, ny) = (self.nx, self.ny);
+        let mut data: Vec<Complex<f64>> = self.values.iter().map(|&v| Complex::new(v, 0.0)).collect();
+        if data.is_empty() {
+            return data;
+        }
+
+        let mut planner = rustfft::FftPlanner::new();
+
+        // 行方向 (x) は連続メモリなのでそのまま一括変換
+        planner.plan_fft_forward(nx).process(&mut data);
+
+        // 列方向 (y) は一時バッファに集めて変換
+        let column_fft = planner.plan_fft_forward(ny);
+        let mut column = vec![Complex::new(0.0, 0.0); ny];
+        for ix in 0..nx {
+            for iy in 0..ny {
+                column[iy] = data[iy * nx + ix];
+            }
+            column_fft.process(&mut column);
+            for iy in 0..ny {
+                data[iy * nx + ix] = column[iy];
+            }
+        }
+
+        data
+    }
+
+    /// Marching-squares iso-lines for each of `levels`, stitched into world-space polylines.
+    ///
+    /// Closed contours repeat their first point at the end. Saddle cells are resolved
+    /// with the average of the four corners.
+    pub fn iso_contours(&self, levels: &[f64]) -> Vec<Vec<[f32; 2]>> {
+        let mut polylines = Vec::new();
+        if self.nx < 2 || self.ny < 2 {
+            return polylines;
+        }
+
+        for &level in levels {
+            polylines.extend(self.iso_contour_level(level));
+        }
+        polylines
+    }
+
+    fn iso_contour_level(&self, level: f64) -> Vec<Vec<[f32; 2]>> {
+        let (nx, ny) = (self.nx, self.ny);
+
+        // エッジID: 水平エッジ (ix,iy)-(ix+1,iy) と 垂直エッジ (ix,iy)-(ix,iy+1)
+        let num_h = (nx - 1) * ny;
+        let h_edge = |ix: usize, iy: usize| iy * (nx - 1) + ix;
+        let v_edge = |ix: usize, iy: usize| num_h + iy * nx + ix;
+
+        let crossing = |(ax, ay): (usize, usize), (bx, by): (usize, usize)| -> [f32; 2] {
+            let (va, vb) = (self.get(ax, ay), self.get(bx, by));
+            let t = if vb != va { ((level - va) / (vb - va)) as f32 } else { 0.5 };
+            let pa = self.cell_center(ax, ay);
+            let pb = self.cell_center(bx, by);
+            [pa[0] + t * (pb[0] - pa[0]), pa[1] + t * (pb[1] - pa[1])]
+        };
+
+        let mut points: BTreeMap<usize, [f32; 2]> = BTreeMap::new();
+        let mut segments: Vec<(usize, usize)> = Vec::new();
+
+        for iy in 0..ny - 1 {
+            for ix in 0..nx - 1 {
+                let corners = [
+                    (ix, iy),
+                    (ix + 1, iy),
+                    (ix + 1, iy + 1),
+                    (ix, iy + 1),
+                ];
+                let values = corners.map(|(cx, cy)| self.get(cx, cy));
+
+                let case = values
+                    .iter()
+                    .enumerate()
+                    .fold(0, |acc, (bit, &v)| if v >= level { acc | (1 << bit) } else { acc });
+                if case == 0 || case == 15 {
+                    continue;
+                }
+
+                // 0: bottom, 1: right, 2: top, 3: left
+                let edges = [
+                    (h_edge(ix, iy), corners[0], corners[1]),
+                    (v_edge(ix + 1, iy), corners[1], corners[2]),
+                    (h_edge(ix, iy + 1), corners[3], corners[2]),
+                    (v_edge(ix, iy), corners[0], corners[3]),
+                ];
+
+                let center_above = values.iter().sum::<f64>() / 4.0 >= level;
+                let pairs: &[(usize, usize)] = match case {
+                    1 | 14 => &[(3, 0)],
+                    2 | 13 => &[(0, 1)],
+                    3 | 12 => &[(3, 1)],
+                    4 | 11 => &[(1, 2)],
+                    6 | 9 => &[(0, 2)],
+                    7 | 8 => &[(3, 2)],
+                    5 if center_above => &[(0, 1), (2, 3)],
+                    5 => &[(3, 0), (1, 2)],
+                    10 if center_above => &[(3, 0), (1, 2)],
+                    _ => &[(0, 1), (2, 3)],
+                };
+
+                for &(a, b) in pairs {
+                    for e in [a, b] {
+                        let (id, pa, pb) = edges[e];
+                        points.entry(id).or_insert_with(|| crossing(pa, pb));
+                    }
+                    segments.push((edges[a].0, edges[b].0));
+                }
+            }
+        }
+
+        // セグメントを共有エッジでつなぎ、ポリラインにする (各エッジは高々2セグメントに属する)
+        let mut by_edge: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for (i, &(a, b)) in segments.iter().enumerate() {
+            by_edge.entry(a).or_default().push(i);
+            by_edge.entry(b).or_default().push(i);
+        }
+
+        let mut used = vec![false; segments.len()];
+        let next_segment = |edge: usize, used: &[bool]| {
+            by_edge[&edge].iter().copied().find(|&s| !used[s])
+        };
+
+        let mut polylines = Vec::new();
+        for start in 0..segments.len() {
+            if used[start] {
+                continue;
+            }
+            used[start] = true;
+
+            let (first, mut tail) = segments[start];
+            let mut chain = vec![first, tail];
+            while let Some(s) = next_segment(tail, &used) {
+                used[s] = true;
+                let (a, b) = segments[s];
+                tail = if a == tail { b } else { a };
+                chain.push(tail);
+            }
+
+            // 閉じていなければ逆方向にも伸ばす
+            if tail != first {
+                let mut head = first;
+                let mut prefix = Vec::new();
+                while let Some(s) = next_segment(head, &used) {
+                    used[s] = true;
+                    let (a, b) = segments[s];
+                    head = if a == head { b } else { a };
+                    prefix.push(head);
+                }
+                prefix.reverse();
+                prefix.extend(chain);
+                chain = prefix;
+            }
+
+            polylines.push(chain.iter().map(|id| points[id]).collect());
+        }
+
+        polylines
+    }
+}
+
+// ----------------------------------------------------------------------------
+//  Cached Field (Incremental Grid Updates)
+// ----------------------------------------------------------------------------
+
+/// The range field of a [`QuantumSlamCore`] (as `evaluate_grid_f64`) on a fixed
+/// grid, kept as the complex sum `ψ` per cell so that editing one landmark costs one
+/// pass over the grid: [`CachedField::update_landmark`] subtracts the landmark's old
+/// term and adds its new one instead of re-summing every landmark.
+#[derive(Clone, Debug)]
+pub struct CachedField {
+    bounds: Bounds,
+    nx: usize,
+    ny: usize,
+    // セルごとの ψ = [re, im]
+    psi: Vec<[f64; 2]>,
+    // ψ に加えた時点の各ランドマーク (引き戻し用)
+    terms: BTreeMap<LandmarkId, Landmark>,
+    // ψ を求めたときの (波数, decay_factor, time, propagation_speed)。変わったら全体を再評価
+    params: [f64; 4],
+}
+
+impl CachedField {
+    /// Evaluates every landmark of `core` on an `nx` x `ny` grid over `bounds`.
+    pub fn new(core: &QuantumSlamCore, bounds: Bounds, nx: usize, ny: usize) -> Self {
+        let mut cached = Self {
+            bounds,
+            nx,
+            ny,
+            psi: Vec::new(),
+            terms: BTreeMap::new(),
+            params: Self::params_of(core),
+        };
+        cached.refresh(core);
+        cached
+    }
+
+    fn params_of(core: &QuantumSlamCore) -> [f64; 4] {
+        [core.wave_number, core.decay_factor as f64, core.time as f64, core.propagation_speed as f64]
+    }
+
+    /// Re-evaluates the whole grid, for edits that touch every term (the wave number,
+    /// `decay_factor`, the clock) or many landmarks at once.
+    pub fn refresh(&mut self, core: &QuantumSlamCore) {
+        self.psi = vec![[0.0; 2]; self.nx * self.ny];
+        self.terms.clear();
+        self.params = Self::params_of(core);
+        for lm in &core.landmarks {
+            self.accumulate(lm, 1.0);
+            self.terms.insert(lm.id, *lm);
+        }
+    }
+
+    /// Brings the cached term of landmark `id` up to date with `core` after it moved or
+    /// was re-observed; a landmark new to the cache is added and one removed from `core`
+    /// is subtracted. Falls back to [`Self::refresh`] if a parameter shared by every
+    /// term changed since the last evaluation.
+    pub fn update_landmark(&mut self, core: &QuantumSlamCore, id: LandmarkId) {
+        if Self::params_of(core) != self.params {
+            self.refresh(core);
+            return;
+        }
+        if let Some(old) = self.terms.remove(&id) {
+            self.accumulate(&old, -1.0);
+        }
+        if let Some(lm) = core.landmarks.iter().find(|lm| lm.id == id) {
+            self.accumulate(lm, 1.0);
+            self.terms.insert(id, *lm);
+        }
+    }
+
+    // lm の項を sign 倍して全セルの ψ に加える
+    fn accumulate(&mut self, lm: &Landmark, sign: f64) {
+        let [k, decay, t, speed] = self.params;
+        // セル中心は evaluate_grid_f64 と同じく f64 で
+        let (x_min, x_max, y_min, y_max) = self.bounds;
+        let cell_w = (x_max as f64 - x_min as f64) / self.nx as f64;
+        let cell_h = (y_max as f64 - y_min as f64) / self.ny as f64;
+        for iy in 0..self.ny {
+            let y = y_min as f64 + (iy as f64 + 0.5) * cell_h;
+            for ix in 0..self.nx {
+                let x = x_min as f64 + (ix as f64 + 0.5) * cell_w;
+                let [re, im] = lm.range_term_f64(x, y, k, decay, t, speed);
+                let psi = &mut self.psi[iy * self.nx + ix];
+                psi[0] += sign * re;
+                psi[1] += sign * im;
+            }
+        }
+    }
+
+    /// The cached `|ψ|²` as a [`ProbabilityField`].
+    pub fn field(&self) -> ProbabilityField {
+        ProbabilityField {
+            bounds: self.bounds,
+            nx: self.nx,
+            ny: self.ny,
+            values: self.psi.iter().map(|[re, im]| re * re + im * im).collect(),
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------
+//  Radial Field (Polar Grid Evaluation Result)
+// ----------------------------------------------------------------------------
+
+/// Spacing of the radii of a polar sampling grid.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RadialSpacing {
+    /// `max_radius * (i + 1) / n_radial`.
+    Linear,
+    /// Geometric from `max_radius / n_radial` to `max_radius`: dense near the center.
+    Log,
+}
+
+/// A probability field sampled on a polar grid around `center`.
+///
+/// `values` is indexed `ir * angles.len() + ia`; see [`RadialField::position`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct RadialField {
+    pub center: [f32; 2],
+    pub radii: Vec<f32>,
+    pub angles: Vec<f32>,
+    pub values: Vec<f64>,
+}
+
+impl RadialField {
+    /// World coordinate of sample `(ir, ia)`.
+    pub fn position(&self, ir: usize, ia: usize) -> [f32; 2] {
+        let (sin, cos) = math::sin_cos(self.angles[ia]);
+        let r = self.radii[ir];
+        [self.center[0] + r * cos, self.center[1] + r * sin]
+    }
+
+    /// Every sample as `(position, value)`, in `values` order.
+    pub fn samples(&self) -> impl Iterator<Item = ([f32; 2], f64)> + '_ {
+        let n_angular = self.angles.len();
+        self.values
+            .iter()
+            .enumerate()
+            .map(move |(i, &v)| (self.position(i / n_angular, i % n_angular), v))
+    }
+}
+
+// ============================================================================
+//  2. Python Bindings (PyO3)
+// ============================================================================
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::exceptions::PyValueError;
+
+#[cfg(feature = "python")]
+impl From<Error> for PyErr {
+    fn from(e: Error) -> Self {
+        PyValueError::new_err(e.to_string())
+    }
+}
+
+#[cfg(feature = "python")]
+#[pyclass]
+pub struct PyQuantumSlam {
+    core: QuantumSlamCore,
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl PyQuantumSlam {
+    #[new]
+    #[pyo3(signature = (wave_number, decay_factor = None))]
+    fn new(wave_number: f64, decay_factor: Option<f32>) -> PyResult<Self> {
+        let mut slam = Self { core: QuantumSlamCore::new(wave_number)? };
+        if let Some(decay_factor) = decay_factor {
+            slam.set_decay_factor(decay_factor)?;
+        }
+        Ok(slam)
+    }
+
+    #[getter]
+    fn wave_number(&self) -> f64 {
+        self.core.wave_number
+    }
+
+    #[setter]
+    fn set_wave_number(&mut self, value: f64) -> PyResult<()> {
+        Ok(self.core.set_wave_number(value)?)
+    }
+
+    #[getter]
+    fn decay_factor(&self) -> f32 {
+        self.core.decay_factor
+    }
+
+    #[setter]
+    fn set_decay_factor(&mut self, value: f32) -> PyResult<()> {
+        Ok(self.core.set_decay_factor(value)?)
+    }
+
+    #[getter]
+    fn time(&self) -> f32 {
+        self.core.time
+    }
+
+    #[setter]
+    fn set_time(&mut self, value: f32) -> PyResult<()> {
+        Ok(self.core.set_time(value)?)
+    }
+
+    #[getter]
+    fn bearing_wave_number(&self) -> f64 {
+        self.core.bearing_wave_number
+    }
+
+    #[setter]
+    fn set_bearing_wave_number(&mut self, value: f64) -> PyResult<()> {
+        Ok(self.core.set_bearing_wave_number(value)?)
+    }
+
+    // 戻り値は set_observation に渡すランドマークID
+    fn add_landmark(&mut self, x: f32, y: f32) -> PyResult<u32> {
+        Ok(self.core.add_landmark(x, y)?.0)
+    }
+
+    #[pyo3(signature = (landmark_id, wave_number = None))]
+    fn set_landmark_wave_number(&mut self, landmark_id: u32, wave_number: Option<f32>) -> PyResult<()> {
+        Ok(self.core.set_landmark_wave_number(LandmarkId(landmark_id), wave_number)?)
+    }
+
+    fn set_landmark_velocity(&mut self, landmark_id: u32, vx: f32, vy: f32) -> PyResult<()> {
+        Ok(self.core.set_landmark_velocity(LandmarkId(landmark_id), [vx, vy])?)
+    }
+
+    fn propagate(&mut self, dt: f32) -> PyResult<()> {
+        Ok(self.core.propagate(dt)?)
+    }
+
+    fn set_landmark_radial_velocity(&mut self, landmark_id: u32, radial_velocity: f32) -> PyResult<()> {
+        Ok(self.core.set_landmark_radial_velocity(LandmarkId(landmark_id), radial_velocity)?)
+    }
+
+    fn observe_with_velocity(&mut self, cam_x: f32, cam_y: f32, vx: f32, vy: f32) -> PyResult<()> {
+        Ok(self.core.observe_with_velocity([cam_x, cam_y], [vx, vy])?)
+    }
+
+    fn set_landmark_sigma(&mut self, landmark_id: u32, sigma: f32) -> PyResult<()> {
+        Ok(self.core.set_landmark_sigma(LandmarkId(landmark_id), sigma)?)
+    }
+
+    fn set_observation(&mut self, landmark_id: u32, measured_dist: f32) -> PyResult<()> {
+        Ok(self.core.set_observation(LandmarkId(landmark_id), measured_dist)?)
+    }
+
+    fn observe_one(&mut self, landmark_id: u32, cam_x: f32, cam_y: f32) -> PyResult<()> {
+        Ok(self.core.observe_one(LandmarkId(landmark_id), [cam_x, cam_y])?)
+    }
+
+    fn associate(&mut self, measured_ranges: Vec<f32>) -> Vec<Option<u32>> {
+        self.core.associate(&measured_ranges).into_iter().map(|id| id.ok().map(|id| id.0)).collect()
+    }
+
+    fn observe_for_agent(&mut self, agent_id: u32, cam_x: f32, cam_y: f32) -> PyResult<()> {
+        Ok(self.core.observe_for_agent(agent_id, cam_x, cam_y)?)
+    }
+
+    fn probability_at_for_agent(&self, agent_id: u32, x: f32, y: f32) -> f64 {
+        self.core.probability_at_for_agent(agent_id, x, y)
+    }
+
+    fn update_observation(&mut self, cam_x: f32, cam_y: f32) -> PyResult<()> {
+        Ok(self.core.observe(cam_x, cam_y)?)
+    }
+
+    fn observe_bearing(&mut self, cam_x: f32, cam_y: f32, heading: f32) -> PyResult<()> {
+        Ok(self.core.observe_bearing([cam_x, cam_y], heading)?)
+    }
+
+    fn observe_range_bearing(&mut self, cam_x: f32, cam_y: f32, heading: f32) -> PyResult<()> {
+        Ok(self.core.observe_range_bearing([cam_x, cam_y], heading)?)
+    }
+
+    fn reset_observations(&mut self) {
+        self.core.reset_observations();
+    }
+
+    #[pyo3(signature = (min_confidence, max_landmarks = None))]
+    fn prune(&mut self, min_confidence: f32, max_landmarks: Option<usize>) -> usize {
+        self.core.prune(min_confidence, max_landmarks)
+    }
+
+    // other の座標系は (x, y, theta) にあるとして取り込む。戻り値は融合したランドマーク数
+    fn merge(&mut self, other: PyRef<'_, PyQuantumSlam>, x: f32, y: f32, theta: f32, merge_radius: f32) -> usize {
+        self.core.merge(&other.core, Pose2::new(x, y, theta), merge_radius)
+    }
+
+    #[getter]
+    fn landmark_radius(&self) -> f32 {
+        self.core.landmark_radius
+    }
+
+    #[setter]
+    fn set_landmark_radius(&mut self, value: f32) -> PyResult<()> {
+        Ok(self.core.set_landmark_radius(value)?)
+    }
+
+    fn simulate_scan(&self, cam_x: f32, cam_y: f32, heading: f32, n_beams: usize, max_range: f32) -> Vec<f32> {
+        self.core.simulate_scan([cam_x, cam_y], heading, n_beams, max_range)
+    }
+
+    fn observe_scan(&mut self, cam_x: f32, cam_y: f32, heading: f32, ranges: Vec<f32>, max_range: f32) -> PyResult<()> {
+        Ok(self.core.observe_scan([cam_x, cam_y], heading, &ranges, max_range)?)
+    }
+
+    fn set_decoherence(&mut self, confidence_rate: f32, phase_diffusion: f32) -> PyResult<()> {
+        Ok(self.core.set_decoherence(Decoherence { confidence_rate, phase_diffusion })?)
+    }
+
+    fn step(&mut self, dt: f32) -> PyResult<()> {
+        Ok(self.core.step(dt)?)
+    }
+
+    fn advance_time(&mut self, dt: f32) -> PyResult<()> {
+        Ok(self.core.advance_time(dt)?)
+    }
+
+    fn reinforce(&mut self, landmark_id: u32, weight: f32) -> PyResult<()> {
+        Ok(self.core.reinforce(LandmarkId(landmark_id), weight)?)
+    }
+
+    fn decay_all(&mut self, rate: f32) -> PyResult<()> {
+        Ok(self.core.decay_all(rate)?)
+    }
+
+    fn get_probability(&self, x: f32, y: f32) -> f64 {
+        self.core.probability_at(x, y)
+    }
+
+    fn get_probability_f64(&self, x: f64, y: f64) -> f64 {
+        self.core.probability_at_f64(x, y)
+    }
+
+    fn gradient_at(&self, x: f32, y: f32) -> [f64; 2] {
+        self.core.gradient_at(x, y)
+    }
+
+    // 複素振幅 ψ = (re, im)。get_probability は re² + im²
+    fn amplitude_at(&self, x: f32, y: f32) -> (f64, f64) {
+        let [re, im] = self.core.complex_field_at(x, y);
+        (re, im)
+    }
+
+    // ヒートマップ用: セル中心の確率を行優先 (index = iy * nx + ix, 行0 = y_min) で一括計算
+    fn probability_grid(&self, x_min: f32, x_max: f32, y_min: f32, y_max: f32, nx: usize, ny: usize) -> Vec<f64> {
+        self.core.evaluate_grid((x_min, x_max, y_min, y_max), nx, ny).values
+    }
+
+    // probability_grid を bounds 上の spatial_index (セル幅 cell_size, 打ち切り epsilon) で評価
+    fn probability_grid_indexed(&self, bounds: Bounds, nx: usize, ny: usize, cell_size: f32, epsilon: f32) -> PyResult<Vec<f64>> {
+        let index = self.core.spatial_index(bounds, cell_size, epsilon)?;
+        Ok(self.core.evaluate_grid_indexed(&index, bounds, nx, ny).values)
+    }
+
+    // 大きな格子をタイルごとに評価: callback(origin, nx, ny, values, progress)。
+    // values はタイル内で行優先、callback が False を返すとそこで打ち切る
+    fn probability_grid_tiled(&self, py: Python<'_>, bounds: Bounds, nx: usize, ny: usize, tile_size: usize, callback: PyObject) -> PyResult<()> {
+        let mut failure = None;
+        self.core.evaluate_grid_tiled(bounds, nx, ny, tile_size, |tile, progress| {
+            let args = (tile.origin, tile.field.nx, tile.field.ny, tile.field.values, progress);
+            match callback.call1(py, args).and_then(|keep| Ok(keep.is_none(py) || keep.is_true(py)?)) {
+                Ok(true) => ControlFlow::Continue(()),
+                Ok(false) => ControlFlow::Break(()),
+                Err(e) => {
+                    failure = Some(e);
+                    ControlFlow::Break(())
+                }
+            }
+        })?;
+        failure.map_or(Ok(()), Err)
+    }
+
+    // 和が 1 になるよう正規化したグリッド (並びは probability_grid と同じ)
+    fn normalized_grid(&self, bounds: Bounds, nx: usize, ny: usize) -> Vec<f64> {
+        self.core.normalized_grid(bounds, nx, ny).values
+    }
+
+    // bounds を nx x ny で評価したときに rect 内にある確率
+    fn mass_in_rect(&self, bounds: Bounds, nx: usize, ny: usize, rect: Bounds) -> f64 {
+        self.core.normalized_grid(bounds, nx, ny).mass_in_rect(rect)
+    }
+
+    // (origin, cell_size, width, height, data): data は行優先 (行0 = y_min)、占有 100 / 空き 0
+    #[pyo3(signature = (bounds, resolution, threshold = None))]
+    fn to_occupancy_grid(&self, bounds: Bounds, resolution: usize, threshold: Option<f64>) -> ([f32; 2], [f32; 2], usize, usize, Vec<u8>) {
+        let grid = self.core.to_occupancy_grid(bounds, resolution, threshold);
+        (grid.origin, grid.cell_size, grid.width, grid.height, grid.data)
+    }
+
+    fn auto_bounds(&self, padding: f32) -> Option<Bounds> {
+        self.core.auto_bounds(padding)
+    }
+
+    // bounds を省略するとランドマークの外接矩形 (10% の余白付き)
+    #[pyo3(signature = (path, nx, ny, bounds = None))]
+    fn save_npy(&self, path: &str, nx: usize, ny: usize, bounds: Option<Bounds>) -> PyResult<()> {
+        Ok(self.core.save_npy(path, bounds, nx, ny)?)
+    }
+
+    fn estimate_position(&self, bounds: Bounds, resolution: usize) -> ([f32; 2], f64) {
+        self.core.estimate_position(bounds, resolution)
+    }
+
+    fn estimate_pose(&self) -> Option<([f32; 2], f64)> {
+        self.core.estimate_pose()
+    }
+
+    // 軌跡は (time, [x, y], probability) のタプル
+    fn record_pose(&mut self) -> Option<(f32, [f32; 2], f64)> {
+        self.core.record_pose().map(|p| (p.time, p.position, p.probability))
+    }
+
+    fn trajectory(&self) -> Vec<(f32, [f32; 2], f64)> {
+        self.core.trajectory().iter().map(|p| (p.time, p.position, p.probability)).collect()
+    }
+
+    fn write_trajectory_csv(&self, path: &str) -> PyResult<()> {
+        Ok(self.core.write_trajectory_csv(path)?)
+    }
+
+    fn find_peaks(&self, k: usize, min_separation: f32) -> Vec<([f32; 2], f64)> {
+        self.core.find_peaks(k, min_separation)
+    }
+
+    fn field_entropy(&self, bounds: Bounds, resolution: usize) -> f64 {
+        self.core.field_entropy(bounds, resolution)
+    }
+
+    // (kl_divergence, l2_distance, correlation, peak_offset)
+    fn compare(&self, other: PyRef<'_, PyQuantumSlam>, bounds: Bounds, resolution: usize) -> (f64, f64, f64, f32) {
+        let c = self.core.compare(&other.core, bounds, resolution);
+        (c.kl_divergence, c.l2_distance, c.correlation, c.peak_offset)
+    }
+
+    fn field_concentration(&self, bounds: Bounds, resolution: usize) -> f64 {
+        self.core.field_concentration(bounds, resolution)
+    }
+
+    fn pose_covariance_at(&self, x: f32, y: f32) -> Option<[[f64; 2]; 2]> {
+        self.core.pose_covariance_at(x, y)
+    }
+
+    fn observe_and_estimate(&mut self, true_cam: [f32; 2], bounds: Bounds, resolution: usize) -> PyResult<([f32; 2], f64)> {
+        Ok(self.core.observe_and_estimate(true_cam, bounds, resolution)?)
+    }
+}
+
+// GpuFieldEvaluator の Python 版: PyQuantumSlam の格子を GPU で評価する
+#[cfg(feature = "python")]
+#[pyclass]
+pub struct PyGpuFieldEvaluator {
+    evaluator: GpuFieldEvaluator,
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl PyGpuFieldEvaluator {
+    // GPU が無ければ ValueError
+    #[new]
+    fn new() -> PyResult<Self> {
+        Ok(Self { evaluator: GpuFieldEvaluator::new()? })
+    }
+
+    // PyQuantumSlam.probability_grid と同じ並び (f32)
+    fn probability_grid(&mut self, sim: PyRef<'_, PyQuantumSlam>, bounds: Bounds, nx: usize, ny: usize) -> PyResult<Vec<f32>> {
+        Ok(self.evaluator.evaluate(&sim.core, bounds, nx, ny)?)
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymodule]
+fn inverse_observation_induced_probability_field_interference(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_class::<PyQuantumSlam>()?;
+    m.add_class::<PyGpuFieldEvaluator>()?;
+    Ok(())
+}
+
+// ----------------------------------------------------------------------------
+//  WASM Bindings (CPU only)
+// ----------------------------------------------------------------------------
+// GPU を初期化せずに JS から確率場を計算するためのラッパー (PyQuantumSlam の Web 版)
+
+/// `QuantumSlamCore` for JavaScript, usable without WebGPU. It is plain Rust as well,
+/// so the binding can be exercised natively.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub struct WasmSlamCore {
+    core: QuantumSlamCore,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl WasmSlamCore {
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new(wave_number: f64) -> Result<WasmSlamCore, Error> {
+        Ok(WasmSlamCore { core: QuantumSlamCore::new(wave_number)? })
+    }
+
+    pub fn add_landmark(&mut self, x: f32, y: f32) -> Result<(), Error> {
+        self.core.add_landmark(x, y)?;
+        Ok(())
+    }
+
+    pub fn observe(&mut self, cam_x: f32, cam_y: f32) -> Result<(), Error> {
+        self.core.observe(cam_x, cam_y)
+    }
+
+    // QuantumRenderer の decay_factor と揃えれば CPU と GPU の場が一致する
+    pub fn set_decay_factor(&mut self, decay_factor: f32) -> Result<(), Error> {
+        self.core.set_decay_factor(decay_factor)
+    }
+
+    pub fn probability_at(&self, x: f32, y: f32) -> f64 {
+        self.core.probability_at(x, y)
+    }
+
+    /// Complex amplitude `[re, im]` at `(x, y)` (a `Float64Array` in JS), e.g. for
+    /// phase-as-hue rendering; `probability_at` is `re² + im²`.
+    pub fn amplitude_at(&self, x: f32, y: f32) -> Vec<f64> {
+        self.core.complex_field_at(x, y).to_vec()
+    }
+
+    /// Row-major `nx * ny` grid of cell-center probabilities (row 0 = `y_min`), a
+    /// `Float64Array` in JS.
+    pub fn evaluate_grid(&self, x_min: f32, x_max: f32, y_min: f32, y_max: f32, nx: usize, ny: usize) -> Vec<f64> {
+        self.core.evaluate_grid((x_min, x_max, y_min, y_max), nx, ny).values
+    }
+}
+
+// ============================================================================
+//  3. WGPU Renderer (WASM / Visualization)
+// ============================================================================
+
+// レンダラー (wgpu) は std 必須。シェーダーソース自体は no_std でも参照できる
+const SHADER_SOURCE: &str = include_str!("shader.wgsl");
+#[cfg(feature = "std")]
+const BLIT_SHADER_SOURCE: &str = include_str!("blit.wgsl");
+#[cfg(feature = "std")]
+const STATS_SHADER_SOURCE: &str = include_str!("stats.wgsl");
+#[cfg(feature = "std")]
+const FIELD_SHADER_SOURCE: &str = include_str!("field.wgsl");
+
+/// The embedded WGSL compute shader source.
+pub fn shader_source() -> &'static str {
+    SHADER_SOURCE
+}
+
+/// Compiles the embedded shaders on a headless device and reports the first
+/// validation error, without needing a surface.
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+pub fn validate_shader() -> Result<(), Error> {
+    pollster::block_on(async {
+        let instance = wgpu::Instance::default();
+        let adapter = instance.request_adapter(&wgpu::RequestAdapterOptions::default())
+            .await
+            .ok_or_else(|| Error::GpuInit("No adapter found".into()))?;
+        let (device, _queue) = adapter.request_device(&wgpu::DeviceDescriptor::default(), None)
+            .await
+            .map_err(|e| Error::GpuInit(e.to_string()))?;
+
+        for (label, source) in [
+            ("Quantum Shader", SHADER_SOURCE),
+            ("Blit Shader", BLIT_SHADER_SOURCE),
+            ("Stats Shader", STATS_SHADER_SOURCE),
+            ("Field Shader", FIELD_SHADER_SOURCE),
+        ] {
+            device.push_error_scope(wgpu::ErrorFilter::Validation);
+            let _module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some(label),
+                source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(source)),
+            });
+            if let Some(err) = device.pop_error_scope().await {
+                return Err(Error::Shader(format!("{label}: {err}")));
+            }
+        }
+
+        Ok(())
+    })
+}
+
+// GPUランドマークバッファの初期容量
+#[cfg(feature = "std")]
+const DEFAULT_LANDMARK_CAPACITY: usize = 100;
+
+// shader.wgsl の @workgroup_size(16, 16) と一致させること
+#[cfg(feature = "std")]
+const WORKGROUP_SIZE: u32 = 16;
+
+// ----------------------------------------------------------------------------
+//  Shared GPU Pipeline (Browser Renderer / Headless Renderer)
+// ----------------------------------------------------------------------------
+
+// タイルごとのピクセル原点 (動的オフセット付きユニフォーム, 16byte)
+#[cfg(feature = "std")]
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct TileOrigin {
+    origin: [u32; 2],
+    _pad: [u32; 2],
+}
+
+/// One compute dispatch covering a rectangular block of pixels.
+#[cfg(feature = "std")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Tile {
+    pub origin: [u32; 2],
+    pub workgroups: [u32; 2],
+}
+
+/// Splits a `width` x `height` field into dispatches of at most `max_workgroups`
+/// workgroups per dimension (`maxComputeWorkgroupsPerDimension`).
+#[cfg(feature = "std")]
+pub fn plan_tiles(width: u32, height: u32, max_workgroups: u32) -> Vec<Tile> {
+    let max_workgroups = max_workgroups.max(1);
+    let groups_x = width.div_ceil(WORKGROUP_SIZE);
+    let groups_y = height.div_ceil(WORKGROUP_SIZE);
+
+    let mut tiles = Vec::new();
+    for gy in (0..groups_y).step_by(max_workgroups as usize) {
+        for gx in (0..groups_x).step_by(max_workgroups as usize) {
+            tiles.push(Tile {
+                origin: [gx * WORKGROUP_SIZE, gy * WORKGROUP_SIZE],
+                workgroups: [max_workgroups.min(groups_x - gx), max_workgroups.min(groups_y - gy)],
+            });
+        }
+    }
+    tiles
+}
+
+/// Field formulation computed by the renderer's compute shader; each is a separate
+/// entry point of `shader.wgsl`.
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum FieldMode {
+    /// `|Σ a e^{iφ}|²`: the interference field (`QuantumSlamCore::probability_at`).
+    #[default]
+    Coherent,
+    /// `Σ |a|²`: the amplitude envelope without interference.
+    Incoherent,
+    /// `|Σ a e^{iφ}| / Σ a`: how well the phases agree, in `[0, 1]`.
+    Phase,
+}
+
+#[cfg(feature = "std")]
+impl FieldMode {
+    pub fn entry_point(self) -> &'static str {
+        match self {
+            FieldMode::Coherent => "main_coherent",
+            FieldMode::Incoherent => "main_incoherent",
+            FieldMode::Phase => "main_phase",
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+fn create_field_pipeline(device: &wgpu::Device, mode: FieldMode) -> (wgpu::BindGroupLayout, wgpu::ComputePipeline) {
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Quantum Shader"),
+        source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(SHADER_SOURCE)),
+    });
+
+    let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Compute Bind Group Layout"),
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer { 
+                    ty: wgpu::BufferBindingType::Uniform, 
+                    has_dynamic_offset: false, 
+                    min_binding_size: None 
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer { 
+                    ty: wgpu::BufferBindingType::Storage { read_only: true }, 
+                    has_dynamic_offset: false, 
+                    min_binding_size: None 
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 2,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Texture { 
+                    sample_type: wgpu::TextureSampleType::Float { filterable: false }, 
+                    view_dimension: wgpu::TextureViewDimension::D2, 
+                    multisampled: false 
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 3,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::StorageTexture { 
+                    access: wgpu::StorageTextureAccess::WriteOnly, 
+                    format: wgpu::TextureFormat::Rgba8Unorm, 
+                    view_dimension: wgpu::TextureViewDimension::D2 
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 4,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: true,
+                    min_binding_size: wgpu::BufferSize::new(std::mem::size_of::<TileOrigin>() as u64),
+                },
+                count: None,
+            },
+        ],
+    });
+
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Pipeline Layout"),
+        bind_group_layouts: &[&bind_group_layout],
+        push_constant_ranges: &[],
+    });
+
+    let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: Some("Compute Pipeline"),
+        layout: Some(&pipeline_layout),
+        module: &shader,
+        entry_point: Some(mode.entry_point()),
+        compilation_options: wgpu::PipelineCompilationOptions::default(), 
+        cache: None,
+    });
+
+    (bind_group_layout, pipeline)
+}
+
+// Ping-Pong 用の確率場テクスチャ
+#[cfg(feature = "std")]
+fn create_field_texture(device: &wgpu::Device, width: u32, height: u32) -> (wgpu::Texture, wgpu::TextureView) {
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Probability Field"),
+        size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8Unorm,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING 
+             | wgpu::TextureUsages::STORAGE_BINDING 
+             | wgpu::TextureUsages::COPY_SRC 
+             | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    (texture, view)
+}
+
+#[cfg(feature = "std")]
+fn create_uniform_buffer(device: &wgpu::Device) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Uniform Buffer"),
+        size: std::mem::size_of::<Uniforms>() as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+// 1要素 item_size バイトのストレージバッファにデバイスが確保できる要素数
+#[cfg(feature = "std")]
+fn storage_buffer_capacity(limits: &wgpu::Limits, item_size: usize) -> usize {
+    let max_bytes = limits.max_buffer_size.min(limits.max_storage_buffer_binding_size as u64);
+    usize::try_from(max_bytes / item_size as u64).unwrap_or(usize::MAX)
+}
+
+#[cfg(feature = "std")]
+fn create_landmark_buffer(device: &wgpu::Device, capacity: usize) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Landmark Buffer"),
+        size: (std::mem::size_of::<Landmark>() * capacity) as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+// 1フレーム分のコンピュートパスで使うリソース
+#[cfg(feature = "std")]
+struct FieldBindings<'a> {
+    pipeline: &'a wgpu::ComputePipeline,
+    layout: &'a wgpu::BindGroupLayout,
+    uniforms: &'a wgpu::Buffer,
+    landmarks: &'a wgpu::Buffer,
+    input: &'a wgpu::TextureView,
+    output: &'a wgpu::TextureView,
+}
+
+// 大きな場を複数ディスパッチに分割して書き込む (各タイルの原点は動的オフセットで渡す)
+#[cfg(feature = "std")]
+struct TileDispatcher {
+    buffer: wgpu::Buffer,
+    stride: u64,
+    capacity: usize,
+    max_workgroups: u32,
+}
+
+#[cfg(feature = "std")]
+impl TileDispatcher {
+    fn new(device: &wgpu::Device) -> Self {
+        let limits = device.limits();
+        let stride = (std::mem::size_of::<TileOrigin>() as u64)
+            .next_multiple_of(limits.min_uniform_buffer_offset_alignment as u64);
+        Self {
+            buffer: Self::create_buffer(device, stride, 1),
+            stride,
+            capacity: 1,
+            max_workgroups: limits.max_compute_workgroups_per_dimension,
+        }
+    }
+
+    fn create_buffer(device: &wgpu::Device, stride: u64, capacity: usize) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Tile Buffer"),
+            size: stride * capacity as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    fn encode(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        bindings: &FieldBindings,
+        width: u32,
+        height: u32,
+    ) {
+        let tiles = plan_tiles(width, height, self.max_workgroups);
+        if tiles.len() > self.capacity {
+            self.capacity = tiles.len();
+            self.buffer = Self::create_buffer(device, self.stride, self.capacity);
+        }
+
+        let mut origins = vec![0u8; self.stride as usize * tiles.len()];
+        for (i, tile) in tiles.iter().enumerate() {
+            let entry = TileOrigin { origin: tile.origin, _pad: [0; 2] };
+            let start = i * self.stride as usize;
+            origins[start..start + std::mem::size_of::<TileOrigin>()].copy_from_slice(bytemuck::bytes_of(&entry));
+        }
+        queue.write_buffer(&self.buffer, 0, &origins);
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Frame BindGroup"),
+            layout: bindings.layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: bindings.uniforms.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: bindings.landmarks.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::TextureView(bindings.input) },
+                wgpu::BindGroupEntry { binding: 3, resource: wgpu::BindingResource::TextureView(bindings.output) },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                        buffer: &self.buffer,
+                        offset: 0,
+                        size: wgpu::BufferSize::new(std::mem::size_of::<TileOrigin>() as u64),
+                    }),
+                },
+            ],
+        });
+
+        let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: None, timestamp_writes: None });
+        cpass.set_pipeline(bindings.pipeline);
+        for (i, tile) in tiles.iter().enumerate() {
+            cpass.set_bind_group(0, &bind_group, &[(i as u64 * self.stride) as u32]);
+            cpass.dispatch_workgroups(tile.workgroups[0], tile.workgroups[1], 1);
+        }
+    }
+}
+
+// stats.wgsl の NUM_WORKGROUPS と一致させること
+#[cfg(feature = "std")]
+const STATS_WORKGROUPS: u32 = 64;
+
+// stats.wgsl の Stats 構造体: min, max, ワークグループごとの部分和
+#[cfg(feature = "std")]
+const STATS_BUFFER_SIZE: u64 = (2 + STATS_WORKGROUPS as u64) * 4;
+
+/// Minimum, maximum and mean of the field (the output texture's green channel, in `[0, 1]`).
+#[cfg(feature = "std")]
+pub type FieldStats = (f32, f32, f32);
+
+// 出力テクスチャの min/max/mean を求める縮約パス。結果は非同期に読み戻し、
+// 最新の値だけを保持する (読み戻し中のフレームは集計をスキップ)
+#[cfg(feature = "std")]
+struct FieldStatsPass {
+    pipeline: wgpu::ComputePipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    buffer: wgpu::Buffer,
+    readback: Arc<wgpu::Buffer>,
+    in_flight: Arc<AtomicBool>,
+    latest: Arc<Mutex<Option<FieldStats>>>,
+}
+
+#[cfg(feature = "std")]
+impl FieldStatsPass {
+    fn new(device: &wgpu::Device) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Stats Shader"),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(STATS_SHADER_SOURCE)),
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Stats Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: false },
+                        has_dynamic_offset: false,
+                        min_binding_size: wgpu::BufferSize::new(STATS_BUFFER_SIZE),
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Stats Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Stats Pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &shader,
+            entry_point: Some("main"),
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            cache: None,
+        });
+
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Stats Buffer"),
+            size: STATS_BUFFER_SIZE,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let readback = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Stats Readback Buffer"),
+            size: STATS_BUFFER_SIZE,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            pipeline,
+            bind_group_layout,
+            buffer,
+            readback: Arc::new(readback),
+            in_flight: Arc::new(AtomicBool::new(false)),
+            latest: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    // 縮約パスと読み戻し用コピーを記録する。前回の読み戻しが終わっていなければ何もしない
+    fn encode(&self, device: &wgpu::Device, queue: &wgpu::Queue, encoder: &mut wgpu::CommandEncoder, field: &wgpu::TextureView) -> bool {
+        if self.in_flight.load(Ordering::Acquire) {
+            return false;
+        }
+
+        let mut reset = [0u32; 2 + STATS_WORKGROUPS as usize];
+        reset[0] = 255;
+        queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&reset));
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Stats BindGroup"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(field) },
+                wgpu::BindGroupEntry { binding: 1, resource: self.buffer.as_entire_binding() },
+            ],
+        });
+
+        {
+            let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: Some("Stats Pass"), timestamp_writes: None });
+            cpass.set_pipeline(&self.pipeline);
+            cpass.set_bind_group(0, &bind_group, &[]);
+            cpass.dispatch_workgroups(STATS_WORKGROUPS, 1, 1);
+        }
+        encoder.copy_buffer_to_buffer(&self.buffer, 0, &self.readback, 0, STATS_BUFFER_SIZE);
+        true
+    }
+
+    // encode したコマンドの submit 後に呼ぶ。完了時に latest を更新する
+    fn request_readback(&self, pixel_count: u32) {
+        self.in_flight.store(true, Ordering::Release);
+
+        let readback = Arc::clone(&self.readback);
+        let in_flight = Arc::clone(&self.in_flight);
+        let latest = Arc::clone(&self.latest);
+        self.readback.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+            if result.is_ok() {
+                let stats = {
+                    let data = readback.slice(..).get_mapped_range();
+                    let words: &[u32] = bytemuck::cast_slice(&data);
+                    let sum: u64 = words[2..].iter().map(|&w| w as u64).sum();
+                    (
+                        words[0] as f32 / 255.0,
+                        words[1] as f32 / 255.0,
+                        (sum as f64 / pixel_count as f64 / 255.0) as f32,
+                    )
+                };
+                readback.unmap();
+                *latest.lock().unwrap() = Some(stats);
+            }
+            in_flight.store(false, Ordering::Release);
+        });
+    }
+
+    fn latest(&self) -> Option<FieldStats> {
+        *self.latest.lock().unwrap()
+    }
+}
+
+// ----------------------------------------------------------------------------
+//  GPU Field Evaluator (Native)
+// ----------------------------------------------------------------------------
+
+/// `QuantumSlamCore::evaluate_grid` on a native GPU without a renderer: owns only
+/// the device, the landmark buffer and the `field.wgsl` pass, and reads each field
+/// back as f32 (same values as [`HeadlessRenderer::compute_field`]).
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+pub struct GpuFieldEvaluator {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    landmark_buffer: wgpu::Buffer,
+    landmark_capacity: usize,
+    pass: FieldGridPass,
+}
+
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+impl GpuFieldEvaluator {
+    /// Opens the default adapter; fails with [`Error::GpuInit`] if there is none.
+    pub fn new() -> Result<Self, Error> {
+        pollster::block_on(async {
+            let instance = wgpu::Instance::default();
+            let adapter = instance.request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::HighPerformance,
+                compatible_surface: None,
+                force_fallback_adapter: false,
+            }).await.ok_or_else(|| Error::GpuInit("No adapter found".into()))?;
+
+            let (device, queue) = adapter.request_device(&wgpu::DeviceDescriptor {
+                label: Some("Quantum Field Evaluator Device"),
+                required_features: wgpu::Features::empty(),
+                required_limits: adapter.limits(),
+                memory_hints: wgpu::MemoryHints::default(),
+            }, None).await.map_err(|e| Error::GpuInit(e.to_string()))?;
+
+            let landmark_buffer = create_landmark_buffer(&device, DEFAULT_LANDMARK_CAPACITY);
+            let pass = FieldGridPass::new(&device);
+            Ok(Self { device, queue, landmark_buffer, landmark_capacity: DEFAULT_LANDMARK_CAPACITY, pass })
+        })
+    }
+
+    /// `|ψ|²` of `core` at the cell centers of an `nx` x `ny` grid over `bounds`,
+    /// row-major with row 0 at `y_min`.
+    pub fn evaluate(&mut self, core: &QuantumSlamCore, bounds: Bounds, nx: usize, ny: usize) -> Result<Vec<f32>, Error> {
+        let landmarks = &core.landmarks;
+        if landmarks.len() > self.landmark_capacity {
+            self.landmark_capacity = landmarks.len().next_power_of_two();
+            self.landmark_buffer = create_landmark_buffer(&self.device, self.landmark_capacity);
+        }
+        self.queue.write_buffer(&self.landmark_buffer, 0, bytemuck::cast_slice(landmarks));
+        self.pass.run(&self.device, &self.queue, &self.landmark_buffer, core, bounds, (nx, ny))
+    }
+
+    /// [`Self::evaluate`] as a [`ProbabilityField`].
+    pub fn evaluate_grid(&mut self, core: &QuantumSlamCore, bounds: Bounds, nx: usize, ny: usize) -> Result<ProbabilityField, Error> {
+        let values = self.evaluate(core, bounds, nx, ny)?;
+        Ok(ProbabilityField { bounds, nx, ny, values: values.into_iter().map(f64::from).collect() })
+    }
+}
+
+// ----------------------------------------------------------------------------
+//  Headless Renderer (Native)
+// ----------------------------------------------------------------------------
+
+// field.wgsl の FieldParams (40byte)
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct FieldParams {
+    bounds_min: [f32; 2],
+    bounds_max: [f32; 2],
+    grid: [u32; 2],
+    wave_number: f32,
+    decay_factor: f32,
+    num_landmarks: u32,
+    time: f32,
+    propagation_speed: f32,
+    _pad: u32,
+}
+
+// 可視化を介さず |psi|^2 を f32 で求めるパス (QuantumSlamCore::evaluate_grid の GPU 版)
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+struct FieldGridPass {
+    pipeline: wgpu::ComputePipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    params_buffer: wgpu::Buffer,
+}
+
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+impl FieldGridPass {
+    fn new(device: &wgpu::Device) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Field Shader"),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(FIELD_SHADER_SOURCE)),
+        });
+
+        let storage = |binding, read_only| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Field Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                storage(1, true),
+                storage(2, false),
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Field Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Field Pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &shader,
+            entry_point: Some("main"),
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            cache: None,
+        });
+
+        let params_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Field Params Buffer"),
+            size: std::mem::size_of::<FieldParams>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Self { pipeline, bind_group_layout, params_buffer }
+    }
+
+    // landmark_buffer に書き込み済みの core.landmarks で grid = (nx, ny) の |psi|^2 を求めて読み戻す
+    fn run(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        landmark_buffer: &wgpu::Buffer,
+        core: &QuantumSlamCore,
+        bounds: Bounds,
+        (nx, ny): (usize, usize),
+    ) -> Result<Vec<f32>, Error> {
+        let limits = device.limits();
+        if nx == 0 || ny == 0 {
+            return Ok(Vec::new());
+        }
+        let capacity = storage_buffer_capacity(&limits, std::mem::size_of::<f32>());
+        let cells = nx.saturating_mul(ny);
+        if cells > capacity {
+            return Err(Error::CapacityExceeded { requested: cells, capacity });
+        }
+        let size = (cells * std::mem::size_of::<f32>()) as u64;
+        let groups = ((nx as u32).div_ceil(WORKGROUP_SIZE), (ny as u32).div_ceil(WORKGROUP_SIZE));
+        if groups.0 > limits.max_compute_workgroups_per_dimension || groups.1 > limits.max_compute_workgroups_per_dimension {
+            return Err(Error::GpuInit(format!("A {}x{} grid exceeds the device dispatch limits", nx, ny)));
+        }
+
+        let (x_min, x_max, y_min, y_max) = bounds;
+        let params = FieldParams {
+            bounds_min: [x_min, y_min],
+            bounds_max: [x_max, y_max],
+            grid: [nx as u32, ny as u32],
+            wave_number: core.wave_number as f32,
+            decay_factor: core.decay_factor,
+            num_landmarks: core.landmarks.len() as u32,
+            time: core.time,
+            propagation_speed: core.propagation_speed,
+            _pad: 0,
+        };
+        queue.write_buffer(&self.params_buffer, 0, bytemuck::bytes_of(&params));
+
+        let values_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Field Values Buffer"),
+            size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Field Readback Buffer"),
+            size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Field BindGroup"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: self.params_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: landmark_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 2, resource: values_buffer.as_entire_binding() },
+            ],
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        {
+            let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: Some("Field Pass"), timestamp_writes: None });
+            cpass.set_pipeline(&self.pipeline);
+            cpass.set_bind_group(0, &bind_group, &[]);
+            cpass.dispatch_workgroups(groups.0, groups.1, 1);
+        }
+        encoder.copy_buffer_to_buffer(&values_buffer, 0, &readback, 0, size);
+        queue.submit(Some(encoder.finish()));
+
+        let slice = readback.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |_| {});
+        device.poll(wgpu::Maintain::Wait);
+
+        let values = bytemuck::cast_slice::<u8, f32>(&slice.get_mapped_range()).to_vec();
+        Ok(values)
+    }
+}
+
+/// Frames rendered by [`HeadlessRenderer::render_frame_to_bytes`] so the temporal
+/// feedback settles before the pixels are read.
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+pub const GOLDEN_WARMUP_FRAMES: u32 = 16;
+
+/// Offscreen version of the browser renderer for native use: runs the same compute
+/// shader into ping-pong textures and reads the result back.
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+pub struct HeadlessRenderer {
+    pub device: wgpu::Device,
+    pub queue: wgpu::Queue,
+    pub pipeline: wgpu::ComputePipeline,
+    pub bind_group_layout: wgpu::BindGroupLayout,
+
+    // Double Buffering
+    pub texture_a: wgpu::Texture,
+    pub texture_a_view: wgpu::TextureView,
+    pub texture_b: wgpu::Texture,
+    pub texture_b_view: wgpu::TextureView,
 
-#[repr(C)]
-#[derive(Copy, Clone, Debug, Pod, Zeroable, Serialize, Deserialize)]
-pub struct Landmark {
-    pub position: [f32; 2],
-    pub observed_dist: f32,
-    pub confidence: f32,
-    pub phase_offset: f32,
-}
+    pub uniform_buffer: wgpu::Buffer,
+    pub landmark_buffer: wgpu::Buffer,
+    landmark_capacity: usize,
+    tiles: TileDispatcher,
+    stats: Option<FieldStatsPass>,
+    grid_pass: Option<FieldGridPass>,
 
-#[repr(C)]
-#[derive(Copy, Clone, Debug, Pod, Zeroable)]
-pub struct Uniforms {
-    pub resolution: [f32; 2],
-    pub time: f32,
-    pub wave_number: f32,
-    pub decay_factor: f32,
-    pub feedback_strength: f32,
-    pub num_landmarks: u32,
-    pub _pad: u32, // WGSLのアライメント(8byte)調整用パディング
-    pub camera_pos: [f32; 2],
+    frame_count: u64,
+    width: u32,
+    height: u32,
 }
 
-// ============================================================================
-//  1. Physics Core (Pure Rust - CPU Implementation)
-// ============================================================================
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+impl HeadlessRenderer {
+    pub fn new(width: u32, height: u32) -> Result<Self, Error> {
+        pollster::block_on(async {
+            let instance = wgpu::Instance::default();
+            let adapter = instance.request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::HighPerformance,
+                compatible_surface: None,
+                force_fallback_adapter: false,
+            }).await.ok_or_else(|| Error::GpuInit("No adapter found".into()))?;
 
-pub struct QuantumSlamCore {
-    pub landmarks: Vec<Landmark>,
-    pub wave_number: f64,
-}
+            let limits = adapter.limits();
+            if width == 0 || height == 0 || width > limits.max_texture_dimension_2d || height > limits.max_texture_dimension_2d {
+                return Err(Error::InvalidParameter(format!(
+                    "Field size {}x{} is outside the supported range 1..={}",
+                    width, height, limits.max_texture_dimension_2d
+                )));
+            }
 
-impl QuantumSlamCore {
-    pub fn new(wave_number: f64) -> Self {
-        Self {
-            landmarks: Vec::new(),
-            wave_number,
+            let (device, queue) = adapter.request_device(&wgpu::DeviceDescriptor {
+                label: Some("Quantum Headless Device"),
+                required_features: wgpu::Features::empty(),
+                required_limits: limits,
+                memory_hints: wgpu::MemoryHints::default(),
+            }, None).await.map_err(|e| Error::GpuInit(e.to_string()))?;
+
+            let (bind_group_layout, pipeline) = create_field_pipeline(&device, FieldMode::Coherent);
+            let (texture_a, texture_a_view) = create_field_texture(&device, width, height);
+            let (texture_b, texture_b_view) = create_field_texture(&device, width, height);
+            let uniform_buffer = create_uniform_buffer(&device);
+            let landmark_buffer = create_landmark_buffer(&device, DEFAULT_LANDMARK_CAPACITY);
+            let tiles = TileDispatcher::new(&device);
+
+            Ok(Self {
+                device,
+                queue,
+                pipeline,
+                bind_group_layout,
+                texture_a,
+                texture_a_view,
+                texture_b,
+                texture_b_view,
+                uniform_buffer,
+                landmark_buffer,
+                landmark_capacity: DEFAULT_LANDMARK_CAPACITY,
+                tiles,
+                stats: None,
+                grid_pass: None,
+                frame_count: 0,
+                width,
+                height,
+            })
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Rebuilds the compute pipeline from the entry point of `mode`.
+    pub fn set_field_mode(&mut self, mode: FieldMode) {
+        (self.bind_group_layout, self.pipeline) = create_field_pipeline(&self.device, mode);
+    }
+
+    /// Caps the workgroups per dimension of each dispatch (at most the device limit),
+    /// splitting the field into more tiles.
+    pub fn set_max_tile_workgroups(&mut self, max_workgroups: u32) {
+        let limit = self.device.limits().max_compute_workgroups_per_dimension;
+        self.tiles.max_workgroups = max_workgroups.clamp(1, limit);
+    }
+
+    /// Runs one compute pass (one feedback step) with the given parameters.
+    /// `resolution` and `num_landmarks` are taken from the renderer and `landmarks`.
+    pub fn render(&mut self, uniforms: &Uniforms, landmarks: &[Landmark]) {
+        if landmarks.len() > self.landmark_capacity {
+            self.landmark_capacity = landmarks.len().next_power_of_two();
+            self.landmark_buffer = create_landmark_buffer(&self.device, self.landmark_capacity);
+        }
+        self.queue.write_buffer(&self.landmark_buffer, 0, bytemuck::cast_slice(landmarks));
+
+        let uniforms = Uniforms {
+            resolution: [self.width as f32, self.height as f32],
+            num_landmarks: landmarks.len() as u32,
+            ..*uniforms
+        };
+        self.queue.write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(&uniforms));
+
+        let (input, output) = if self.frame_count.is_multiple_of(2) {
+            (&self.texture_a_view, &self.texture_b_view)
+        } else {
+            (&self.texture_b_view, &self.texture_a_view)
+        };
+
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        let bindings = FieldBindings {
+            pipeline: &self.pipeline,
+            layout: &self.bind_group_layout,
+            uniforms: &self.uniform_buffer,
+            landmarks: &self.landmark_buffer,
+            input,
+            output,
+        };
+        self.tiles.encode(&self.device, &self.queue, &mut encoder, &bindings, self.width, self.height);
+
+        let mut stats_encoded = false;
+        if let Some(stats) = &self.stats {
+            // 前フレームの読み戻しを待ってから毎フレーム集計する
+            if stats.in_flight.load(Ordering::Acquire) {
+                self.device.poll(wgpu::Maintain::Wait);
+            }
+            stats_encoded = stats.encode(&self.device, &self.queue, &mut encoder, output);
         }
+        self.queue.submit(Some(encoder.finish()));
+        if let (Some(stats), true) = (&self.stats, stats_encoded) {
+            stats.request_readback(self.width * self.height);
+        }
+
+        self.frame_count += 1;
     }
 
-    pub fn add_landmark(&mut self, x: f32, y: f32) {
-        self.landmarks.push(Landmark {
-            position: [x, y],
-            observed_dist: 0.0, // Init
-            confidence: 1.0,
-            phase_offset: 0.0,
-        });
+    /// Zeroes both ping-pong textures, discarding the accumulated feedback history.
+    pub fn clear(&mut self) {
+        let zeros = vec![0u8; (self.width * self.height * 4) as usize];
+        for texture in [&self.texture_a, &self.texture_b] {
+            self.queue.write_texture(
+                wgpu::ImageCopyTexture { texture, mip_level: 0, origin: wgpu::Origin3d::ZERO, aspect: wgpu::TextureAspect::All },
+                &zeros,
+                wgpu::ImageDataLayout { offset: 0, bytes_per_row: Some(self.width * 4), rows_per_image: Some(self.height) },
+                wgpu::Extent3d { width: self.width, height: self.height, depth_or_array_layers: 1 },
+            );
+        }
+        self.frame_count = 0;
     }
 
-    pub fn observe(&mut self, true_cam_x: f32, true_cam_y: f32) {
-        for lm in &mut self.landmarks {
-            let dx = lm.position[0] - true_cam_x;
-            let dy = lm.position[1] - true_cam_y;
-            lm.observed_dist = (dx * dx + dy * dy).sqrt();
+    /// Deterministic frame for golden-image tests: clears the feedback history, renders
+    /// [`GOLDEN_WARMUP_FRAMES`] frames at the fixed time `t` and returns the RGBA8 pixels.
+    pub fn render_frame_to_bytes(&mut self, uniforms: &Uniforms, landmarks: &[Landmark], t: f64) -> Vec<u8> {
+        self.clear();
+        let uniforms = Uniforms { time: t as f32, ..*uniforms };
+        for _ in 0..GOLDEN_WARMUP_FRAMES {
+            self.render(&uniforms, landmarks);
         }
+        self.read_pixels()
     }
 
-    pub fn probability_at(&self, x: f32, y: f32) -> f64 {
-        let mut re_sum = 0.0;
-        let mut im_sum = 0.0;
+    /// Enables the per-frame min/max/mean reduction read by `field_stats`.
+    pub fn set_collect_stats(&mut self, enabled: bool) {
+        match (enabled, &self.stats) {
+            (true, None) => self.stats = Some(FieldStatsPass::new(&self.device)),
+            (false, Some(_)) => self.stats = None,
+            _ => {}
+        }
+    }
 
-        for lm in &self.landmarks {
-            let dx = x - lm.position[0];
-            let dy = y - lm.position[1];
-            let hypo_dist = (dx * dx + dy * dy).sqrt();
-            
-            let residual = hypo_dist - lm.observed_dist;
-            let phase = self.wave_number as f32 * residual;
-            let amp = lm.confidence * (-2.0 * residual.abs()).exp();
+    /// Field statistics of the most recent frame, or `None` if collection is off or no
+    /// frame has been rendered since enabling it.
+    pub fn field_stats(&self) -> Option<FieldStats> {
+        let stats = self.stats.as_ref()?;
+        self.device.poll(wgpu::Maintain::Wait);
+        stats.latest()
+    }
 
-            re_sum += amp * phase.cos();
-            im_sum += amp * phase.sin();
+    /// GPU evaluation of [`QuantumSlamCore::evaluate_grid`]: the same `|ψ|²` at the
+    /// same cell centers, in f32 and without the renderer's colour mapping, so the two
+    /// can be compared directly. Uses the renderer's landmark buffer but not its frames.
+    pub fn evaluate_grid(&mut self, core: &QuantumSlamCore, bounds: Bounds, nx: usize, ny: usize) -> Result<ProbabilityField, Error> {
+        let values = self.compute_field(core, bounds, nx, ny)?;
+        Ok(ProbabilityField { bounds, nx, ny, values: values.into_iter().map(f64::from).collect() })
+    }
+
+    /// The raw f32 values behind [`Self::evaluate_grid`], row-major with row 0 at
+    /// `y_min`, for callers that don't need the f64 field.
+    pub fn compute_field(&mut self, core: &QuantumSlamCore, bounds: Bounds, nx: usize, ny: usize) -> Result<Vec<f32>, Error> {
+        let landmarks = &core.landmarks;
+        if landmarks.len() > self.landmark_capacity {
+            self.landmark_capacity = landmarks.len().next_power_of_two();
+            self.landmark_buffer = create_landmark_buffer(&self.device, self.landmark_capacity);
         }
+        self.queue.write_buffer(&self.landmark_buffer, 0, bytemuck::cast_slice(landmarks));
 
-        (re_sum * re_sum + im_sum * im_sum) as f64
+        let pass = self.grid_pass.get_or_insert_with(|| FieldGridPass::new(&self.device));
+        pass.run(&self.device, &self.queue, &self.landmark_buffer, core, bounds, (nx, ny))
     }
-}
 
-// ============================================================================
-//  2. Python Bindings (PyO3)
-// ============================================================================
-#[cfg(feature = "python")]
-use pyo3::prelude::*;
+    /// RGBA8 pixels of the most recent frame, row-major from the top row.
+    pub fn read_pixels(&self) -> Vec<u8> {
+        let texture = if self.frame_count % 2 == 1 { &self.texture_b } else { &self.texture_a };
 
-#[cfg(feature = "python")]
-#[pyclass]
-pub struct PyQuantumSlam {
-    core: QuantumSlamCore,
-}
+        let unpadded_row = self.width * 4;
+        let padded_row = unpadded_row.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+        let readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Readback Buffer"),
+            size: (padded_row * self.height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
 
-#[cfg(feature = "python")]
-#[pymethods]
-impl PyQuantumSlam {
-    #[new]
-    fn new(wave_number: f64) -> Self {
-        Self { core: QuantumSlamCore::new(wave_number) }
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture { texture, mip_level: 0, origin: wgpu::Origin3d::ZERO, aspect: wgpu::TextureAspect::All },
+            wgpu::ImageCopyBuffer {
+                buffer: &readback,
+                layout: wgpu::ImageDataLayout { offset: 0, bytes_per_row: Some(padded_row), rows_per_image: Some(self.height) },
+            },
+            wgpu::Extent3d { width: self.width, height: self.height, depth_or_array_layers: 1 },
+        );
+        self.queue.submit(Some(encoder.finish()));
+
+        let slice = readback.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |_| {});
+        self.device.poll(wgpu::Maintain::Wait);
+
+        let padded = slice.get_mapped_range();
+        let mut pixels = Vec::with_capacity((unpadded_row * self.height) as usize);
+        for row in padded.chunks(padded_row as usize) {
+            pixels.extend_from_slice(&row[..unpadded_row as usize]);
+        }
+        pixels
     }
+}
 
-    fn add_landmark(&mut self, x: f32, y: f32) {
-        self.core.add_landmark(x, y);
+// ----------------------------------------------------------------------------
+//  Browser Renderer (WASM)
+// ----------------------------------------------------------------------------
+
+// JS 側には Error の表示文字列を投げる
+#[cfg(feature = "wasm")]
+impl From<Error> for JsValue {
+    fn from(e: Error) -> Self {
+        JsValue::from_str(&e.to_string())
     }
+}
+
+/// Sinusoidal modulation `amplitude * sin(frequency * t)` added on top of a base parameter.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Oscillation {
+    pub amplitude: f32,
+    pub frequency: f32, // rad/s
+}
 
-    fn update_observation(&mut self, cam_x: f32, cam_y: f32) {
-        self.core.observe(cam_x, cam_y);
+impl Oscillation {
+    /// `base + amplitude * sin(frequency * t)`; the renderer clamps the result to the
+    /// parameter's valid range.
+    pub fn value_at(&self, base: f32, t: f32) -> f32 {
+        base + self.amplitude * math::sin(self.frequency * t)
     }
 
-    fn get_probability(&self, x: f32, y: f32) -> f64 {
-        self.core.probability_at(x, y)
+    // 振幅と角周波数は有限でなければならない
+    fn check(&self) -> Result<(), Error> {
+        check_finite("Animation amplitude", self.amplitude)?;
+        check_finite("Animation frequency", self.frequency)
     }
 }
 
-#[cfg(feature = "python")]
-#[pymodule]
-fn inverse_observation_induced_probability_field_interference(_py: Python, m: &PyModule) -> PyResult<()> {
-    m.add_class::<PyQuantumSlam>()?;
+/// Camera position of the browser demo `t` seconds after start: a Lissajous path
+/// within `[-0.5, 0.5]²`, which `QuantumRenderer::camera_x`/`camera_y` read back.
+pub fn demo_camera_position(t: f64) -> [f32; 2] {
+    [math::sin(t * 0.5) as f32 * 0.5, math::cos(t * 0.3) as f32 * 0.5]
+}
+
+// 表示範囲は有限で、x_max > x_min かつ y_max > y_min
+fn check_viewport(viewport: Bounds) -> Result<(), Error> {
+    let (x_min, x_max, y_min, y_max) = viewport;
+    check_position([x_min, y_min])?;
+    check_position([x_max, y_max])?;
+    if !(x_max > x_min && y_max > y_min) {
+        return Err(Error::InvalidParameter("Viewport must have x_max > x_min and y_max > y_min".into()));
+    }
     Ok(())
 }
 
-// ============================================================================
-//  3. WGPU Renderer (WASM / Visualization)
-// ============================================================================
+/// A renderer session saved by `QuantumRenderer::export_scene`: the landmarks and the
+/// parameter overrides.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Scene {
+    pub landmarks: Vec<Landmark>,
+    pub wave_number: f32,
+    pub feedback_strength: f32,
+    pub decay_factor: f32,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub propagation_speed: f32,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub decay_oscillation: Oscillation,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub feedback_oscillation: Oscillation,
+    /// `None` keeps the renderer's current viewport on import.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub viewport: Option<Bounds>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub feedback_mode: FeedbackMode,
+}
 
-#[cfg(feature = "wasm")]
-const SHADER_SOURCE: &str = include_str!("shader.wgsl");
+impl Scene {
+    /// Checks every value as the renderer's individual setters do: finite landmark
+    /// positions, a valid wave number, feedback strength and viewport, a non-negative
+    /// decay and finite speed and animations.
+    pub fn validate(&self) -> Result<(), Error> {
+        for lm in &self.landmarks {
+            check_position(lm.position)?;
+        }
+        check_wave_number(self.wave_number as f64)?;
+        check_feedback_strength(self.feedback_strength)?;
+        check_non_negative("decay_factor", self.decay_factor)?;
+        check_finite("propagation_speed", self.propagation_speed)?;
+        self.decay_oscillation.check()?;
+        self.feedback_oscillation.check()?;
+        if let Some(viewport) = self.viewport {
+            check_viewport(viewport)?;
+        }
+        Ok(())
+    }
+
+    /// Serializes the scene to JSON.
+    #[cfg(any(feature = "wasm", feature = "persist"))]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("scene is always serializable")
+    }
+
+    /// Parses a scene saved by [`Self::to_json`] and [validates](Self::validate) it.
+    #[cfg(any(feature = "wasm", feature = "persist"))]
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let scene: Self = serde_json::from_str(json).map_err(|e| Error::InvalidParameter(e.to_string()))?;
+        scene.validate()?;
+        Ok(scene)
+    }
+}
 
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
@@ -150,6 +5682,12 @@ pub struct QuantumRenderer {
     pub pipeline: wgpu::ComputePipeline,
     #[wasm_bindgen(skip)]
     pub bind_group_layout: wgpu::BindGroupLayout,
+
+    // Present (Compute Texture -> Surface)
+    #[wasm_bindgen(skip)]
+    pub blit_pipeline: wgpu::RenderPipeline,
+    #[wasm_bindgen(skip)]
+    pub blit_bind_group_layout: wgpu::BindGroupLayout,
     
     // Double Buffering
     #[wasm_bindgen(skip)]
@@ -165,6 +5703,9 @@ pub struct QuantumRenderer {
     pub uniform_buffer: wgpu::Buffer,
     #[wasm_bindgen(skip)]
     pub landmark_buffer: wgpu::Buffer,
+    landmark_capacity: usize,
+    tiles: TileDispatcher,
+    stats: Option<FieldStatsPass>,
     
     start_time: f64,
     frame_count: u64,
@@ -172,10 +5713,21 @@ pub struct QuantumRenderer {
     // Physics State
     landmarks: Vec<Landmark>,
     camera_pos: [f32; 2],
+    // 1つのランドマークの寄与だけを表示する (None = 全ランドマーク)
+    solo_landmark: Option<u32>,
+    // 描画するワールド範囲 (x_min, x_max, y_min, y_max)
+    viewport: Bounds,
     
     // Interactive Parameters
     pub wave_number: f32,
     pub feedback_strength: f32,
+    pub decay_factor: f32,
+    pub propagation_speed: f32,
+    feedback_mode: FeedbackMode,
+
+    // Parameter Animation (振幅0で一定値)
+    decay_oscillation: Oscillation,
+    feedback_oscillation: Oscillation,
     
     width: u32,
     height: u32,
@@ -218,8 +5770,9 @@ impl QuantumRenderer {
             .find(|f| *f == wgpu::TextureFormat::Rgba8Unorm)
             .unwrap_or(surface_caps.formats[0]);
 
+        // sRGB専用のアダプタでも動くよう、サーフェスへはコピーではなくブリットで描画する
         let config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_DST,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
             format: surface_format,
             width,
             height,
@@ -231,113 +5784,75 @@ impl QuantumRenderer {
         surface.configure(&device, &config);
 
         // Ping-Pong Textures
-        let texture_desc = wgpu::TextureDescriptor {
-            label: Some("Probability Field"),
-            size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Rgba8Unorm,
-            usage: wgpu::TextureUsages::TEXTURE_BINDING 
-                 | wgpu::TextureUsages::STORAGE_BINDING 
-                 | wgpu::TextureUsages::COPY_SRC 
-                 | wgpu::TextureUsages::COPY_DST,
-            view_formats: &[],
-        };
-
-        let texture_a = device.create_texture(&texture_desc);
-        let texture_b = device.create_texture(&texture_desc);
-        let texture_a_view = texture_a.create_view(&wgpu::TextureViewDescriptor::default());
-        let texture_b_view = texture_b.create_view(&wgpu::TextureViewDescriptor::default());
+        let (texture_a, texture_a_view) = create_field_texture(&device, width, height);
+        let (texture_b, texture_b_view) = create_field_texture(&device, width, height);
 
         // Buffers
-        let uniform_size = std::mem::size_of::<Uniforms>() as wgpu::BufferAddress;
-        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Uniform Buffer"),
-            size: uniform_size,
-            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-        });
-
-        let landmark_size = (std::mem::size_of::<Landmark>() * 100) as wgpu::BufferAddress;
-        let landmark_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Landmark Buffer"),
-            size: landmark_size,
-            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-        });
+        let uniform_buffer = create_uniform_buffer(&device);
+        let landmark_buffer = create_landmark_buffer(&device, DEFAULT_LANDMARK_CAPACITY);
+        let tiles = TileDispatcher::new(&device);
 
         // Pipeline
-        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Quantum Shader"),
-            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(SHADER_SOURCE)),
+        let (bind_group_layout, pipeline) = create_field_pipeline(&device, FieldMode::Coherent);
+
+        // Blit Pipeline
+        let blit_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Blit Shader"),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(BLIT_SHADER_SOURCE)),
         });
 
-        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            label: Some("Compute Bind Group Layout"),
+        let blit_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Blit Bind Group Layout"),
             entries: &[
                 wgpu::BindGroupLayoutEntry {
                     binding: 0,
-                    visibility: wgpu::ShaderStages::COMPUTE,
-                    ty: wgpu::BindingType::Buffer { 
-                        ty: wgpu::BufferBindingType::Uniform, 
-                        has_dynamic_offset: false, 
-                        min_binding_size: None 
-                    },
-                    count: None,
-                },
-                wgpu::BindGroupLayoutEntry {
-                    binding: 1,
-                    visibility: wgpu::ShaderStages::COMPUTE,
-                    ty: wgpu::BindingType::Buffer { 
-                        ty: wgpu::BufferBindingType::Storage { read_only: true }, 
-                        has_dynamic_offset: false, 
-                        min_binding_size: None 
-                    },
-                    count: None,
-                },
-                wgpu::BindGroupLayoutEntry {
-                    binding: 2,
-                    visibility: wgpu::ShaderStages::COMPUTE,
-                    ty: wgpu::BindingType::Texture { 
-                        sample_type: wgpu::TextureSampleType::Float { filterable: false }, 
-                        view_dimension: wgpu::TextureViewDimension::D2, 
-                        multisampled: false 
-                    },
-                    count: None,
-                },
-                wgpu::BindGroupLayoutEntry {
-                    binding: 3,
-                    visibility: wgpu::ShaderStages::COMPUTE,
-                    ty: wgpu::BindingType::StorageTexture { 
-                        access: wgpu::StorageTextureAccess::WriteOnly, 
-                        format: wgpu::TextureFormat::Rgba8Unorm, 
-                        view_dimension: wgpu::TextureViewDimension::D2 
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false
                     },
                     count: None,
                 },
             ],
         });
 
-        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: Some("Pipeline Layout"),
-            bind_group_layouts: &[&bind_group_layout],
+        let blit_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Blit Pipeline Layout"),
+            bind_group_layouts: &[&blit_bind_group_layout],
             push_constant_ranges: &[],
         });
 
-        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
-            label: Some("Compute Pipeline"),
-            layout: Some(&pipeline_layout),
-            module: &shader,
-            entry_point: Some("main"),
-            compilation_options: wgpu::PipelineCompilationOptions::default(), 
+        let blit_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Blit Pipeline"),
+            layout: Some(&blit_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &blit_shader,
+                entry_point: Some("vs_main"),
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &blit_shader,
+                entry_point: Some("fs_main"),
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: surface_format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
             cache: None,
         });
 
         let landmarks = vec![
-            Landmark { position: [0.0, 0.5], observed_dist: 0.0, confidence: 1.0, phase_offset: 0.0 },
-            Landmark { position: [0.5, -0.5], observed_dist: 0.0, confidence: 1.0, phase_offset: 0.0 },
-            Landmark { position: [-0.5, -0.5], observed_dist: 0.0, confidence: 1.0, phase_offset: 0.0 },
+            Landmark::new(0.0, 0.5),
+            Landmark::new(0.5, -0.5),
+            Landmark::new(-0.5, -0.5),
         ];
 
         Ok(Self {
@@ -347,100 +5862,262 @@ impl QuantumRenderer {
             config,
             pipeline,
             bind_group_layout,
+            blit_pipeline,
+            blit_bind_group_layout,
             texture_a,
             texture_a_view,
             texture_b,
             texture_b_view,
             uniform_buffer,
             landmark_buffer,
+            landmark_capacity: DEFAULT_LANDMARK_CAPACITY,
+            tiles,
+            stats: None,
             start_time: js_sys::Date::now(),
             frame_count: 0,
             landmarks,
             camera_pos: [0.0, 0.0],
+            solo_landmark: None,
+            // 縦 [-1, 1] を基準にアスペクト比を維持
+            viewport: (-(width as f32 / height as f32), width as f32 / height as f32, -1.0, 1.0),
             width,
             height,
             // デフォルトパラメータ
             wave_number: 80.0,
             feedback_strength: 0.90,
+            decay_factor: 5.0,
+            propagation_speed: 0.0,
+            feedback_mode: FeedbackMode::Uniform,
+            decay_oscillation: Oscillation::default(),
+            feedback_oscillation: Oscillation::default(),
         })
     }
 
     // JSから動的にパラメータを変更するためのセッター
-    pub fn set_wave_number(&mut self, val: f32) {
+    pub fn set_wave_number(&mut self, val: f32) -> Result<(), Error> {
+        check_wave_number(val as f64)?;
         self.wave_number = val;
+        Ok(())
     }
 
-    pub fn set_feedback_strength(&mut self, val: f32) {
+    pub fn set_feedback_strength(&mut self, val: f32) -> Result<(), Error> {
+        check_feedback_strength(val)?;
         self.feedback_strength = val;
+        Ok(())
+    }
+
+    // Confidence: 確率の高い領域ほど前フレームを保持し、低い領域は速く更新する
+    pub fn set_feedback_mode(&mut self, mode: FeedbackMode) {
+        self.feedback_mode = mode;
+    }
+
+    // 0 で静止した干渉パターン、正の値で外向きに進む波面
+    pub fn set_propagation_speed(&mut self, val: f32) -> Result<(), Error> {
+        check_finite("propagation_speed", val)?;
+        self.propagation_speed = val;
+        Ok(())
+    }
+
+    // decay_factor = base + amp * sin(freq * t) (amp > base でも負にならないよう 0 でクランプ)
+    pub fn set_decay_animation(&mut self, base: f32, amp: f32, freq: f32) -> Result<(), Error> {
+        let oscillation = Oscillation { amplitude: amp, frequency: freq };
+        check_non_negative("decay_factor", base)?;
+        oscillation.check()?;
+        self.decay_factor = base;
+        self.decay_oscillation = oscillation;
+        Ok(())
+    }
+
+    // feedback_strength = base + amp * sin(freq * t) ([0, 1] にクランプ)
+    pub fn set_feedback_animation(&mut self, base: f32, amp: f32, freq: f32) -> Result<(), Error> {
+        let oscillation = Oscillation { amplitude: amp, frequency: freq };
+        check_feedback_strength(base)?;
+        oscillation.check()?;
+        self.feedback_strength = base;
+        self.feedback_oscillation = oscillation;
+        Ok(())
+    }
+
+    // 大きなマップ向けに、GPUランドマークバッファを事前に確保し直す
+    pub fn set_landmark_buffer_capacity(&mut self, capacity: usize) -> Result<(), Error> {
+        let limits = self.device.limits();
+        let max_capacity = storage_buffer_capacity(&limits, std::mem::size_of::<Landmark>());
+
+        if capacity == 0 {
+            return Err(Error::InvalidParameter("Landmark buffer capacity must be at least 1".into()));
+        }
+        if capacity < self.landmarks.len() {
+            return Err(Error::InvalidParameter(format!("Capacity {} is smaller than the {} current landmarks", capacity, self.landmarks.len())));
+        }
+        if capacity > max_capacity {
+            return Err(Error::CapacityExceeded { requested: capacity, capacity: max_capacity });
+        }
+
+        // バインドグループは毎フレーム作り直すので、バッファの差し替えだけで良い
+        self.landmark_buffer = create_landmark_buffer(&self.device, capacity);
+        self.landmark_capacity = capacity;
+        self.queue.write_buffer(&self.landmark_buffer, 0, bytemuck::cast_slice(&self.landmarks));
+        Ok(())
+    }
+
+    pub fn landmark_buffer_capacity(&self) -> usize {
+        self.landmark_capacity
+    }
+
+    // 画面に表示するワールド範囲 (y上向き)。ランドマーク・カメラ位置は同じワールド座標
+    pub fn set_viewport(&mut self, x_min: f32, x_max: f32, y_min: f32, y_max: f32) -> Result<(), Error> {
+        check_viewport((x_min, x_max, y_min, y_max))?;
+        self.viewport = (x_min, x_max, y_min, y_max);
+        Ok(())
+    }
+
+    // 場の定式化を切り替える (コンピュートパイプラインを指定のエントリポイントで作り直す)
+    pub fn set_field_mode(&mut self, mode: FieldMode) {
+        (self.bind_group_layout, self.pipeline) = create_field_pipeline(&self.device, mode);
+    }
+
+    // 指定したランドマークの干渉パターンだけを表示する (None で通常表示に戻す)
+    pub fn set_solo_landmark(&mut self, index: Option<u32>) {
+        self.solo_landmark = index;
+    }
+
+    /// Serializes the landmarks and parameter overrides to a JSON string.
+    pub fn export_scene(&self) -> String {
+        let scene = Scene {
+            landmarks: self.landmarks.clone(),
+            wave_number: self.wave_number,
+            feedback_strength: self.feedback_strength,
+            decay_factor: self.decay_factor,
+            propagation_speed: self.propagation_speed,
+            decay_oscillation: self.decay_oscillation,
+            feedback_oscillation: self.feedback_oscillation,
+            viewport: Some(self.viewport),
+            feedback_mode: self.feedback_mode,
+        };
+        scene.to_json()
+    }
+
+    /// Restores a scene produced by `export_scene`, growing the landmark buffer if needed.
+    /// The values are checked by [`Scene::validate`]; an invalid scene is rejected
+    /// without changing the renderer.
+    pub fn import_scene(&mut self, json: &str) -> Result<(), Error> {
+        let scene = Scene::from_json(json)?;
+        let viewport = scene.viewport.unwrap_or(self.viewport);
+
+        if scene.landmarks.len() > self.landmark_capacity {
+            self.set_landmark_buffer_capacity(scene.landmarks.len().next_power_of_two())?;
+        }
+
+        let (x_min, x_max, y_min, y_max) = viewport;
+        self.set_viewport(x_min, x_max, y_min, y_max)?;
+        self.landmarks = scene.landmarks;
+        self.wave_number = scene.wave_number;
+        self.feedback_strength = scene.feedback_strength;
+        self.decay_factor = scene.decay_factor;
+        self.propagation_speed = scene.propagation_speed;
+        self.decay_oscillation = scene.decay_oscillation;
+        self.feedback_oscillation = scene.feedback_oscillation;
+        self.feedback_mode = scene.feedback_mode;
+        self.queue.write_buffer(&self.landmark_buffer, 0, bytemuck::cast_slice(&self.landmarks));
+        Ok(())
+    }
+
+    // 現在のカメラ位置をJS側へ読み出すためのゲッター
+    pub fn camera_x(&self) -> f32 {
+        self.camera_pos[0]
+    }
+
+    pub fn camera_y(&self) -> f32 {
+        self.camera_pos[1]
     }
 
     pub fn update(&mut self) {
         let now = js_sys::Date::now();
         let t = (now - self.start_time) / 1000.0;
         
-        self.camera_pos = [
-            (t * 0.5).sin() as f32 * 0.5,
-            (t * 0.3).cos() as f32 * 0.5
-        ];
+        self.camera_pos = demo_camera_position(t);
 
         for lm in &mut self.landmarks {
-            let dx = lm.position[0] - self.camera_pos[0];
-            let dy = lm.position[1] - self.camera_pos[1];
-            lm.observed_dist = (dx*dx + dy*dy).sqrt();
+            lm.observed_dist = lm.distance_to(self.camera_pos[0], self.camera_pos[1]);
             lm.phase_offset = (t as f32 * 2.0).sin() * 0.5;
         }
 
-        self.queue.write_buffer(&self.landmark_buffer, 0, bytemuck::cast_slice(&self.landmarks));
+        // ソロ表示中は対象のランドマークだけを GPU に送る
+        let visible = match self.solo_landmark {
+            Some(i) => self.landmarks.get(i as usize..=i as usize).unwrap_or(&[]),
+            None => &self.landmarks[..],
+        };
+        self.queue.write_buffer(&self.landmark_buffer, 0, bytemuck::cast_slice(visible));
 
-        let uniforms = Uniforms {
-            resolution: [self.width as f32, self.height as f32],
-            time: t as f32,
+        let uniforms = Uniforms::builder()
+            .resolution(self.width, self.height)
+            .time(t as f32)
             // メンバ変数の値を使用
-            wave_number: self.wave_number,
-            decay_factor: 5.0,
-            feedback_strength: self.feedback_strength,
-            num_landmarks: self.landmarks.len() as u32,
-            _pad: 0,
-            camera_pos: self.camera_pos,
-        };
-        self.queue.write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(&uniforms));
+            .wave_number(self.wave_number)
+            .decay_factor(self.decay_oscillation.value_at(self.decay_factor, t as f32).max(0.0))
+            .feedback_strength(self.feedback_oscillation.value_at(self.feedback_strength, t as f32).clamp(0.0, 1.0))
+            .feedback_mode(self.feedback_mode)
+            .num_landmarks(visible.len())
+            .landmark_capacity(self.landmark_capacity)
+            .propagation_speed(self.propagation_speed)
+            .camera_pos(self.camera_pos)
+            .viewport(self.viewport)
+            .build();
+        // 0x0 のキャンバスなど描画できない状態ではこのフレームを飛ばし、前回の uniforms を使う
+        if let Ok(uniforms) = uniforms {
+            self.queue.write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(&uniforms));
+        }
     }
 
     pub fn render(&mut self) {
         let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
 
-        let (input_view, output_view, source_tex) = if self.frame_count % 2 == 0 {
-            (&self.texture_a_view, &self.texture_b_view, &self.texture_b)
+        let (input_view, output_view) = if self.frame_count.is_multiple_of(2) {
+            (&self.texture_a_view, &self.texture_b_view)
         } else {
-            (&self.texture_b_view, &self.texture_a_view, &self.texture_a)
+            (&self.texture_b_view, &self.texture_a_view)
         };
 
-        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Frame BindGroup"),
+        let bindings = FieldBindings {
+            pipeline: &self.pipeline,
             layout: &self.bind_group_layout,
-            entries: &[
-                wgpu::BindGroupEntry { binding: 0, resource: self.uniform_buffer.as_entire_binding() },
-                wgpu::BindGroupEntry { binding: 1, resource: self.landmark_buffer.as_entire_binding() },
-                wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::TextureView(input_view) },
-                wgpu::BindGroupEntry { binding: 3, resource: wgpu::BindingResource::TextureView(output_view) },
-            ],
-        });
+            uniforms: &self.uniform_buffer,
+            landmarks: &self.landmark_buffer,
+            input: input_view,
+            output: output_view,
+        };
+        self.tiles.encode(&self.device, &self.queue, &mut encoder, &bindings, self.width, self.height);
 
-        {
-            let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: None, timestamp_writes: None });
-            cpass.set_pipeline(&self.pipeline);
-            cpass.set_bind_group(0, &bind_group, &[]);
-            cpass.dispatch_workgroups((self.width + 15) / 16, (self.height + 15) / 16, 1);
-        }
+        let stats_encoded = self.stats.as_ref()
+            .is_some_and(|stats| stats.encode(&self.device, &self.queue, &mut encoder, output_view));
 
         if let Some(surface_texture) = self.get_current_texture() {
-            let _surface_view = surface_texture.texture.create_view(&wgpu::TextureViewDescriptor::default());
-            
-            encoder.copy_texture_to_texture(
-                wgpu::ImageCopyTexture { texture: source_tex, mip_level: 0, origin: wgpu::Origin3d::ZERO, aspect: wgpu::TextureAspect::All },
-                wgpu::ImageCopyTexture { texture: &surface_texture.texture, mip_level: 0, origin: wgpu::Origin3d::ZERO, aspect: wgpu::TextureAspect::All },
-                wgpu::Extent3d { width: self.width, height: self.height, depth_or_array_layers: 1 }
-            );
+            let surface_view = surface_texture.texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+            let blit_bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Blit BindGroup"),
+                layout: &self.blit_bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(output_view) },
+                ],
+            });
+
+            {
+                let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("Blit Pass"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: &surface_view,
+                        resolve_target: None,
+                        ops: wgpu::Operations { load: wgpu::LoadOp::Clear(wgpu::Color::BLACK), store: wgpu::StoreOp::Store },
+                    })],
+                    depth_stencil_attachment: None,
+                    timestamp_writes: None,
+                    occlusion_query_set: None,
+                });
+                rpass.set_pipeline(&self.blit_pipeline);
+                rpass.set_bind_group(0, &blit_bind_group, &[]);
+                rpass.draw(0..3, 0..1);
+            }
 
             self.queue.submit(Some(encoder.finish()));
             surface_texture.present();
@@ -448,9 +6125,30 @@ impl QuantumRenderer {
             self.queue.submit(Some(encoder.finish()));
         }
 
+        if let (Some(stats), true) = (&self.stats, stats_encoded) {
+            stats.request_readback(self.width * self.height);
+        }
+
         self.frame_count += 1;
     }
 
+    // フィールド統計 (min/max/mean) の縮約パスを有効化する。未使用時はオーバーヘッドなし
+    pub fn set_collect_stats(&mut self, enabled: bool) {
+        match (enabled, &self.stats) {
+            (true, None) => self.stats = Some(FieldStatsPass::new(&self.device)),
+            (false, Some(_)) => self.stats = None,
+            _ => {}
+        }
+    }
+
+    /// Latest completed `[min, max, mean]` of the field's green channel. Readback is
+    /// asynchronous, so this lags a frame or two behind `render`; empty until the first
+    /// result arrives or when collection is off.
+    pub fn field_stats(&self) -> Option<Vec<f32>> {
+        let (min, max, mean) = self.stats.as_ref()?.latest()?;
+        Some(vec![min, max, mean])
+    }
+
     fn get_current_texture(&self) -> Option<wgpu::SurfaceTexture> {
         match self.surface.get_current_texture() {
             Ok(texture) => Some(texture),
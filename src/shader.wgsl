@@ -13,14 +13,36 @@ struct Uniforms {
     decay_factor: f32,       // 距離減衰率
     feedback_strength: f32,  // 時間フィードバック強度 (0.0 ~ 1.0)
     num_landmarks: u32,      // ランドマーク数
+    propagation_speed: f32,  // 波面の伝播速度 (半径 observed_dist + speed * t)
     camera_pos: vec2<f32>,   // (デバッグ用) 真のカメラ位置
+    view_min: vec2<f32>,     // 描画範囲 (ワールド座標) の左下 (x_min, y_min)
+    view_max: vec2<f32>,     // 描画範囲の右上 (x_max, y_max)
+    feedback_mode: u32,      // 0: 一様フィードバック, 1: 確率 (信頼度) で重み付け
+    _pad: u32,
 };
 
+// lib.rs の FeedbackMode と一致させること
+const FEEDBACK_UNIFORM: u32 = 0u;
+const FEEDBACK_CONFIDENCE: u32 = 1u;
+
 struct Landmark {
     position: vec2<f32>,     // ランドマークの空間位置
     observed_dist: f32,      // カメラから観測された距離
     confidence: f32,         // 信頼度 (量子の振幅に対応)
     phase_offset: f32,       // 時間的位相ズレ
+    amplitude: f32,          // 場への寄与の大きさ (confidenceとは独立)
+    last_seen: f32,          // 最後に観測されたシミュレーション時刻
+    orientation: f32,        // 形状: 楕円の長軸の向き (rad)
+    aspect: f32,             // 形状: 長軸方向の縮尺 (1.0 = 点ランドマーク)
+    observed: u32,           // 0 = 直近の観測で見逃された (寄与しない)
+    id: u32,                 // LandmarkId
+    observed_bearing: f32,   // カメラ座標系での観測方位 (CPU の SensorModel::Bearing 用)
+    decay_matrix: mat2x2<f32>, // 異方的な減衰 (全て 0 なら uniforms.decay_factor)
+    wave_number: f32,        // ランドマーク固有の波数 (0 なら uniforms.wave_number)
+    sigma: f32,              // 距離計測の標準偏差 (包絡を広げ重みを下げる, 0 = 正確)
+    velocity: vec2<f32>,     // ランドマークの速度 (CPU の propagate 用)
+    radial_velocity: f32,    // 視線方向の相対速度 (ドップラー項 k * v * t)
+    decayed_until: f32,      // 減衰を適用済みの時刻 (CPU の decay_confidence_by_age 用, 88byte に揃える位置)
 };
 
 // ------------------------------------------------------------------------
@@ -36,6 +58,15 @@ struct Landmark {
 @group(0) @binding(2) var prev_frame_texture: texture_2d<f32>;
 @group(0) @binding(3) var output_texture: texture_storage_2d<rgba8unorm, write>;
 
+// Tiled Dispatch
+// 大きな場は複数ディスパッチに分割される。各タイルのピクセル原点 (動的オフセット)
+struct Tile {
+    origin: vec2<u32>,
+    _pad: vec2<u32>,
+};
+
+@group(0) @binding(4) var<uniform> tile: Tile;
+
 // ------------------------------------------------------------------------
 // Math Helpers (Complex Numbers)
 // ------------------------------------------------------------------------
@@ -58,74 +89,154 @@ fn probability_density(z: vec2<f32>) -> f32 {
     return z.x * z.x + z.y * z.y;
 }
 
+// 形状を考慮した距離 (aspect = 1 ならユークリッド距離)
+fn landmark_distance(lm: Landmark, p: vec2<f32>) -> f32 {
+    let d = p - lm.position;
+    let c = cos(lm.orientation);
+    let s = sin(lm.orientation);
+    let u = (c * d.x + s * d.y) / lm.aspect;
+    let v = -s * d.x + c * d.y;
+    return sqrt(u * u + v * v);
+}
+
+// ランドマーク→p の方向 u での減衰率 uᵀ M u (lib.rs の Landmark::matrix_decay_and_gradient と同じ)
+fn matrix_decay(lm: Landmark, p: vec2<f32>, isotropic: f32) -> f32 {
+    let m = lm.decay_matrix;
+    if (all(m[0] == vec2<f32>(0.0)) && all(m[1] == vec2<f32>(0.0))) {
+        return isotropic;
+    }
+    let d = p - lm.position;
+    let rho = length(d);
+    if (rho == 0.0) {
+        return 0.5 * (m[0][0] + m[1][1]);
+    }
+    let u = d / rho;
+    return dot(u, m * u);
+}
+
+// 計測ノイズ sigma で広げた減衰率 rate / sqrt(1 + (sigma rate)^2) (lib.rs の Landmark::decay_and_gradient と同じ)
+fn landmark_decay(lm: Landmark, p: vec2<f32>, isotropic: f32) -> f32 {
+    let rate = matrix_decay(lm, p, isotropic);
+    let spread = lm.sigma * rate;
+    return rate / sqrt(1.0 + spread * spread);
+}
+
+// 寄与の重み。見逃されたランドマークは 0、sigma で包絡の広がりと同じ比率だけ下げる (lib.rs の Landmark::weight と同じ)
+fn landmark_weight(lm: Landmark, isotropic: f32) -> f32 {
+    let spread = lm.sigma * isotropic;
+    return select(0.0, lm.amplitude * lm.confidence / sqrt(1.0 + spread * spread), lm.observed != 0u);
+}
+
 // ------------------------------------------------------------------------
 // Main Kernel
 // ------------------------------------------------------------------------
 
+// 場の定式化 (lib.rs の FieldMode と一致させること)。エントリポイントごとに固定
+const FIELD_COHERENT: u32 = 0u;    // |Σ a e^{iφ}|^2 : 干渉あり
+const FIELD_INCOHERENT: u32 = 1u;  // Σ |a|^2       : 干渉なし (振幅の包絡)
+const FIELD_PHASE: u32 = 2u;       // |Σ a e^{iφ}| / Σ a : 位相の揃い具合 (0..1)
+
 @compute @workgroup_size(16, 16)
-fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+fn main_coherent(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    shade(global_id.xy + tile.origin, FIELD_COHERENT);
+}
+
+@compute @workgroup_size(16, 16)
+fn main_incoherent(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    shade(global_id.xy + tile.origin, FIELD_INCOHERENT);
+}
+
+@compute @workgroup_size(16, 16)
+fn main_phase(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    shade(global_id.xy + tile.origin, FIELD_PHASE);
+}
+
+fn shade(pixel: vec2<u32>, mode: u32) {
     let width = u32(uniforms.resolution.x);
     let height = u32(uniforms.resolution.y);
 
-    if (global_id.x >= width || global_id.y >= height) {
+    if (pixel.x >= width || pixel.y >= height) {
         return;
     }
 
-    // ピクセル座標を UV空間 (-1.0 ~ 1.0) に正規化
-    // アスペクト比を維持
-    let aspect = uniforms.resolution.x / uniforms.resolution.y;
-    let uv = (vec2<f32>(global_id.xy) / uniforms.resolution) * 2.0 - 1.0;
-    let pos_space = vec2<f32>(uv.x * aspect, uv.y);
+    // ピクセル中心をワールド座標へ (lib.rs の pixel_to_world と同じ)
+    // ワールドは y 上向き、画面の行0 が view_max.y
+    let frac = (vec2<f32>(pixel) + 0.5) / uniforms.resolution;
+    let view_size = uniforms.view_max - uniforms.view_min;
+    let pos_space = vec2<f32>(
+        uniforms.view_min.x + frac.x * view_size.x,
+        uniforms.view_max.y - frac.y * view_size.y
+    );
 
     // ------------------------------------------------------------
     // Step 1: 波動関数の重ね合わせ (Quantum Superposition)
     // ------------------------------------------------------------
     // 全てのランドマークからの「逆観測波」を複素加算する
     var psi: vec2<f32> = vec2<f32>(0.0, 0.0);
+    var intensity_sum = 0.0;   // Σ |a|^2
+    var amplitude_sum = 0.0;   // Σ a
 
     for (var i = 0u; i < uniforms.num_landmarks; i = i + 1u) {
         let lm = landmarks[i];
 
         // 仮説: もしカメラが「ここ(pos_space)」にいるとしたら、距離は？
-        let hypo_dist = distance(pos_space, lm.position);
+        let hypo_dist = landmark_distance(lm, pos_space);
 
-        // 残差 (Residual): 仮説距離 - 観測距離
+        // 残差 (Residual): 仮説距離 - 波面の半径
         // これが 0 に近い場所ほど、位相が揃う (Constructive Interference)
-        let residual = hypo_dist - lm.observed_dist;
+        // 波面は観測距離の円から speed * t だけ外へ進み、包絡ごと動く
+        let front = lm.observed_dist + uniforms.propagation_speed * uniforms.time;
+        let residual = hypo_dist - front;
 
         // 位相計算:
-        // k * residual + temporal_phase
-        // 時間項を入れることで「ゆらぎ」や「6次元的な回転」を表現
-        let phase = uniforms.wave_number * residual + lm.phase_offset;
+        // k * residual + temporal_phase + doppler
+        // doppler = k * v * t: 視線方向の相対速度 v によるランドマークごとの位相の進み
+        let k = select(uniforms.wave_number, lm.wave_number, lm.wave_number > 0.0);
+        let doppler = k * lm.radial_velocity * uniforms.time;
+        let phase = k * residual + lm.phase_offset + doppler;
 
         // 振幅計算:
         // 距離が離れるほど不確かさが増す (減衰)
-        let amplitude = lm.confidence * exp(-uniforms.decay_factor * abs(residual));
+        let weight = landmark_weight(lm, uniforms.decay_factor);
+        let decay = landmark_decay(lm, pos_space, uniforms.decay_factor);
+        let amplitude = weight * exp(-decay * abs(residual));
 
         // 波動関数への寄与
         let wave = complex_mul_scalar(complex_exp(phase), amplitude);
         psi = complex_add(psi, wave);
+        intensity_sum = intensity_sum + amplitude * amplitude;
+        amplitude_sum = amplitude_sum + amplitude;
     }
 
     // ------------------------------------------------------------
     // Step 2: 確率密度の収縮 (Wavefunction Collapse)
     // ------------------------------------------------------------
     // 現在のフレームにおける瞬間的な存在確率
-    let current_prob = probability_density(psi);
+    var current_prob = probability_density(psi);
+    if (mode == FIELD_INCOHERENT) {
+        current_prob = intensity_sum;
+    } else if (mode == FIELD_PHASE) {
+        current_prob = select(0.0, length(psi) / amplitude_sum, amplitude_sum > 0.0);
+    }
 
     // ------------------------------------------------------------
     // Step 3: 時間的フィードバック (Tenet Feedback)
     // ------------------------------------------------------------
     // 前のフレームの確率場をサンプリング
     // textureLoad は整数座標(ivec2)を使う
-    let prev_color = textureLoad(prev_frame_texture, vec2<i32>(global_id.xy), 0);
+    let prev_color = textureLoad(prev_frame_texture, vec2<i32>(pixel), 0);
     // 直前の確率は Gチャンネル に入っていると仮定 (Sci-Fi Green)
     let prev_prob = prev_color.g; 
 
     // 過去と現在の融合 (Incoherent mixing)
     // alpha = feedback_strength
     // これにより、確率の「軌跡」が描かれ、過去の情報が現在を拘束する
-    let mixed_prob = mix(current_prob, prev_prob, uniforms.feedback_strength);
+    var alpha = uniforms.feedback_strength;
+    if (uniforms.feedback_mode == FEEDBACK_CONFIDENCE) {
+        // 確かな領域 (prev_prob 大) は安定させ、背景のノイズは現在フレームへ速く追従させる
+        alpha = alpha * clamp(prev_prob, 0.0, 1.0);
+    }
+    let mixed_prob = mix(current_prob, prev_prob, alpha);
 
     // ------------------------------------------------------------
     // Step 4: 可視化レンダリング
@@ -134,7 +245,8 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
     // 真の解周辺は強め合い(干渉)、誤った解は弱め合う
     
     // R: 瞬間的な位相の干渉 (赤くチラつくノイズ成分)
-    let r = current_prob * 0.1;
+    // |psi|^2 は共通位相に依存しないため、実部 Re(psi)^2 で進行する波面を表示する
+    let r = psi.x * psi.x * 0.1;
     
     // G: 時間積分された確かな存在確率 (量子SLAMの解)
     let g = mixed_prob * 2.0; 
@@ -153,5 +265,5 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
         1.0
     );
 
-    textureStore(output_texture, global_id.xy, final_color);
+    textureStore(output_texture, pixel, final_color);
 }
\ No newline at end of file
@@ -0,0 +1,65 @@
+// ========================================================================
+// Field Statistics Reduction (min / max / mean)
+// ========================================================================
+//
+// 確率場テクスチャの Gチャンネル (時間積分された存在確率) を集計する。
+// Rgba8Unorm なので値は 0..255 の整数として扱い、u32 のアトミックで最小/最大を、
+// ワークグループごとの部分和で平均を求める (合計は CPU 側で足し合わせる)。
+
+@group(0) @binding(0) var field_texture: texture_2d<f32>;
+
+// lib.rs の STATS_WORKGROUPS と一致させること
+const NUM_WORKGROUPS: u32 = 64u;
+
+struct Stats {
+    min_value: atomic<u32>,
+    max_value: atomic<u32>,
+    partial_sums: array<u32, NUM_WORKGROUPS>,
+};
+
+@group(0) @binding(1) var<storage, read_write> stats: Stats;
+
+var<workgroup> wg_min: atomic<u32>;
+var<workgroup> wg_max: atomic<u32>;
+var<workgroup> wg_sum: atomic<u32>;
+
+@compute @workgroup_size(256)
+fn main(
+    @builtin(global_invocation_id) global_id: vec3<u32>,
+    @builtin(local_invocation_index) local_index: u32,
+    @builtin(workgroup_id) workgroup_id: vec3<u32>,
+) {
+    if (local_index == 0u) {
+        atomicStore(&wg_min, 255u);
+        atomicStore(&wg_max, 0u);
+        atomicStore(&wg_sum, 0u);
+    }
+    workgroupBarrier();
+
+    // グリッドストライドループ: 固定数のワークグループで全ピクセルを走査
+    let dims = textureDimensions(field_texture);
+    let count = dims.x * dims.y;
+    let stride = NUM_WORKGROUPS * 256u;
+
+    var lo = 255u;
+    var hi = 0u;
+    var sum = 0u;
+    for (var i = global_id.x; i < count; i = i + stride) {
+        let g = textureLoad(field_texture, vec2<u32>(i % dims.x, i / dims.x), 0).g;
+        let v = u32(round(g * 255.0));
+        lo = min(lo, v);
+        hi = max(hi, v);
+        sum = sum + v;
+    }
+
+    atomicMin(&wg_min, lo);
+    atomicMax(&wg_max, hi);
+    atomicAdd(&wg_sum, sum);
+    workgroupBarrier();
+
+    if (local_index == 0u) {
+        atomicMin(&stats.min_value, atomicLoad(&wg_min));
+        atomicMax(&stats.max_value, atomicLoad(&wg_max));
+        stats.partial_sums[workgroup_id.x] = atomicLoad(&wg_sum);
+    }
+}
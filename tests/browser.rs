@@ -0,0 +1,82 @@
+//! The pure parts of the browser renderer (`QuantumRenderer`), testable without WebGPU.
+
+use inverse_observation_induced_probability_field_interference::{
+    demo_camera_position, Error, FeedbackMode, Landmark, Oscillation, Scene,
+};
+
+fn scene() -> Scene {
+    Scene {
+        landmarks: vec![Landmark::new(0.0, 0.5), Landmark::new(-0.4, -0.3)],
+        wave_number: 80.0,
+        feedback_strength: 0.9,
+        decay_factor: 5.0,
+        propagation_speed: 0.2,
+        decay_oscillation: Oscillation { amplitude: 1.0, frequency: 2.0 },
+        feedback_oscillation: Oscillation::default(),
+        viewport: Some((-1.5, 1.5, -1.0, 1.0)),
+        feedback_mode: FeedbackMode::Confidence,
+    }
+}
+
+#[test]
+fn validate_rejects_every_invalid_field() {
+    assert!(scene().validate().is_ok());
+    assert!(Scene { viewport: None, ..scene() }.validate().is_ok());
+
+    let invalid = [
+        Scene { viewport: Some((1.0, -1.0, -1.0, 1.0)), ..scene() },
+        Scene { viewport: Some((-1.0, 1.0, 0.0, 0.0)), ..scene() },
+        Scene { viewport: Some((f32::NAN, 1.0, -1.0, 1.0)), ..scene() },
+        Scene { feedback_strength: f32::NAN, ..scene() },
+        Scene { feedback_strength: 1.5, ..scene() },
+        Scene { wave_number: 0.0, ..scene() },
+        Scene { decay_factor: -1.0, ..scene() },
+        Scene { propagation_speed: f32::INFINITY, ..scene() },
+        Scene { decay_oscillation: Oscillation { amplitude: f32::NAN, frequency: 1.0 }, ..scene() },
+        Scene { feedback_oscillation: Oscillation { amplitude: 0.1, frequency: f32::INFINITY }, ..scene() },
+    ];
+    for scene in invalid {
+        assert!(scene.validate().is_err(), "{scene:?}");
+    }
+    let bad_landmark = Scene { landmarks: vec![Landmark::new(f32::NAN, 0.0)], ..scene() };
+    assert!(matches!(bad_landmark.validate(), Err(Error::NonFinitePosition(_))));
+}
+
+#[cfg(feature = "persist")]
+#[test]
+fn json_round_trips_and_rejects_invalid_scenes() {
+    let json = scene().to_json();
+    assert_eq!(Scene::from_json(&json).unwrap(), scene());
+
+    // 省略できる項目は既定値になる
+    let minimal = r#"{"landmarks":[],"wave_number":10.0,"feedback_strength":0.5,"decay_factor":1.0}"#;
+    let parsed = Scene::from_json(minimal).unwrap();
+    assert_eq!((parsed.viewport, parsed.feedback_mode, parsed.decay_oscillation), (None, FeedbackMode::Uniform, Oscillation::default()));
+
+    let inverted = Scene { viewport: Some((1.0, -1.0, -1.0, 1.0)), ..scene() }.to_json();
+    assert!(matches!(Scene::from_json(&inverted), Err(Error::InvalidParameter(_))));
+    assert!(matches!(Scene::from_json("{"), Err(Error::InvalidParameter(_))));
+}
+
+#[test]
+fn demo_camera_position_follows_a_bounded_lissajous_path() {
+    assert_eq!(demo_camera_position(0.0), [0.0, 0.5]);
+    let mut previous = demo_camera_position(0.0);
+    for step in 1..200 {
+        let [x, y] = demo_camera_position(step as f64 * 0.1);
+        assert!(x.abs() <= 0.5 && y.abs() <= 0.5, "({x}, {y})");
+        assert_ne!([x, y], previous);
+        previous = [x, y];
+    }
+}
+
+#[test]
+fn oscillation_modulates_around_the_base_value() {
+    let oscillation = Oscillation { amplitude: 2.0, frequency: 3.0 };
+    assert_eq!(oscillation.value_at(5.0, 0.0), 5.0);
+    let quarter = core::f32::consts::FRAC_PI_2 / 3.0;
+    assert!((oscillation.value_at(5.0, quarter) - 7.0).abs() < 1e-5);
+    assert!((oscillation.value_at(5.0, 3.0 * quarter) - 3.0).abs() < 1e-5);
+    // 振幅 0 なら一定値
+    assert_eq!(Oscillation::default().value_at(5.0, 1.7), 5.0);
+}
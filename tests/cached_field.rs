@@ -0,0 +1,46 @@
+//! Incremental updates of `CachedField` against a full re-evaluation.
+
+use inverse_observation_induced_probability_field_interference::{CachedField, ProbabilityField, QuantumSlamCore};
+
+const BOUNDS: (f32, f32, f32, f32) = (-5.0, 5.0, -5.0, 5.0);
+
+fn assert_close(cached: &ProbabilityField, full: &ProbabilityField) {
+    for (a, b) in cached.values.iter().zip(&full.values) {
+        assert!((a - b).abs() <= 1e-9 * b.max(1.0), "{a} != {b}");
+    }
+}
+
+#[test]
+fn update_landmark_matches_full_evaluation() {
+    let mut core = QuantumSlamCore::new(10.0).unwrap();
+    let ids: Vec<_> = [(0.0, 4.0), (-4.0, -3.0), (4.0, -3.0), (2.5, 2.5)]
+        .into_iter()
+        .map(|(x, y)| core.add_landmark(x, y).unwrap())
+        .collect();
+    core.observe(0.7, -0.4).unwrap();
+    let mut cached = CachedField::new(&core, BOUNDS, 32, 24);
+    assert_close(&cached.field(), &core.evaluate_grid_f64(BOUNDS, 32, 24));
+
+    // 1点の移動
+    core.landmarks[1].position = [-3.5, -2.0];
+    cached.update_landmark(&core, ids[1]);
+    assert_close(&cached.field(), &core.evaluate_grid_f64(BOUNDS, 32, 24));
+
+    // 1つの観測の更新
+    core.observe_one(ids[2], [1.0, 0.5]).unwrap();
+    cached.update_landmark(&core, ids[2]);
+    assert_close(&cached.field(), &core.evaluate_grid_f64(BOUNDS, 32, 24));
+
+    // 追加と削除
+    let added = core.add_landmark(-1.0, 3.0).unwrap();
+    core.observe_one(added, [1.0, 0.5]).unwrap();
+    cached.update_landmark(&core, added);
+    core.remove_landmark(ids[0]).unwrap();
+    cached.update_landmark(&core, ids[0]);
+    assert_close(&cached.field(), &core.evaluate_grid_f64(BOUNDS, 32, 24));
+
+    // 全項に効くパラメータの変更は全体の再評価になる
+    core.decay_factor = 2.0;
+    cached.update_landmark(&core, ids[3]);
+    assert_close(&cached.field(), &core.evaluate_grid_f64(BOUNDS, 32, 24));
+}
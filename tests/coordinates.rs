@@ -0,0 +1,72 @@
+//! World <-> pixel convention shared by the CPU core and the GPU renderer.
+
+use inverse_observation_induced_probability_field_interference::{
+    pixel_to_world, world_to_pixel, HeadlessRenderer, Landmark, QuantumSlamCore, Uniforms,
+};
+
+const BOUNDS: (f32, f32, f32, f32) = (-2.0, 2.0, -1.0, 1.0);
+const WIDTH: u32 = 160;
+const HEIGHT: u32 = 80;
+
+#[test]
+fn pixel_and_world_round_trip() {
+    for (col, row) in [(0, 0), (WIDTH - 1, 0), (0, HEIGHT - 1), (37, 61)] {
+        let p = pixel_to_world(BOUNDS, WIDTH, HEIGHT, col, row);
+        assert_eq!(world_to_pixel(BOUNDS, WIDTH, HEIGHT, p), Some([col, row]));
+    }
+
+    // 行0 は画面上端 = y_max
+    assert_eq!(world_to_pixel(BOUNDS, WIDTH, HEIGHT, [-1.99, 0.99]), Some([0, 0]));
+    assert_eq!(world_to_pixel(BOUNDS, WIDTH, HEIGHT, [1.99, -0.99]), Some([WIDTH - 1, HEIGHT - 1]));
+    assert_eq!(world_to_pixel(BOUNDS, WIDTH, HEIGHT, [2.5, 0.0]), None);
+}
+
+// GPU を使うので #[ignore] (`cargo test -- --include-ignored` で走らせる)
+#[test]
+#[ignore = "needs a GPU adapter"]
+fn landmark_renders_at_expected_pixel() {
+    let mut renderer = HeadlessRenderer::new(WIDTH, HEIGHT).expect("GPU tests need a GPU adapter");
+
+    let target = [0.91, 0.455];
+    // 観測距離0 のランドマーク: 場はランドマーク上で最大 (飽和しないよう振幅を抑える)
+    let mut landmark = Landmark::new(target[0], target[1]);
+    landmark.amplitude = 0.5;
+
+    let uniforms = Uniforms::builder()
+        .resolution(WIDTH, HEIGHT)
+        .viewport(BOUNDS)
+        .decay_factor(20.0)
+        .feedback_strength(0.0)
+        .camera_pos([100.0, 100.0])
+        .build()
+        .unwrap();
+    renderer.render(&uniforms, &[landmark]);
+
+    let pixels = renderer.read_pixels();
+    let brightest = (0..(WIDTH * HEIGHT) as usize)
+        .max_by_key(|&i| pixels[i * 4 + 1])
+        .unwrap() as u32;
+
+    assert_eq!(
+        Some([brightest % WIDTH, brightest / WIDTH]),
+        world_to_pixel(BOUNDS, WIDTH, HEIGHT, target)
+    );
+}
+
+#[test]
+fn evaluate_grid_rows_follow_cell_centers() {
+    // parallel フィーチャで行を分割しても、並びは行優先 (行0 = y_min) のまま
+    let mut core = QuantumSlamCore::new(10.0).unwrap();
+    for (x, y) in [(0.0, 1.5), (-1.5, -1.0), (1.5, -1.0)] {
+        core.add_landmark(x, y).unwrap();
+    }
+    core.observe(0.4, -0.2).unwrap();
+
+    let field = core.evaluate_grid(BOUNDS, 33, 17);
+    for (iy, ix) in [(0, 0), (0, 32), (16, 0), (9, 21)] {
+        let [x, y] = field.cell_center(ix, iy);
+        // 格子は SIMD のランドマークレーン、1点評価は逐次ループなので丸め誤差の分だけ許容する
+        let expected = core.probability_at(x, y);
+        assert!((field.values[iy * 33 + ix] - expected).abs() <= 1e-5 * expected.max(1.0), "cell ({ix}, {iy})");
+    }
+}
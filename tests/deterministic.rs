@@ -0,0 +1,28 @@
+//! Golden bit patterns of the field with the `deterministic` feature, identical on
+//! every platform (x86, ARM, WASM) so replayed runs can be compared exactly.
+#![cfg(feature = "deterministic")]
+
+use inverse_observation_induced_probability_field_interference::QuantumSlamCore;
+
+fn replay_core() -> QuantumSlamCore {
+    let mut core = QuantumSlamCore::new(10.0).unwrap();
+    for (x, y) in [(0.0, 4.0), (-4.0, -3.0), (4.0, -3.0), (2.5, 2.5), (-3.0, 1.0)] {
+        core.add_landmark(x, y).unwrap();
+    }
+    core.observe(0.7, -0.4).unwrap();
+    core.advance_time(0.25).unwrap();
+    core
+}
+
+// FNV-1a over the bit patterns of the values
+fn fingerprint(values: &[f64]) -> u64 {
+    values.iter().fold(0xcbf2_9ce4_8422_2325, |h, v| (h ^ v.to_bits()).wrapping_mul(0x0100_0000_01b3))
+}
+
+#[test]
+fn field_matches_golden_bits() {
+    let core = replay_core();
+    let field = core.evaluate_grid((-5.0, 5.0, -5.0, 5.0), 16, 16);
+    assert_eq!(fingerprint(&field.values), 0x21b5_af4d_336b_b725);
+    assert_eq!(core.probability_at_f64(1.3, 0.2).to_bits(), 0x3fcf_4a24_8d57_a2bb);
+}
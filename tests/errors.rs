@@ -0,0 +1,38 @@
+//! The crate-level `Error` returned by the fallible constructors.
+
+use inverse_observation_induced_probability_field_interference::{Error, Landmark, ProbabilityField, QuantumSlamCore};
+
+#[test]
+fn constructors_report_typed_errors() {
+    for bad in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+        assert!(matches!(QuantumSlamCore::new(bad), Err(Error::InvalidWaveNumber(_))), "{bad}");
+        assert!(matches!(QuantumSlamCore::with_capacity(bad, 8), Err(Error::InvalidWaveNumber(_))), "{bad}");
+    }
+    assert_eq!(QuantumSlamCore::with_capacity(10.0, 8).unwrap(), QuantumSlamCore::new(10.0).unwrap());
+
+    assert!(matches!(QuantumSlamCore::from_landmarks(10.0, Vec::new()), Err(Error::NoLandmarks)));
+    let landmarks = vec![Landmark::new(0.0, 1.0), Landmark::new(f32::NAN, 2.0)];
+    assert!(matches!(QuantumSlamCore::from_landmarks(10.0, landmarks), Err(Error::NonFinitePosition(_))));
+    let core = QuantumSlamCore::from_landmarks(10.0, vec![Landmark::new(0.0, 1.0), Landmark::new(2.0, 2.0)]).unwrap();
+    assert_eq!(core.landmarks.len(), 2);
+
+    let mismatch = ProbabilityField::from_values((0.0, 1.0, 0.0, 1.0), 3, 2, vec![0.0; 5]);
+    assert!(matches!(mismatch, Err(Error::DimensionMismatch { expected: 6, found: 5 })));
+}
+
+#[test]
+fn errors_display_and_chain_as_std_errors() {
+    let err: Box<dyn std::error::Error> = Box::new(Error::InvalidWaveNumber(-1.0));
+    assert_eq!(err.to_string(), "wave_number must be positive, got -1");
+    assert_eq!(Error::NonFinitePosition([f32::NAN, 1.0]).to_string(), "position (NaN, 1) is not finite");
+    assert_eq!(
+        Error::CapacityExceeded { requested: 5, capacity: 4 }.to_string(),
+        "5 items exceed the buffer capacity of 4"
+    );
+
+    // I/O エラーは ? でそのまま変換される
+    let io = std::io::Error::new(std::io::ErrorKind::NotFound, "missing");
+    let err = Error::from(io);
+    assert!(matches!(&err, Error::Io(e) if e.kind() == std::io::ErrorKind::NotFound));
+    assert_eq!(err.to_string(), "missing");
+}
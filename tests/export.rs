@@ -0,0 +1,66 @@
+//! Field exports with explicit and automatic bounds.
+
+use inverse_observation_induced_probability_field_interference::{Error, QuantumSlamCore};
+
+fn scratch_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("{}_{}", std::process::id(), name))
+}
+
+fn triangle() -> QuantumSlamCore {
+    let mut core = QuantumSlamCore::new(10.0).unwrap();
+    for (x, y) in [(0.0, 4.0), (-4.0, -3.0), (4.0, -3.0)] {
+        core.add_landmark(x, y).unwrap();
+    }
+    core.observe(0.7, -0.4).unwrap();
+    core
+}
+
+// .npy の (shape, f64 の値) を読み出す
+fn read_npy(bytes: &[u8]) -> (String, Vec<f64>) {
+    assert_eq!(&bytes[..8], b"\x93NUMPY\x01\x00");
+    let header_len = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+    assert_eq!((10 + header_len) % 64, 0);
+    let header = std::str::from_utf8(&bytes[10..10 + header_len]).unwrap();
+    let shape = header.split("'shape': ").nth(1).unwrap().split(')').next().unwrap().to_owned() + ")";
+    let values = bytes[10 + header_len..].chunks(8).map(|b| f64::from_le_bytes(b.try_into().unwrap())).collect();
+    (shape, values)
+}
+
+#[test]
+fn save_npy_defaults_to_auto_bounds() {
+    let core = triangle();
+    let path = scratch_path("auto_bounds.npy");
+
+    core.save_npy(&path, None, 24, 16).unwrap();
+    let (shape, values) = read_npy(&std::fs::read(&path).unwrap());
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(shape, "(16, 24)");
+    let expected = core.evaluate_grid(core.auto_bounds(0.1).unwrap(), 24, 16);
+    assert_eq!(values, expected.values);
+
+    // ランドマークが無ければ範囲を決められない
+    let empty = QuantumSlamCore::new(10.0).unwrap();
+    assert!(matches!(empty.save_npy(&path, None, 4, 4), Err(Error::NoLandmarks)));
+    assert!(!path.exists());
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn save_png_puts_y_max_on_the_top_row() {
+    let core = triangle();
+    let path = scratch_path("auto_bounds.png");
+    let bounds = (-5.0, 5.0, -5.0, 5.0);
+
+    core.save_png(&path, Some(bounds), 32, 20).unwrap();
+    let img = image::open(&path).unwrap().into_luma8();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(img.dimensions(), (32, 20));
+    let field = core.evaluate_grid(bounds, 32, 20);
+    let max = field.value_range().unwrap().1;
+    for (col, row) in [(0, 0), (31, 0), (12, 7), (20, 19)] {
+        let v = field.values[(19 - row) * 32 + col];
+        assert_eq!(img.get_pixel(col as u32, row as u32)[0], (v * 255.0 / max).round() as u8);
+    }
+}
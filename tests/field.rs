@@ -0,0 +1,371 @@
+//! CPU field variants built on top of `probability_at`.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use inverse_observation_induced_probability_field_interference::{Error, QuantumSlamCore, RadialSpacing, TemporalCore, WasmSlamCore};
+
+fn core_with(wave_number: f64, landmark_wave_number: Option<f32>) -> QuantumSlamCore {
+    let mut core = QuantumSlamCore::new(wave_number).unwrap();
+    let id = core.add_landmark(0.0, 4.0).unwrap();
+    core.add_landmark(-4.0, -3.0).unwrap();
+    core.set_landmark_wave_number(id, landmark_wave_number).unwrap();
+    core.observe(0.7, -0.4).unwrap();
+    core
+}
+
+#[test]
+fn chirp_sweeps_landmark_wave_numbers_in_proportion() {
+    // k_i = 2 k のランドマークは、k の掃引 [8, 12] に対して [16, 24] を掃引する
+    let overridden = core_with(10.0, Some(20.0));
+    let mut reference = core_with(20.0, None);
+    reference.landmarks[1].wave_number = 10.0;
+
+    for (x, y) in [(0.7, -0.4), (0.9, -0.1), (-1.0, 2.0)] {
+        let a = overridden.probability_chirp_at(x, y, 8.0, 12.0, 5);
+        let b = reference.probability_chirp_at(x, y, 16.0, 24.0, 5);
+        assert!((a - b).abs() < 1e-9, "{a} vs {b} at ({x}, {y})");
+    }
+
+    // 固有の波数が wave_number と同じなら上書きしない場合と一致する
+    let same = core_with(10.0, Some(10.0));
+    let plain = core_with(10.0, None);
+    let a = same.probability_chirp_at(0.9, -0.1, 8.0, 12.0, 5);
+    let b = plain.probability_chirp_at(0.9, -0.1, 8.0, 12.0, 5);
+    assert!((a - b).abs() < 1e-9, "{a} vs {b}");
+}
+
+#[test]
+fn temporal_core_rejects_invalid_time_constant() {
+    for bad in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+        assert!(TemporalCore::new(QuantumSlamCore::new(10.0).unwrap(), 4, bad).is_err(), "time_constant {bad}");
+    }
+
+    let mut temporal = TemporalCore::new(QuantumSlamCore::new(10.0).unwrap(), 4, 0.5).unwrap();
+    assert!(temporal.set_time_constant(f32::NAN).is_err());
+    assert_eq!(temporal.time_constant(), 0.5);
+    temporal.set_time_constant(2.0).unwrap();
+    assert_eq!(temporal.time_constant(), 2.0);
+}
+
+#[test]
+fn temporal_core_weights_recent_snapshots() {
+    let mut core = QuantumSlamCore::new(10.0).unwrap();
+    for (x, y) in [(0.0, 4.0), (-4.0, -3.0), (4.0, -3.0)] {
+        core.add_landmark(x, y).unwrap();
+    }
+    let mut temporal = TemporalCore::new(core, 2, 0.1).unwrap();
+    assert!(temporal.is_empty());
+    assert_eq!(temporal.probability_at(0.7, -0.4), 0.0);
+
+    // スナップショットが1つならコアの場と一致する
+    temporal.observe(0.7, -0.4).unwrap();
+    for (x, y) in [(0.7, -0.4), (-1.0, 1.5)] {
+        let expected = temporal.core.probability_at(x, y);
+        assert!((temporal.probability_at(x, y) - expected).abs() < 1e-4 * expected.max(1.0));
+    }
+
+    // 時定数の 50 倍古いスナップショットの重みは無視できる
+    temporal.core.advance_time(5.0).unwrap();
+    temporal.observe(-1.0, 1.5).unwrap();
+    for (x, y) in [(0.7, -0.4), (-1.0, 1.5)] {
+        let expected = temporal.core.probability_at(x, y);
+        assert!((temporal.probability_at(x, y) - expected).abs() < 1e-4 * expected.max(1.0));
+    }
+
+    // 窓からあふれた最古のスナップショットは捨てられる
+    temporal.observe(0.0, 0.0).unwrap();
+    assert_eq!(temporal.len(), 2);
+}
+
+#[test]
+fn localization_error_map_restores_the_core() {
+    let mut core = QuantumSlamCore::new(10.0).unwrap();
+    for (x, y) in [(0.0, 4.0), (-4.0, -3.0), (4.0, -3.0), (2.5, 2.5)] {
+        core.add_landmark(x, y).unwrap();
+    }
+    core.observe_range_bearing([0.7, -0.4], 0.3).unwrap();
+    let before = core.clone();
+
+    let positions = [[0.7, -0.4], [-1.0, 1.5], [2.0, 0.0]];
+    let errors = core.localization_error_map(&positions, (-5.0, 5.0, -5.0, 5.0), 101).unwrap();
+
+    // 観測とセンサーモデル (ここでは RangeBearing) は元に戻る
+    assert_eq!(core, before);
+    assert_eq!(errors.len(), positions.len());
+    for error in errors {
+        assert!(error < 0.2, "localization error {error}");
+    }
+
+    // 不正な位置で中断しても状態は元に戻る
+    let bad = [[0.7, -0.4], [f32::NAN, 0.0]];
+    assert!(core.localization_error_map(&bad, (-5.0, 5.0, -5.0, 5.0), 16).is_err());
+    assert_eq!(core, before);
+}
+
+#[test]
+fn observe_with_phase_leaves_carrier_phase_uncompensated() {
+    let mut core = QuantumSlamCore::new(10.0).unwrap();
+    for (x, y) in [(0.0, 4.0), (-4.0, -3.0), (4.0, -3.0)] {
+        core.add_landmark(x, y).unwrap();
+    }
+    let cam = [0.7, -0.4];
+
+    // observe では残差 0 で全ての項の位相が揃う
+    core.observe(cam[0], cam[1]).unwrap();
+    assert!((core.probability_at(cam[0], cam[1]) - 9.0).abs() < 1e-4);
+
+    // observe_with_phase ではカメラ位置でも各項が k d mod 2π の位相を持つ
+    core.observe_with_phase(cam).unwrap();
+    let (mut re, mut im) = (0.0f64, 0.0f64);
+    for lm in &core.landmarks {
+        let phase = (10.0 * lm.distance_to(cam[0], cam[1])).rem_euclid(std::f32::consts::TAU);
+        assert!((lm.phase_offset - phase).abs() < 1e-4);
+        re += (phase as f64).cos();
+        im += (phase as f64).sin();
+    }
+    let expected = re * re + im * im;
+    assert!((core.probability_at(cam[0], cam[1]) - expected).abs() < 1e-3, "{expected}");
+    assert!(expected < 9.0 - 1e-2);
+}
+
+#[test]
+fn evaluate_grid_with_progress_reports_monotonic_fractions() {
+    let core = core_with(10.0, None);
+    let bounds = (-5.0, 5.0, -5.0, 5.0);
+
+    let mut reports = Vec::new();
+    let field = core.evaluate_grid_with_progress(bounds, 16, 250, |fraction| reports.push(fraction));
+
+    // 約1%ごと (ここでは2行ごと) に通知し、最後は必ず 1.0
+    assert_eq!(reports.len(), 125);
+    assert!(reports.windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(reports.last(), Some(&1.0));
+    assert!(field.approx_eq(&core.evaluate_grid(bounds, 16, 250), 1e-9));
+}
+
+#[test]
+fn observe_and_estimate_reports_distance_to_the_true_pose() {
+    let mut core = QuantumSlamCore::new(10.0).unwrap();
+    for (x, y) in [(0.0, 4.0), (-4.0, -3.0), (4.0, -3.0)] {
+        core.add_landmark(x, y).unwrap();
+    }
+    let bounds = (-5.0, 5.0, -5.0, 5.0);
+    let cam = [0.7, -0.4];
+
+    let (estimate, error) = core.observe_and_estimate(cam, bounds, 101).unwrap();
+    assert_eq!(estimate, core.estimate_position(bounds, 101).0);
+    let expected = ((estimate[0] - cam[0]).powi(2) + (estimate[1] - cam[1]).powi(2)).sqrt();
+    assert!((error - expected as f64).abs() < 1e-6);
+    // セル幅 0.1 の格子なので誤差はセル1つ分程度に収まる
+    assert!(error < 0.1, "error {error}");
+
+    let mut observed = core.clone();
+    observed.observe(cam[0], cam[1]).unwrap();
+    assert_eq!(core, observed);
+
+    assert!(matches!(core.observe_and_estimate([f32::INFINITY, 0.0], bounds, 16), Err(Error::NonFinitePosition(_))));
+    assert_eq!(core, observed);
+}
+
+#[test]
+fn value_and_gradient_match_the_field_and_finite_differences() {
+    let core = core_with(10.0, None);
+    let h = 1e-4;
+
+    // 残差 0 のカメラ位置は |r| の折れ目で微分できないので避ける
+    for (x, y) in [(0.9f32, -0.1f32), (-1.0, 2.0), (2.0, 0.5), (-2.5, -1.5)] {
+        let (value, gradient) = core.value_and_gradient_at(x, y);
+        let expected = core.probability_at(x, y);
+        assert!((value - expected).abs() < 1e-4 * expected.max(1.0), "{value} vs {expected}");
+        assert_eq!(core.gradient_at(x, y), gradient);
+
+        // 中心差分は f64 経路で取る (f32 の丸めを避ける)
+        let (x, y) = (x as f64, y as f64);
+        let numeric = [
+            (core.probability_at_f64(x + h, y) - core.probability_at_f64(x - h, y)) / (2.0 * h),
+            (core.probability_at_f64(x, y + h) - core.probability_at_f64(x, y - h)) / (2.0 * h),
+        ];
+        let scale = numeric[0].hypot(numeric[1]).max(1.0);
+        for axis in 0..2 {
+            assert!((gradient[axis] - numeric[axis]).abs() < 1e-3 * scale, "({x}, {y}): {gradient:?} vs {numeric:?}");
+        }
+    }
+}
+
+#[test]
+fn propagation_speed_moves_the_fronts_over_time() {
+    let mut core = core_with(10.0, None);
+    core.set_propagation_speed(0.5).unwrap();
+    assert!(core.set_propagation_speed(f32::NAN).is_err());
+    let (x, y) = (0.9, -0.1);
+
+    // 共通の波数でも包絡が動くので |ψ|² は時間とともに変わる
+    let t = 0.3f32;
+    let p0 = core.probability_at_time(x, y, 0.0);
+    let p = core.probability_at_time(x, y, t);
+    assert!((p - p0).abs() > 1e-2 * p0.max(1e-3), "{p} vs {p0}");
+
+    // 1 つのランドマークなら、時刻 t の場は観測距離から speed * t だけ外へずれた時刻 0 の場
+    let mut single = QuantumSlamCore::new(10.0).unwrap();
+    single.add_landmark(0.0, 0.0).unwrap();
+    single.observe(2.0, 0.0).unwrap();
+    single.set_propagation_speed(0.5).unwrap();
+    let r = single.landmarks[0].observed_dist;
+    for offset in [-0.4f32, -0.1, 0.0, 0.2, 0.5] {
+        let moved = single.probability_at_time(r + offset + 0.5 * t, 0.0, t);
+        let still = single.probability_at_time(r + offset, 0.0, 0.0);
+        assert!((moved - still).abs() < 1e-4 * still.max(1.0), "{offset}: {moved} vs {still}");
+    }
+    assert!(single.probability_at_time(r, 0.0, t) < 0.9 * single.probability_at_time(r, 0.0, 0.0));
+
+    // probability_at はコアの時計で評価する
+    core.set_time(t).unwrap();
+    assert!((core.probability_at(x, y) - core.probability_at_time(x, y, t)).abs() < 1e-4);
+
+    // 波数の違うランドマークの間では位相差も時間とともに変わる
+    let mut mixed = core_with(10.0, Some(12.0));
+    mixed.set_propagation_speed(0.5).unwrap();
+    let a = mixed.probability_at_time(x, y, 0.0);
+    let b = mixed.probability_at_time(x, y, 1.0);
+    assert!((a - b).abs() > 1e-2, "{a} vs {b}");
+}
+
+#[test]
+fn evaluate_grid_cancellable_stops_once_cancelled() {
+    let core = core_with(10.0, None);
+    let bounds = (-5.0, 5.0, -5.0, 5.0);
+    let cancel = AtomicBool::new(false);
+
+    let field = core.evaluate_grid_cancellable(bounds, 24, 16, &cancel).unwrap();
+    assert!(field.approx_eq(&core.evaluate_grid(bounds, 24, 16), 1e-9));
+
+    cancel.store(true, Ordering::Relaxed);
+    assert!(core.evaluate_grid_cancellable(bounds, 24, 16, &cancel).is_none());
+}
+
+#[test]
+fn contribution_field_of_isolates_one_landmark() {
+    let core = core_with(10.0, None);
+    let bounds = (-5.0, 5.0, -5.0, 5.0);
+
+    for lm in &core.landmarks {
+        let mut alone = core.clone();
+        alone.landmarks = vec![*lm];
+        let expected = alone.evaluate_grid(bounds, 20, 20);
+        let contribution = core.contribution_field_of(lm.id, bounds, 20, 20).unwrap();
+        for (a, b) in contribution.values.iter().zip(&expected.values) {
+            assert!((a - b).abs() < 1e-4 * b.max(1e-3), "landmark {:?}: {a} vs {b}", lm.id);
+        }
+    }
+}
+
+#[test]
+fn contribution_field_of_rejects_an_unknown_landmark() {
+    let mut core = core_with(10.0, None);
+    let id = core.landmarks[1].id;
+    core.remove_landmark(id).unwrap();
+    assert!(matches!(core.contribution_field_of(id, (-5.0, 5.0, -5.0, 5.0), 4, 4), Err(Error::UnknownLandmark(bad)) if bad == id));
+}
+
+#[test]
+fn evaluate_radial_grid_samples_rings_around_the_center() {
+    let core = core_with(10.0, None);
+    let center = [0.7, -0.4];
+
+    let linear = core.evaluate_radial_grid(center, 2.0, 4, 8, RadialSpacing::Linear);
+    assert_eq!(linear.radii, vec![0.5, 1.0, 1.5, 2.0]);
+    assert_eq!(linear.angles.len(), 8);
+    assert_eq!(linear.values.len(), 32);
+    let [x, y] = linear.position(1, 2);
+    assert!((x - 0.7).abs() < 1e-6 && (y - 0.6).abs() < 1e-6, "({x}, {y})");
+    for (position, value) in linear.samples() {
+        assert_eq!(value, core.probability_at(position[0], position[1]));
+    }
+
+    // 対数間隔は max_radius / n_radial から max_radius まで等比
+    let log = core.evaluate_radial_grid(center, 8.0, 4, 3, RadialSpacing::Log);
+    for (r, expected) in log.radii.iter().zip([2.0, 3.174802, 5.039684, 8.0]) {
+        assert!((r - expected).abs() < 1e-4, "{:?}", log.radii);
+    }
+}
+
+#[test]
+fn chirp_keeps_the_main_peak_and_suppresses_fringe_side_lobes() {
+    let mut core = QuantumSlamCore::new(10.0).unwrap();
+    for (x, y) in [(0.0, 4.0), (-4.0, -3.0), (4.0, -3.0)] {
+        core.add_landmark(x, y).unwrap();
+    }
+    let cam = [0.7, -0.4];
+    core.observe(cam[0], cam[1]).unwrap();
+
+    // 1ステップの掃引は単一波数の場そのもの
+    for (x, y) in [(0.7, -0.4), (0.9, -0.1)] {
+        let single = core.probability_at(x, y);
+        assert!((core.probability_chirp_at(x, y, 10.0, 10.0, 1) - single).abs() < 1e-4 * single.max(1.0));
+    }
+    assert_eq!(core.probability_chirp_at(0.7, -0.4, 5.0, 15.0, 0), 0.0);
+
+    // 残差 0 の真の位置ではどの波数でも位相が揃う
+    let main = core.probability_chirp_at(cam[0], cam[1], 5.0, 15.0, 32);
+    assert!((main - core.probability_at(cam[0], cam[1])).abs() < 1e-3, "{main}");
+
+    // 単一波数で最も強いカメラ近傍の干渉縞は、掃引で平均すると弱まる
+    let field = core.evaluate_grid((-5.0, 5.0, -5.0, 5.0), 201, 201);
+    let (side_lobe, single) = (0..201 * 201)
+        .map(|i| (field.cell_center(i % 201, i / 201), field.values[i]))
+        .filter(|([x, y], _)| (0.3..1.5).contains(&(x - cam[0]).hypot(y - cam[1])))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap();
+    let chirp = core.probability_chirp_at(side_lobe[0], side_lobe[1], 5.0, 15.0, 32);
+    assert!(chirp < 0.75 * single, "side lobe at {side_lobe:?}: chirp {chirp} vs single {single}");
+}
+
+#[test]
+fn probability_at_3d_matches_the_planar_field_and_resolves_height() {
+    let mut core = QuantumSlamCore::new(10.0).unwrap();
+    for (x, y) in [(0.0, 4.0), (-4.0, -3.0), (4.0, -3.0)] {
+        core.add_landmark(x, y).unwrap();
+        core.add_landmark_3d(x, y, 0.0).unwrap();
+    }
+    assert!(core.add_landmark_3d(0.0, 0.0, f32::NAN).is_err());
+    assert!(core.add_landmark_3d(f32::INFINITY, 0.0, 1.0).is_err());
+    assert_eq!(core.landmarks_3d.len(), 3);
+
+    // z = 0 の平面内では 2D の場と同じ
+    core.observe(0.7, -0.4).unwrap();
+    core.observe_3d([0.7, -0.4, 0.0]).unwrap();
+    for (x, y) in [(0.7, -0.4), (0.9, -0.1), (-1.0, 2.0)] {
+        let planar = core.probability_at(x, y);
+        assert!((core.probability_at_3d(x, y, 0.0) - planar).abs() < 1e-4 * planar.max(1.0));
+    }
+
+    // 高さの違うランドマークを加えると z 方向の位置も決まる
+    core.add_landmark_3d(0.0, 0.0, 3.0).unwrap();
+    core.observe_3d([0.7, -0.4, 1.0]).unwrap();
+    let peak = core.probability_at_3d(0.7, -0.4, 1.0);
+    assert!((peak - 16.0).abs() < 1e-3, "{peak}");
+    assert!(core.probability_at_3d(0.7, -0.4, 1.3) < 0.9 * peak);
+}
+
+#[test]
+fn wasm_slam_core_mirrors_the_cpu_core() {
+    let mut wasm = WasmSlamCore::new(10.0).unwrap();
+    let mut core = QuantumSlamCore::new(10.0).unwrap();
+    for (x, y) in [(0.0, 4.0), (-4.0, -3.0)] {
+        wasm.add_landmark(x, y).unwrap();
+        core.add_landmark(x, y).unwrap();
+    }
+    wasm.set_decay_factor(2.0).unwrap();
+    core.set_decay_factor(2.0).unwrap();
+    wasm.observe(0.7, -0.4).unwrap();
+    core.observe(0.7, -0.4).unwrap();
+
+    assert_eq!(wasm.probability_at(0.5, 0.5), core.probability_at(0.5, 0.5));
+    assert_eq!(wasm.amplitude_at(0.5, 0.5), core.complex_field_at(0.5, 0.5).to_vec());
+    assert_eq!(wasm.evaluate_grid(-5.0, 5.0, -5.0, 5.0, 6, 4), core.evaluate_grid((-5.0, 5.0, -5.0, 5.0), 6, 4).values);
+
+    assert!(WasmSlamCore::new(0.0).is_err());
+    assert!(matches!(wasm.add_landmark(f32::NAN, 0.0), Err(Error::NonFinitePosition(_))));
+    assert!(wasm.observe(0.0, f32::INFINITY).is_err());
+    assert!(wasm.set_decay_factor(-1.0).is_err());
+}
@@ -0,0 +1,330 @@
+//! Per-landmark state and map editing on `QuantumSlamCore`.
+
+use inverse_observation_induced_probability_field_interference::{Error, Landmark, LandmarkShape, QuantumSlamCore};
+
+#[test]
+fn amplitude_scales_the_contribution_independently_of_confidence() {
+    assert_eq!(Landmark::new(1.0, 2.0).amplitude, 1.0);
+
+    let mut core = QuantumSlamCore::new(10.0).unwrap();
+    core.add_landmark(0.0, 4.0).unwrap();
+    core.observe(0.7, -0.4).unwrap();
+    let base = core.probability_at(0.5, 0.0);
+
+    // 単独のランドマークなら |ψ|² は振幅の2乗に比例する
+    core.landmarks[0].amplitude = 2.0;
+    let amplified = core.probability_at(0.5, 0.0);
+    assert!((amplified - 4.0 * base).abs() < 1e-9 * amplified, "{amplified} vs {base}");
+
+    // 信頼度の減衰は振幅を変えない
+    core.decay_all(0.5).unwrap();
+    assert_eq!(core.landmarks[0].amplitude, 2.0);
+    assert_eq!(core.landmarks[0].confidence, 0.5);
+    let decayed = core.probability_at(0.5, 0.0);
+    assert!((decayed - base).abs() < 1e-9 * base, "{decayed} vs {base}");
+}
+
+#[test]
+fn decay_confidence_by_age_halves_per_half_life_since_last_seen() {
+    let mut core = QuantumSlamCore::new(10.0).unwrap();
+    let old = core.add_landmark(0.0, 4.0).unwrap();
+    let recent = core.add_landmark(-4.0, -3.0).unwrap();
+    core.observe(0.0, 0.0).unwrap();
+
+    core.set_time(2.0).unwrap();
+    core.observe_one(recent, [0.0, 0.0]).unwrap();
+    let last_seen = |core: &QuantumSlamCore, id| core.landmarks[core.landmark_index(id).unwrap()].last_seen;
+    assert_eq!((last_seen(&core, old), last_seen(&core, recent)), (0.0, 2.0));
+
+    core.decay_confidence_by_age(4.0, 2.0).unwrap();
+    assert!((core.landmarks[0].confidence - 0.25).abs() < 1e-6);
+    assert!((core.landmarks[1].confidence - 0.5).abs() < 1e-6);
+
+    // 観測より前の時刻では減衰しない
+    core.decay_confidence_by_age(1.0, 2.0).unwrap();
+    assert!((core.landmarks[1].confidence - 0.5).abs() < 1e-6);
+
+    // 同じ時刻で呼び直しても重ねて減衰しない
+    core.decay_confidence_by_age(4.0, 2.0).unwrap();
+    assert!((core.landmarks[0].confidence - 0.25).abs() < 1e-6);
+    assert!((core.landmarks[1].confidence - 0.5).abs() < 1e-6);
+
+    for bad in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+        assert!(matches!(core.decay_confidence_by_age(6.0, bad), Err(Error::InvalidParameter(_))), "{bad}");
+    }
+    assert!((core.landmarks[0].confidence - 0.25).abs() < 1e-6);
+}
+
+#[test]
+fn decay_confidence_by_age_depends_on_elapsed_time_not_call_count() {
+    let mut stepped = QuantumSlamCore::new(10.0).unwrap();
+    let id = stepped.add_landmark(0.0, 4.0).unwrap();
+    stepped.add_landmark(-4.0, -3.0).unwrap();
+    stepped.observe(0.0, 0.0).unwrap();
+    let mut once = stepped.clone();
+
+    for i in 1..=12 {
+        stepped.decay_confidence_by_age(0.5 * i as f32, 3.0).unwrap();
+    }
+    once.decay_confidence_by_age(6.0, 3.0).unwrap();
+    for (a, b) in stepped.landmarks.iter().zip(&once.landmarks) {
+        assert!((a.confidence - 0.25).abs() < 1e-5 && (b.confidence - 0.25).abs() < 1e-5, "{} {}", a.confidence, b.confidence);
+    }
+
+    // 観測し直すとその時刻から減衰を数え直す (それまでの減衰は残る)
+    stepped.set_time(8.0).unwrap();
+    stepped.observe_one(id, [0.0, 0.0]).unwrap();
+    stepped.decay_confidence_by_age(11.0, 3.0).unwrap();
+    assert!((stepped.landmarks[0].confidence - 0.125).abs() < 1e-5);
+    assert!((stepped.landmarks[1].confidence - 0.25 * 0.5f32.powf(5.0 / 3.0)).abs() < 1e-5);
+}
+
+#[test]
+fn landmark_shape_stretches_distances_along_its_orientation() {
+    let point = Landmark::new(1.0, 1.0);
+    assert_eq!(point.shape, LandmarkShape::default());
+    assert!((point.distance_to(4.0, 5.0) - 5.0).abs() < 1e-6);
+
+    // y 軸に沿って 2 倍に伸びた楕円
+    let mut wall = point;
+    wall.shape = LandmarkShape { orientation: std::f32::consts::FRAC_PI_2, aspect: 2.0 };
+    assert!((wall.distance_to(1.0, 3.0) - 1.0).abs() < 1e-6);
+    assert!((wall.distance_to(3.0, 1.0) - 2.0).abs() < 1e-6);
+
+    // 同じ楕円距離の点では場の値も等しい
+    let mut core = QuantumSlamCore::new(10.0).unwrap();
+    let id = core.add_landmark(1.0, 1.0).unwrap();
+    core.landmarks[0].shape = wall.shape;
+    core.set_observation(id, 0.5).unwrap();
+    let along = core.probability_at(1.0, 3.0);
+    let across = core.probability_at(2.0, 1.0);
+    assert!((along - across).abs() < 1e-5, "{along} vs {across}");
+}
+
+#[test]
+fn append_adds_new_handles_and_merges_close_landmarks() {
+    let mut map = QuantumSlamCore::new(10.0).unwrap();
+    map.add_landmark(0.0, 0.0).unwrap();
+    map.add_landmark(5.0, 0.0).unwrap();
+    let mut other = QuantumSlamCore::new(20.0).unwrap();
+    other.add_landmark(0.2, 0.0).unwrap();
+    other.add_landmark(0.0, 5.0).unwrap();
+
+    let mut appended = map.clone();
+    appended.append(&other, None);
+    assert_eq!(appended.landmarks.len(), 4);
+    assert_eq!(appended.wave_number, 10.0);
+    let mut ids: Vec<_> = appended.landmarks.iter().map(|lm| lm.id).collect();
+    ids.sort_unstable();
+    ids.dedup();
+    assert_eq!(ids.len(), 4);
+
+    // 半径内の (0, 0) と (0.2, 0) は信頼度で重み付けた位置に融合される
+    appended.landmarks[2].confidence = 0.25;
+    assert_eq!(appended.merge_close_landmarks(0.5), 1);
+    assert_eq!(appended.landmarks.len(), 3);
+    assert!((appended.landmarks[0].position[0] - 0.04).abs() < 1e-6);
+    assert_eq!(appended.landmarks[0].confidence, 1.0);
+
+    map.append(&other, Some(0.5));
+    assert_eq!(map.landmarks.len(), 3);
+}
+
+#[test]
+fn append_leaves_agent_and_aperture_fields_to_the_existing_landmarks() {
+    let mut map = QuantumSlamCore::new(10.0).unwrap();
+    for (x, y) in [(0.0, 4.0), (-4.0, -3.0), (4.0, -3.0)] {
+        map.add_landmark(x, y).unwrap();
+    }
+    map.observe_for_agent(1, 0.7, -0.4).unwrap();
+    map.observe_for_agent(2, -1.0, 1.0).unwrap();
+    map.observe_aperture(&[[0.7, -0.4], [0.9, -0.4]]).unwrap();
+    // (0.2, 4) は信頼度 0 なので融合しても (0, 4) は動かない
+    let mut other = QuantumSlamCore::new(10.0).unwrap();
+    other.add_landmark(0.2, 4.0).unwrap();
+    other.add_landmark(3.0, 3.0).unwrap();
+    other.landmarks[0].confidence = 0.0;
+
+    let points = [(0.7, -0.4), (-1.0, 1.0), (2.0, 0.5)];
+    let fields = |core: &QuantumSlamCore| -> Vec<[f64; 3]> {
+        points
+            .iter()
+            .map(|&(x, y)| {
+                [core.probability_at_for_agent(1, x, y), core.probability_at_for_agent(2, x, y), core.probability_aperture_at(x, y)]
+            })
+            .collect()
+    };
+    let before = fields(&map);
+
+    for merge_radius in [None, Some(0.5)] {
+        let mut appended = map.clone();
+        appended.append(&other, merge_radius);
+        assert_eq!(appended.landmarks.len(), if merge_radius.is_some() { 4 } else { 5 });
+        assert_eq!(fields(&appended), before, "{merge_radius:?}");
+
+        // 観測し直せば追加分も場に入る
+        appended.observe_for_agent(1, 0.7, -0.4).unwrap();
+        assert_ne!(appended.probability_at_for_agent(1, 2.0, 0.5), before[2][0]);
+    }
+}
+
+#[test]
+fn merge_close_landmarks_fuses_agent_and_aperture_distances() {
+    let mut core = QuantumSlamCore::new(10.0).unwrap();
+    for (x, y) in [(0.0, 4.0), (-4.0, -3.0), (0.2, 4.0), (4.0, -3.0)] {
+        core.add_landmark(x, y).unwrap();
+    }
+    core.landmarks[2].confidence = 0.5;
+    let cam = [0.7, -0.4];
+    core.observe(cam[0], cam[1]).unwrap();
+    core.observe_for_agent(7, cam[0], cam[1]).unwrap();
+    core.observe_aperture(&[cam]).unwrap();
+    // 観測後に追加したランドマークはエージェントの場に入らないまま
+    core.add_landmark(3.0, 3.0).unwrap();
+
+    assert_eq!(core.merge_close_landmarks(0.5), 1);
+    assert_eq!(core.landmarks.len(), 4);
+
+    // エージェントと開口の距離は observed_dist と同じ重みで融合される
+    let mut agent_only = core.clone();
+    agent_only.landmarks[3].observed = 0;
+    for (x, y) in [(0.7, -0.4), (1.5, 0.5), (-2.0, 1.0)] {
+        let expected = agent_only.probability_at(x, y);
+        let agent = core.probability_at_for_agent(7, x, y);
+        let aperture = core.probability_aperture_at(x, y);
+        assert!((agent - expected).abs() < 1e-4 * expected.max(1.0), "({x}, {y}): {agent} vs {expected}");
+        assert!((aperture - expected).abs() < 1e-4 * expected.max(1.0), "({x}, {y}): {aperture} vs {expected}");
+    }
+}
+
+#[test]
+fn with_capacity_and_reserve_preallocate_landmarks() {
+    let mut core = QuantumSlamCore::with_capacity(10.0, 64).unwrap();
+    assert!(core.landmarks.capacity() >= 64);
+    assert!(core.landmarks.is_empty());
+    assert_eq!(core, QuantumSlamCore::new(10.0).unwrap());
+
+    core.add_landmark(1.0, 2.0).unwrap();
+    core.reserve(200);
+    assert!(core.landmarks.capacity() >= 201);
+    assert_eq!(core.landmarks.len(), 1);
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn landmarks_from_image_seeds_dark_pixels() {
+    let path = std::env::temp_dir().join(format!("{}_floorplan.png", std::process::id()));
+    let mut img = image::GrayImage::from_pixel(4, 4, image::Luma([255]));
+    img.put_pixel(1, 0, image::Luma([0]));
+    img.put_pixel(3, 3, image::Luma([100]));
+    img.save(&path).unwrap();
+
+    let bounds = (0.0, 4.0, 0.0, 4.0);
+    let mut core = QuantumSlamCore::new(10.0).unwrap();
+    assert_eq!(core.landmarks_from_image(&path, 0.5, 1, bounds).unwrap(), 2);
+    // 画像の最上行は y_max
+    let positions: Vec<[f32; 2]> = core.landmarks.iter().map(|lm| lm.position).collect();
+    assert_eq!(positions, vec![[1.5, 3.5], [3.5, 0.5]]);
+
+    // 閾値より明るい画素と、間引きで飛ばした画素は使わない
+    assert_eq!(core.landmarks_from_image(&path, 0.2, 1, bounds).unwrap(), 1);
+    assert_eq!(core.landmarks_from_image(&path, 0.5, 2, bounds).unwrap(), 0);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(core.landmarks.len(), 3);
+}
+
+#[test]
+fn transform_affine_moves_landmarks_and_clears_observations() {
+    let mut core = QuantumSlamCore::new(10.0).unwrap();
+    let id = core.add_landmark(1.0, 0.0).unwrap();
+    core.add_landmark(0.0, 2.0).unwrap();
+    core.landmarks[0].shape = LandmarkShape { orientation: 0.0, aspect: 2.0 };
+    core.observe(0.5, 0.5).unwrap();
+
+    // 90° 回転 + 平行移動
+    core.transform_affine([[0.0, -1.0], [1.0, 0.0]], [3.0, -1.0]);
+    let positions: Vec<[f32; 2]> = core.landmarks.iter().map(|lm| lm.position).collect();
+    assert_eq!(positions, vec![[3.0, 0.0], [1.0, -1.0]]);
+    assert!((core.landmarks[0].shape.orientation - std::f32::consts::FRAC_PI_2).abs() < 1e-6);
+    assert_eq!(core.landmarks[0].id, id);
+    assert!(core.landmarks.iter().all(|lm| lm.observed == 0 && lm.observed_dist == 0.0));
+
+    core.map_landmarks(|[x, y]| [2.0 * x, y + 1.0]);
+    assert_eq!(core.landmarks[1].position, [2.0, 0.0]);
+}
+
+#[test]
+fn landmark_handles_survive_removal_and_are_not_reused() {
+    let mut core = QuantumSlamCore::new(10.0).unwrap();
+    let ids: Vec<_> = [(0.0, 4.0), (-4.0, -3.0), (4.0, -3.0)]
+        .into_iter()
+        .map(|(x, y)| core.add_landmark(x, y).unwrap())
+        .collect();
+    core.observe(0.7, -0.4).unwrap();
+    core.observe_for_agent(7, 0.0, 0.0).unwrap();
+
+    let removed = core.remove_landmark(ids[1]).unwrap();
+    assert_eq!(removed.position, [-4.0, -3.0]);
+    assert_eq!(core.landmark_index(ids[2]), Some(1));
+    assert_eq!(core.landmark_index(ids[1]), None);
+    assert!(matches!(core.remove_landmark(ids[1]), Err(Error::UnknownLandmark(id)) if id == ids[1]));
+    // エージェントの観測距離も同じ並びで削除される
+    let mut expected = core.clone();
+    expected.observe_for_agent(7, 0.0, 0.0).unwrap();
+    assert_eq!(core.probability_at_for_agent(7, 0.5, 0.5), expected.probability_at_for_agent(7, 0.5, 0.5));
+
+    core.update_landmark(ids[2], 1.0, 1.0).unwrap();
+    assert_eq!((core.landmarks[1].position, core.landmarks[1].observed_dist, core.landmarks[1].observed), ([1.0, 1.0], 0.0, 0));
+    // 動かしたランドマークは再観測まで場に寄与しない
+    let mut without = core.clone();
+    without.remove_landmark(ids[2]).unwrap();
+    for (x, y) in [(1.0, 1.0), (0.5, -0.5), (-2.0, 3.0)] {
+        assert_eq!(core.probability_at(x, y), without.probability_at(x, y));
+    }
+    assert!(matches!(core.update_landmark(ids[2], f32::NAN, 1.0), Err(Error::NonFinitePosition(_))));
+    assert!(core.update_landmark(ids[1], 1.0, 1.0).is_err());
+
+    core.clear_landmarks();
+    assert!(core.landmarks.is_empty());
+    assert_eq!(core.agent_ids().count(), 0);
+    let next = core.add_landmark(0.0, 0.0).unwrap();
+    assert!(ids.iter().all(|&id| id < next));
+}
+
+#[test]
+fn associate_returns_unmatched_ranges_and_gates_landmarks_added_for_them() {
+    let geometry = [(3.0, 1.0), (-2.0, 4.0), (-3.5, -2.0), (1.5, -4.0)];
+    let mut core = QuantumSlamCore::new(10.0).unwrap();
+    let ids: Vec<_> = geometry.iter().map(|&(x, y)| core.add_landmark(x, y).unwrap()).collect();
+    core.observe(0.3, 0.7).unwrap();
+
+    let ranges_from = |cam: [f32; 2], points: &[(f32, f32)]| -> Vec<f32> {
+        points.iter().map(|&(x, y)| ((x - cam[0]).powi(2) + (y - cam[1]).powi(2)).sqrt()).collect()
+    };
+    // 対応のない遠い反射は Err で返り、ランドマークは増えない
+    let cam = [0.32, 0.68];
+    let order = [2, 0, 3, 1];
+    let mut ranges = ranges_from(cam, &order.map(|i| geometry[i]));
+    ranges.push(9.0);
+    let result = core.associate(&ranges);
+    assert_eq!(&result[..4], &order.map(|i| Ok(ids[i]))[..]);
+    assert_eq!(result[4], Err(9.0));
+    assert_eq!(core.landmarks.len(), geometry.len());
+    assert!(core.landmarks.iter().all(|lm| lm.observed == 1));
+
+    // 呼び出し側が位置を決めて追加したランドマークは次の走査で対応付けられる
+    let new_id = core.add_landmark(cam[0] + 9.0, cam[1]).unwrap();
+    let result = core.associate(&ranges);
+    assert_eq!(result[4], Ok(new_id));
+    assert_eq!(&result[..4], &order.map(|i| Ok(ids[i]))[..]);
+
+    // 見逃したランドマークは次の走査で再び対応付けられる
+    let result = core.associate(&ranges[1..]);
+    assert!(result.iter().all(Result::is_ok));
+    assert_eq!(core.landmarks[core.landmark_index(ids[order[0]]).unwrap()].observed, 0);
+    assert_eq!(core.associate(&ranges)[0], Ok(ids[order[0]]));
+
+    // 姿勢の推定がなければ全ての計測が未対応
+    let mut empty = QuantumSlamCore::new(10.0).unwrap();
+    assert_eq!(empty.associate(&[1.0, 2.0]), vec![Err(1.0), Err(2.0)]);
+}
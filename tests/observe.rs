@@ -0,0 +1,240 @@
+//! Observation variants and what they record on the landmarks.
+
+use inverse_observation_induced_probability_field_interference::{Error, ProbabilityField, QuantumSlamCore};
+
+fn triangle() -> QuantumSlamCore {
+    let mut core = QuantumSlamCore::new(10.0).unwrap();
+    for (x, y) in [(0.0, 4.0), (-4.0, -3.0), (4.0, -3.0)] {
+        core.add_landmark(x, y).unwrap();
+    }
+    core
+}
+
+#[test]
+fn observe_directional_phases_landmarks_by_relative_bearing() {
+    let mut core = QuantumSlamCore::new(10.0).unwrap();
+    for (x, y) in [(3.0, 0.0), (-3.0, 0.0), (0.0, 2.0)] {
+        core.add_landmark(x, y).unwrap();
+    }
+    let mut plain = core.clone();
+    plain.observe(0.0, 0.0).unwrap();
+
+    // +x 方向を向いたセンサー: 正面 0、真後ろ π、真横 π/2
+    core.observe_directional(0.0, 0.0, 0.0).unwrap();
+    let phases: Vec<f32> = core.landmarks.iter().map(|lm| lm.phase_offset).collect();
+    assert!(phases[0].abs() < 1e-6, "{phases:?}");
+    assert!((phases[1] - std::f32::consts::PI).abs() < 1e-5, "{phases:?}");
+    assert!((phases[2] - std::f32::consts::FRAC_PI_2).abs() < 1e-5, "{phases:?}");
+
+    // 距離の観測は observe と同じ
+    for (a, b) in core.landmarks.iter().zip(&plain.landmarks) {
+        assert_eq!(a.observed_dist, b.observed_dist);
+    }
+
+    // 後ろ向きにすると位相が入れ替わる
+    core.observe_directional(0.0, 0.0, std::f32::consts::PI).unwrap();
+    assert!((core.landmarks[0].phase_offset - std::f32::consts::PI).abs() < 1e-5);
+    assert!(core.landmarks[1].phase_offset.abs() < 1e-5);
+}
+
+#[test]
+fn observation_variants_reject_non_finite_camera_positions() {
+    let mut core = triangle();
+    core.observe(0.7, -0.4).unwrap();
+    let before = core.clone();
+
+    let bad = [f32::NAN, 0.0];
+    assert!(matches!(core.observe_directional(bad[0], bad[1], 0.0), Err(Error::NonFinitePosition(_))));
+    assert!(matches!(core.observe_range_bearing(bad, 0.0), Err(Error::NonFinitePosition(_))));
+    assert!(matches!(core.observe_with_phase([0.0, f32::INFINITY]), Err(Error::NonFinitePosition(_))));
+    assert!(matches!(core.observe_directional(0.0, 0.0, f32::NAN), Err(Error::InvalidParameter(_))));
+    assert!(matches!(core.observe_range_bearing([0.0, 0.0], f32::INFINITY), Err(Error::InvalidParameter(_))));
+    assert!(core.expected_information_gain(bad, (-5.0, 5.0, -5.0, 5.0), 8, 4).is_err());
+    // 弾かれた観測は何も書き換えない
+    assert_eq!(core, before);
+}
+
+#[test]
+fn mutating_apis_reject_garbage_without_changing_state() {
+    let mut core = triangle();
+    core.add_landmark_3d(0.0, 0.0, 2.0).unwrap();
+    core.observe(0.7, -0.4).unwrap();
+    core.observe_for_agent(1, 0.7, -0.4).unwrap();
+    core.observe_aperture(&[[0.7, -0.4], [0.8, -0.4]]).unwrap();
+    let before = core.clone();
+
+    assert!(matches!(core.observe_for_agent(2, f32::NAN, 0.0), Err(Error::NonFinitePosition(_))));
+    assert!(matches!(core.observe_aperture(&[[0.0, 0.0], [f32::INFINITY, 0.0]]), Err(Error::NonFinitePosition(_))));
+    assert!(matches!(core.observe_accumulate(0.0, f32::NAN), Err(Error::NonFinitePosition(_))));
+    assert!(core.observe_bearing([f32::NAN, 0.0], 0.0).is_err());
+    assert!(core.observe_bearing([0.0, 0.0], f32::NAN).is_err());
+    assert!(core.observe_3d([0.0, 0.0, f32::NAN]).is_err());
+    assert!(core.observe_3d([f32::NAN, 0.0, 0.0]).is_err());
+
+    let ranges = core.simulate_scan([0.7, -0.4], 0.0, 16, 20.0);
+    assert!(core.observe_scan([f32::NAN, 0.0], 0.0, &ranges, 20.0).is_err());
+    assert!(core.observe_scan([0.7, -0.4], 0.0, &ranges, f32::NAN).is_err());
+    assert!(core.observe_scan([0.7, -0.4], 0.0, &[1.0, f32::NAN], 20.0).is_err());
+    assert!(core.observe_scan([0.7, -0.4], 0.0, &[1.0, -1.0], 20.0).is_err());
+
+    for dt in [-1.0, f32::NAN, f32::INFINITY] {
+        assert!(matches!(core.propagate(dt), Err(Error::InvalidParameter(_))), "{dt}");
+        assert!(matches!(core.advance_time(dt), Err(Error::InvalidParameter(_))), "{dt}");
+        assert!(matches!(core.step(dt), Err(Error::InvalidParameter(_))), "{dt}");
+    }
+    assert!(core.predict(f32::NAN, 0.0, 0.0, 0.1).is_err());
+    assert!(core.predict(0.1, 0.0, f32::INFINITY, 0.1).is_err());
+    assert!(core.predict(0.1, 0.0, 0.0, -0.1).is_err());
+    assert!(core.decay_confidence_by_age(f32::NAN, 1.0).is_err());
+    assert_eq!(core, before);
+
+    // 有限の入力はそのまま受け付ける (無限大の距離は外れ扱い)
+    assert!(core.observe_scan([0.7, -0.4], 0.0, &[f32::INFINITY; 4], 20.0).is_ok());
+    core.propagate(0.0).unwrap();
+    core.step(0.5).unwrap();
+    assert_eq!(core.time, 0.5);
+}
+
+#[test]
+fn visible_landmarks_gates_by_range() {
+    let mut core = QuantumSlamCore::new(10.0).unwrap();
+    for (x, y) in [(1.0, 0.0), (0.0, -3.0), (5.0, 5.0), (-2.0, 0.0)] {
+        core.add_landmark(x, y).unwrap();
+    }
+
+    assert_eq!(core.visible_landmarks([0.0, 0.0], 2.5), vec![0, 3]);
+    // 境界上の点は範囲内
+    assert_eq!(core.visible_landmarks([0.0, 0.0], 3.0), vec![0, 1, 3]);
+    assert!(core.visible_landmarks([20.0, 20.0], 1.0).is_empty());
+}
+
+#[test]
+fn reset_observations_keeps_the_map() {
+    let mut core = triangle();
+    core.observe(0.7, -0.4).unwrap();
+    let observed = core.clone();
+
+    core.reset_observations();
+    for (lm, before) in core.landmarks.iter().zip(&observed.landmarks) {
+        assert_eq!((lm.position, lm.id, lm.confidence), (before.position, before.id, before.confidence));
+        assert_eq!((lm.observed_dist, lm.observed), (0.0, 0));
+    }
+    assert_eq!(core.probability_at(0.7, -0.4), 0.0);
+
+    // 同じ位置から観測し直せば元に戻る
+    core.observe(0.7, -0.4).unwrap();
+    assert_eq!(core, observed);
+}
+
+#[test]
+fn observe_accumulate_blends_with_feedback_strength() {
+    let mut core = triangle();
+    core.set_feedback_strength(0.25).unwrap();
+    core.observe(0.0, 0.0).unwrap();
+    let previous: Vec<f32> = core.landmarks.iter().map(|lm| lm.observed_dist).collect();
+
+    core.observe_accumulate(1.0, 0.5).unwrap();
+    for (lm, previous) in core.landmarks.iter().zip(previous) {
+        let expected = 0.75 * lm.distance_to(1.0, 0.5) + 0.25 * previous;
+        assert!((lm.observed_dist - expected).abs() < 1e-5);
+    }
+
+    // reset_observations の後でも、混ぜた観測は場に戻る
+    core.reset_observations();
+    core.observe_accumulate(1.0, 0.5).unwrap();
+    assert!(core.landmarks.iter().all(|lm| lm.observed == 1));
+    assert!(core.probability_at(1.0, 0.5) > 0.0);
+}
+
+#[test]
+fn replay_trajectory_csv_observes_then_accumulates() {
+    let path = std::env::temp_dir().join(format!("{}_replay.csv", std::process::id()));
+    std::fs::write(&path, "time,cam_x,cam_y\n# start\n0.0,0.0,0.0\n\n0.1,0.5,0.2\n0.2,1.0,0.5\n").unwrap();
+
+    let mut replayed = triangle();
+    let trajectory = replayed.replay_trajectory_csv(&path).unwrap();
+    assert_eq!(trajectory, vec![[0.0, 0.0], [0.5, 0.2], [1.0, 0.5]]);
+
+    let mut expected = triangle();
+    expected.observe(0.0, 0.0).unwrap();
+    expected.observe_accumulate(0.5, 0.2).unwrap();
+    expected.observe_accumulate(1.0, 0.5).unwrap();
+    assert_eq!(replayed, expected);
+
+    // reset_observations の後の再生も、初めからの再生と同じ場になる
+    replayed.reset_observations();
+    replayed.replay_trajectory_csv(&path).unwrap();
+    assert_eq!(replayed, expected);
+    assert!(replayed.landmarks.iter().all(|lm| lm.observed == 1));
+
+    // 2行目以降のヘッダー風の行は行番号付きのエラーになる
+    std::fs::write(&path, "0.0,0.0,0.0\ntime,cam_x,cam_y\n").unwrap();
+    let err = triangle().replay_trajectory_csv(&path).unwrap_err();
+    std::fs::remove_file(&path).unwrap();
+    assert!(matches!(&err, Error::Io(e) if e.kind() == std::io::ErrorKind::InvalidData), "{err}");
+    assert!(err.to_string().contains("line 2"), "{err}");
+}
+
+#[test]
+fn aperture_sums_poses_coherently_at_the_true_position() {
+    let mut core = triangle();
+    let cam = [0.7, -0.4];
+    assert_eq!(core.probability_aperture_at(cam[0], cam[1]), 0.0);
+
+    // 1ポーズの開口は observe した場と同じ
+    core.observe_aperture(&[cam]).unwrap();
+    let mut observed = triangle();
+    observed.observe(cam[0], cam[1]).unwrap();
+    for (x, y) in [(0.7, -0.4), (0.9, -0.1), (-1.0, 2.0)] {
+        let expected = observed.probability_at(x, y);
+        assert!((core.probability_aperture_at(x, y) - expected).abs() < 1e-4 * expected.max(1.0));
+    }
+
+    // 3ポーズ x 3ランドマークの項は真の位置でだけ全て揃う
+    core.observe_aperture(&[cam, [0.9, -0.4], [0.7, -0.1]]).unwrap();
+    assert!(core.landmarks.iter().all(|lm| lm.observed == 1 && lm.observed_dist == 0.0));
+    let peak = core.probability_aperture_at(cam[0], cam[1]);
+    assert!((peak - 81.0).abs() < 1e-2, "{peak}");
+    // 真の位置から離れた偽のピークはポーズごとに位置がずれるので、コヒーレントな和で弱まる
+    let bounds = (-5.0, 5.0, -5.0, 5.0);
+    let side_peak = |field: ProbabilityField| {
+        let peaks = field.find_peaks(2, 1.0);
+        peaks[1].1 / peaks[0].1
+    };
+    let single = side_peak(observed.evaluate_grid(bounds, 201, 201));
+    let aperture = side_peak(ProbabilityField::from_fn(bounds, 201, 201, |x, y| core.probability_aperture_at(x, y)));
+    assert!(aperture < single, "{aperture} vs {single}");
+
+    core.observe_aperture(&[]).unwrap();
+    assert_eq!(core.probability_aperture_at(cam[0], cam[1]), 0.0);
+}
+
+#[cfg(feature = "rand")]
+#[test]
+fn observe_with_detection_drops_undetected_landmarks_from_the_field() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let mut rng = StdRng::seed_from_u64(7);
+    let mut core = triangle();
+    let mut observed = triangle();
+    observed.observe(0.7, -0.4).unwrap();
+
+    core.observe_with_detection([0.7, -0.4], 1.0, &mut rng).unwrap();
+    assert_eq!(core, observed);
+
+    core.observe_with_detection([0.7, -0.4], 0.0, &mut rng).unwrap();
+    assert!(core.landmarks.iter().all(|lm| lm.observed == 0));
+    assert_eq!(core.probability_at(0.7, -0.4), 0.0);
+
+    // 検出されたランドマークだけが場に寄与する
+    core.observe_with_detection([0.7, -0.4], 0.5, &mut rng).unwrap();
+    let detected = core.landmarks.iter().filter(|lm| lm.observed == 1).count() as f64;
+    assert!((core.probability_at(0.7, -0.4) - detected * detected).abs() < 1e-4);
+
+    // [0, 1] の外や NaN の検出確率は状態を変えずに拒否する
+    let before = core.clone();
+    for detection_prob in [-0.1, 1.5, f32::NAN, f32::INFINITY] {
+        assert!(matches!(core.observe_with_detection([0.7, -0.4], detection_prob, &mut rng), Err(Error::InvalidParameter(_))));
+    }
+    assert_eq!(core, before);
+}
@@ -0,0 +1,48 @@
+//! Saving and restoring the full `QuantumSlamCore` state.
+#![cfg(feature = "persist")]
+
+use inverse_observation_induced_probability_field_interference::{Landmark3d, QuantumSlamCore, SNAPSHOT_VERSION};
+
+fn sample_core() -> QuantumSlamCore {
+    let mut core = QuantumSlamCore::new(10.0).unwrap();
+    for (x, y) in [(0.0, 4.0), (-4.0, -3.0), (4.0, -3.0)] {
+        core.add_landmark(x, y).unwrap();
+    }
+    core.landmarks_3d.push(Landmark3d::new([1.0, 2.0, 3.0]));
+    core.decay_factor = 1.5;
+    core.time = 2.0;
+    core.observe(0.7, -0.4).unwrap();
+    core.record_pose();
+    core.observe_for_agent(7, 1.0, 1.0).unwrap();
+    core
+}
+
+#[test]
+fn json_and_bincode_round_trip() {
+    let core = sample_core();
+
+    let restored = QuantumSlamCore::from_json(&core.to_json()).unwrap();
+    assert_eq!(restored, core);
+
+    let restored = QuantumSlamCore::from_bincode(&core.to_bincode()).unwrap();
+    assert_eq!(restored, core);
+
+    // ID の払い出しも引き継がれる
+    let mut restored = restored;
+    let mut original = core;
+    assert_eq!(restored.add_landmark(2.0, 2.0).unwrap(), original.add_landmark(2.0, 2.0).unwrap());
+}
+
+#[test]
+fn newer_snapshot_version_is_rejected() {
+    let json = sample_core().to_json().replacen(
+        &format!("\"version\":{}", SNAPSHOT_VERSION),
+        &format!("\"version\":{}", SNAPSHOT_VERSION + 1),
+        1,
+    );
+    assert!(QuantumSlamCore::from_json(&json).is_err());
+
+    let mut bytes = sample_core().to_bincode();
+    bytes[..4].copy_from_slice(&(SNAPSHOT_VERSION + 1).to_le_bytes());
+    assert!(QuantumSlamCore::from_bincode(&bytes).is_err());
+}
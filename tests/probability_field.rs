@@ -0,0 +1,186 @@
+//! Queries on a precomputed `ProbabilityField`.
+
+use inverse_observation_induced_probability_field_interference::{Error, ProbabilityField, QuantumSlamCore};
+
+const BOUNDS: (f32, f32, f32, f32) = (-2.0, 2.0, -1.0, 1.0);
+
+#[test]
+fn argmax_and_argmin_return_cell_centers() {
+    // x_max, y_min の角で最大、x_min, y_max の角で最小になる場
+    let field = ProbabilityField::from_fn(BOUNDS, 8, 4, |x, y| (x - 2.0 * y) as f64);
+
+    let (max_pos, max_value) = field.argmax();
+    assert_eq!(max_pos, field.cell_center(7, 0));
+    assert_eq!(max_pos, [1.75, -0.75]);
+    assert_eq!(max_value, field.get(7, 0));
+
+    let (min_pos, min_value) = field.argmin();
+    assert_eq!(min_pos, [-1.75, 0.75]);
+    assert_eq!(min_value, field.get(0, 3));
+    assert_eq!(min_value, -3.25);
+}
+
+#[test]
+fn approx_eq_checks_grid_and_values() {
+    let field = ProbabilityField::from_fn(BOUNDS, 4, 2, |x, y| (x * y) as f64);
+    let mut nudged = field.clone();
+    nudged.values[3] += 1e-3;
+
+    assert!(field.approx_eq(&field, 0.0));
+    assert!(field.approx_eq(&nudged, 1e-2));
+    assert!(!field.approx_eq(&nudged, 1e-4));
+
+    // 値が同じでも格子が違えば等しくない
+    let reshaped = ProbabilityField::from_values(BOUNDS, 2, 4, field.values.clone()).unwrap();
+    assert!(!field.approx_eq(&reshaped, 1.0));
+    let moved = ProbabilityField::from_values((-2.0, 2.0, 0.0, 2.0), 4, 2, field.values.clone()).unwrap();
+    assert!(!field.approx_eq(&moved, 1.0));
+}
+
+#[test]
+fn cores_compare_by_value() {
+    let mut core = QuantumSlamCore::new(10.0).unwrap();
+    core.add_landmark(1.0, 2.0).unwrap();
+    let copy = core.clone();
+    assert_eq!(core, copy);
+
+    core.observe(0.0, 0.0).unwrap();
+    assert_ne!(core, copy);
+}
+
+#[test]
+fn iso_contours_trace_closed_circles() {
+    let field = ProbabilityField::from_fn((-2.0, 2.0, -2.0, 2.0), 41, 41, |x, y| (x * x + y * y) as f64);
+
+    let contours = field.iso_contours(&[1.0]);
+    assert_eq!(contours.len(), 1);
+    let circle = &contours[0];
+    assert!(circle.len() > 20);
+    assert_eq!(circle.first(), circle.last());
+    for &[x, y] in circle {
+        let r = (x * x + y * y).sqrt();
+        // セル内の線形補間なので半径の誤差はセル幅 (0.1) より十分小さい
+        assert!((r - 1.0).abs() < 0.01, "point ({x}, {y}) at radius {r}");
+    }
+
+    // 範囲外のレベルには等値線がない
+    assert!(field.iso_contours(&[100.0]).is_empty());
+    assert_eq!(field.iso_contours(&[0.5, 1.0, 2.0]).len(), 3);
+}
+
+#[test]
+fn otsu_threshold_separates_two_clusters() {
+    // 左半分は 0.0–0.1、右半分は 0.9–1.0 の二峰性の場
+    let field = ProbabilityField::from_fn(BOUNDS, 20, 10, |x, y| {
+        let jitter = 0.05 * (1.0 + (7.0 * y).sin()) as f64;
+        if x < 0.0 { jitter } else { 0.9 + jitter }
+    });
+
+    let (lo, hi) = field.value_range().unwrap();
+    let histogram = field.histogram(10);
+    assert_eq!(histogram.iter().sum::<usize>(), 200);
+    assert_eq!(histogram[0] + histogram[9], 200, "{histogram:?} over [{lo}, {hi}]");
+
+    let threshold = field.otsu_threshold();
+    assert!(threshold > 0.1 && threshold < 0.9, "threshold {threshold}");
+    let occupied = field.occupancy(None);
+    for iy in 0..field.ny {
+        for ix in 0..field.nx {
+            assert_eq!(occupied[iy * field.nx + ix], field.cell_center(ix, iy)[0] > 0.0);
+        }
+    }
+
+    // 一様な場はその値が閾値で、占有セルはない
+    let flat = ProbabilityField::from_fn(BOUNDS, 4, 4, |_, _| 0.5);
+    assert_eq!(flat.otsu_threshold(), 0.5);
+    assert_eq!(flat.histogram(3), vec![16, 0, 0]);
+    assert!(flat.occupancy(None).iter().all(|&occupied| !occupied));
+}
+
+#[test]
+fn centroid_is_the_probability_weighted_mean() {
+    // セル中心 x = -1.5, -0.5, 0.5, 1.5 のうち両端だけに 1:3 の重み
+    let mut values = vec![0.0; 8];
+    values[0] = 1.0;
+    values[7] = 3.0;
+    let field = ProbabilityField::from_values(BOUNDS, 4, 2, values).unwrap();
+    let [x, y] = field.centroid().unwrap();
+    assert!((x - 0.75).abs() < 1e-6 && (y - 0.25).abs() < 1e-6, "({x}, {y})");
+
+    assert_eq!(ProbabilityField::from_fn(BOUNDS, 4, 2, |_, _| 0.0).centroid(), None);
+
+    let mut core = QuantumSlamCore::new(10.0).unwrap();
+    for (x, y) in [(0.0, 4.0), (-4.0, -3.0), (4.0, -3.0)] {
+        core.add_landmark(x, y).unwrap();
+    }
+    core.observe(0.7, -0.4).unwrap();
+    let bounds = (-5.0, 5.0, -5.0, 5.0);
+    assert_eq!(core.expected_position(bounds, 32, 32), core.evaluate_grid(bounds, 32, 32).centroid());
+    assert_eq!(QuantumSlamCore::new(10.0).unwrap().expected_position(bounds, 8, 8), None);
+}
+
+#[test]
+fn argmax_subpixel_recovers_a_quadratic_peak() {
+    // 放物面なら3点の放物線当てはめで頂点が厳密に求まる
+    let peak = [0.33, -0.21];
+    let field = ProbabilityField::from_fn(BOUNDS, 16, 8, |x, y| {
+        5.0 - ((x - peak[0]) as f64).powi(2) - 2.0 * ((y - peak[1]) as f64).powi(2)
+    });
+
+    let (cell, _) = field.argmax();
+    assert_ne!(cell, peak);
+    let ([x, y], value) = field.argmax_subpixel();
+    assert!((x - peak[0]).abs() < 1e-5 && (y - peak[1]).abs() < 1e-5, "({x}, {y})");
+    assert!((value - 5.0).abs() < 1e-6, "{value}");
+
+    // 粗い格子ではセル中心より真の位置に近い
+    let mut core = QuantumSlamCore::new(10.0).unwrap();
+    for (x, y) in [(0.0, 4.0), (-4.0, -3.0), (4.0, -3.0)] {
+        core.add_landmark(x, y).unwrap();
+    }
+    core.observe(0.7, -0.4).unwrap();
+    let bounds = (-5.0, 5.0, -5.0, 5.0);
+    let error = |[x, y]: [f32; 2]| (x - 0.7).hypot(y + 0.4);
+    let coarse = error(core.estimate_position(bounds, 64).0);
+    let refined = error(core.estimate_position_subpixel(bounds, 64).0);
+    assert!(refined < coarse, "{refined} vs {coarse}");
+}
+
+#[test]
+fn entropy_spans_a_point_mass_to_a_flat_field() {
+    let flat = ProbabilityField::from_fn(BOUNDS, 8, 4, |_, _| 0.3);
+    assert!((flat.entropy() - (32.0f64).ln()).abs() < 1e-12);
+
+    let mut values = vec![0.0; 32];
+    values[5] = 2.0;
+    assert_eq!(ProbabilityField::from_values(BOUNDS, 8, 4, values).unwrap().entropy(), 0.0);
+    assert_eq!(ProbabilityField::from_fn(BOUNDS, 8, 4, |_, _| 0.0).entropy(), 0.0);
+}
+
+#[test]
+fn expected_information_gain_favors_views_that_break_a_mirror_ambiguity() {
+    // x 軸上の2つのランドマークだけでは (0.5, ±1.5) が区別できない。
+    // 弱い3つ目のランドマークで真の位置 (0.5, 1.5) の山をわずかに高くする
+    let mut core = QuantumSlamCore::new(10.0).unwrap();
+    core.add_landmark(-3.0, 0.0).unwrap();
+    core.add_landmark(3.0, 0.0).unwrap();
+    core.add_landmark(0.0, 5.0).unwrap();
+    core.landmarks[2].confidence = 0.2;
+    core.observe(0.5, 1.5).unwrap();
+    let before = core.clone();
+    let bounds = (-5.0, 5.0, -5.0, 5.0);
+    let (peak, _) = core.evaluate_grid(bounds, 48, 48).argmax();
+    assert!((peak[1] - 1.5).abs() < 0.2, "{peak:?}");
+
+    // x 方向の移動は鏡像の仮説を鏡像のまま動かすが、y 方向の移動は対称性を崩す
+    let along_axis = core.expected_information_gain([peak[0] + 1.0, peak[1]], bounds, 48, 16).unwrap();
+    let across_axis = core.expected_information_gain([peak[0], peak[1] + 1.0], bounds, 48, 16).unwrap();
+    assert!(across_axis > along_axis, "{across_axis} vs {along_axis}");
+    assert!(across_axis > 0.0, "{across_axis}");
+    assert_eq!(core, before);
+
+    assert!(matches!(core.expected_information_gain([f32::NAN, 0.0], bounds, 8, 4), Err(Error::NonFinitePosition(_))));
+    assert!(matches!(core.expected_information_gain(peak, bounds, 8, 0), Err(Error::InvalidParameter(_))));
+    let unobserved = QuantumSlamCore::new(10.0).unwrap();
+    assert_eq!(unobserved.expected_information_gain(peak, bounds, 8, 4).unwrap(), 0.0);
+}
@@ -0,0 +1,365 @@
+//! Headless renderer output that golden-image tests rely on.
+
+use inverse_observation_induced_probability_field_interference::{
+    plan_tiles, shader_source, validate_shader, Error, FeedbackMode, FieldMode, GpuFieldEvaluator, HeadlessRenderer,
+    Landmark, QuantumSlamCore, Tile, Uniforms,
+};
+
+// GPU を使うテストは #[ignore] で、GPU のある環境で `cargo test -- --include-ignored` で走らせる
+// (cargo test に実行時スキップは無い)。アダプタが無ければ黙って通らないよう失敗させる
+fn gpu<T>(result: Result<T, Error>) -> T {
+    result.unwrap_or_else(|e| panic!("GPU tests need a GPU adapter: {e}"))
+}
+
+#[test]
+#[ignore = "needs a GPU adapter"]
+fn render_frame_to_bytes_is_deterministic() {
+    let mut renderer = gpu(HeadlessRenderer::new(64, 48));
+
+    let landmarks = [Landmark::new(0.2, 0.1), Landmark::new(-0.4, 0.3)];
+    let uniforms = Uniforms::builder().resolution(64, 48).build().unwrap();
+
+    let first = renderer.render_frame_to_bytes(&uniforms, &landmarks, 1.5);
+    // 途中の描画履歴は結果に影響しない
+    renderer.render(&uniforms, &landmarks);
+    let second = renderer.render_frame_to_bytes(&uniforms, &landmarks, 1.5);
+
+    assert_eq!(first.len(), 64 * 48 * 4);
+    assert_eq!(first, second);
+}
+
+#[test]
+#[ignore = "needs a GPU adapter"]
+fn compute_field_matches_cpu_evaluate_grid() {
+    let mut renderer = gpu(HeadlessRenderer::new(1, 1));
+
+    let mut core = QuantumSlamCore::new(10.0).unwrap();
+    for (x, y) in [(0.0, 4.0), (-4.0, -3.0), (4.0, -3.0)] {
+        core.add_landmark(x, y).unwrap();
+    }
+    core.observe(0.7, -0.4).unwrap();
+
+    let bounds = (-5.0, 5.0, -5.0, 5.0);
+    let gpu = renderer.compute_field(&core, bounds, 48, 32).unwrap();
+    let cpu = core.evaluate_grid(bounds, 48, 32);
+
+    assert_eq!(gpu.len(), cpu.values.len());
+    for (g, c) in gpu.iter().zip(&cpu.values) {
+        assert!((*g as f64 - c).abs() < 1e-3, "gpu {g} vs cpu {c}");
+    }
+}
+
+#[test]
+#[ignore = "needs a GPU adapter"]
+fn headless_evaluate_grid_matches_cpu_across_resolutions() {
+    let mut renderer = gpu(HeadlessRenderer::new(1, 1));
+
+    // examples/cpu_vs_gpu.rs と同じ配置と許容誤差
+    let mut core = QuantumSlamCore::new(10.0).unwrap();
+    for (x, y) in [(0.0, 4.0), (-4.0, -3.0), (4.0, -3.0), (2.5, 2.5), (-3.0, 1.0)] {
+        core.add_landmark(x, y).unwrap();
+    }
+    core.observe(0.7, -0.4).unwrap();
+
+    let bounds = (-5.0, 5.0, -5.0, 5.0);
+    for n in [16, 64, 256] {
+        let gpu = renderer.evaluate_grid(&core, bounds, n, n).unwrap();
+        assert!(gpu.approx_eq(&core.evaluate_grid(bounds, n, n), 1e-3), "{n}x{n}");
+    }
+}
+
+#[test]
+#[ignore = "needs a GPU adapter"]
+fn compute_field_honors_phase_offset() {
+    let mut renderer = gpu(HeadlessRenderer::new(1, 1));
+
+    let mut core = QuantumSlamCore::new(10.0).unwrap();
+    for (x, y) in [(0.0, 4.0), (-4.0, -3.0), (4.0, -3.0)] {
+        core.add_landmark(x, y).unwrap();
+    }
+    core.observe_directional(0.7, -0.4, 1.0).unwrap();
+
+    let bounds = (-5.0, 5.0, -5.0, 5.0);
+    let gpu = renderer.compute_field(&core, bounds, 32, 32).unwrap();
+    let cpu = core.evaluate_grid(bounds, 32, 32);
+
+    for (g, c) in gpu.iter().zip(&cpu.values) {
+        assert!((*g as f64 - c).abs() < 1e-3, "gpu {g} vs cpu {c}");
+    }
+}
+
+#[test]
+#[ignore = "needs a GPU adapter"]
+fn compute_field_honors_directional_decay() {
+    let mut renderer = gpu(HeadlessRenderer::new(1, 1));
+
+    let mut core = QuantumSlamCore::new(10.0).unwrap();
+    for (x, y) in [(0.0, 4.0), (-4.0, -3.0), (4.0, -3.0)] {
+        core.add_landmark(x, y).unwrap();
+    }
+    // 壁に沿って (x 方向) ゆっくり、壁から離れる方向に速く減衰する
+    core.landmarks[0] = core.landmarks[0].with_directional_decay(0.0, 0.2, 6.0);
+    core.observe(0.7, -0.4).unwrap();
+
+    let bounds = (-5.0, 5.0, -5.0, 5.0);
+    let gpu = renderer.compute_field(&core, bounds, 32, 32).unwrap();
+    let cpu = core.evaluate_grid(bounds, 32, 32);
+
+    for (g, c) in gpu.iter().zip(&cpu.values) {
+        assert!((*g as f64 - c).abs() < 1e-3, "gpu {g} vs cpu {c}");
+    }
+}
+
+#[test]
+#[ignore = "needs a GPU adapter"]
+fn compute_field_honors_landmark_wave_number() {
+    let mut renderer = gpu(HeadlessRenderer::new(1, 1));
+
+    let mut core = QuantumSlamCore::new(10.0).unwrap();
+    let mut ids = Vec::new();
+    for (x, y) in [(0.0, 4.0), (-4.0, -3.0), (4.0, -3.0)] {
+        ids.push(core.add_landmark(x, y).unwrap());
+    }
+    core.set_landmark_wave_number(ids[1], Some(17.0)).unwrap();
+    core.observe(0.7, -0.4).unwrap();
+
+    let bounds = (-5.0, 5.0, -5.0, 5.0);
+    let gpu = renderer.compute_field(&core, bounds, 32, 32).unwrap();
+    let cpu = core.evaluate_grid(bounds, 32, 32);
+
+    for (g, c) in gpu.iter().zip(&cpu.values) {
+        assert!((*g as f64 - c).abs() < 1e-3, "gpu {g} vs cpu {c}");
+    }
+}
+
+#[test]
+#[ignore = "needs a GPU adapter"]
+fn compute_field_honors_landmark_sigma() {
+    let mut renderer = gpu(HeadlessRenderer::new(1, 1));
+
+    let mut core = QuantumSlamCore::new(10.0).unwrap();
+    let mut ids = Vec::new();
+    for (x, y) in [(0.0, 4.0), (-4.0, -3.0), (4.0, -3.0)] {
+        ids.push(core.add_landmark(x, y).unwrap());
+    }
+    core.landmarks[0] = core.landmarks[0].with_directional_decay(0.3, 4.0, 1.0);
+    core.set_landmark_sigma(ids[0], 0.5).unwrap();
+    core.set_landmark_sigma(ids[2], 2.0).unwrap();
+    core.observe(0.7, -0.4).unwrap();
+
+    let bounds = (-5.0, 5.0, -5.0, 5.0);
+    let gpu = renderer.compute_field(&core, bounds, 32, 32).unwrap();
+    let cpu = core.evaluate_grid(bounds, 32, 32);
+
+    for (g, c) in gpu.iter().zip(&cpu.values) {
+        assert!((*g as f64 - c).abs() < 1e-3, "gpu {g} vs cpu {c}");
+    }
+}
+
+#[test]
+#[ignore = "needs a GPU adapter"]
+fn compute_field_honors_radial_velocity() {
+    let mut renderer = gpu(HeadlessRenderer::new(1, 1));
+
+    let mut core = QuantumSlamCore::new(10.0).unwrap();
+    for (x, y) in [(0.0, 4.0), (-4.0, -3.0), (4.0, -3.0)] {
+        core.add_landmark(x, y).unwrap();
+    }
+    core.observe_with_velocity([0.7, -0.4], [0.8, 0.3]).unwrap();
+    core.time = 0.6;
+
+    let bounds = (-5.0, 5.0, -5.0, 5.0);
+    let gpu = renderer.compute_field(&core, bounds, 32, 32).unwrap();
+    let cpu = core.evaluate_grid(bounds, 32, 32);
+
+    // 時刻 0 とは異なる場になっている
+    let mut at_rest = core.clone();
+    at_rest.time = 0.0;
+    assert_ne!(at_rest.evaluate_grid(bounds, 32, 32), cpu);
+
+    for (g, c) in gpu.iter().zip(&cpu.values) {
+        assert!((*g as f64 - c).abs() < 1e-3, "gpu {g} vs cpu {c}");
+    }
+}
+
+#[test]
+#[ignore = "needs a GPU adapter"]
+fn compute_field_follows_core_clock() {
+    let mut renderer = gpu(HeadlessRenderer::new(1, 1));
+
+    let mut core = QuantumSlamCore::new(10.0).unwrap();
+    let mut ids = Vec::new();
+    for (x, y) in [(0.0, 4.0), (-4.0, -3.0), (4.0, -3.0)] {
+        ids.push(core.add_landmark(x, y).unwrap());
+    }
+    // 波面が観測距離から外へ進み、波数の違うランドマークの間では位相差もずれる
+    core.set_landmark_wave_number(ids[1], Some(13.0)).unwrap();
+    core.propagation_speed = 0.5;
+    core.observe(0.7, -0.4).unwrap();
+
+    let bounds = (-5.0, 5.0, -5.0, 5.0);
+    let before = core.evaluate_grid(bounds, 32, 32);
+    core.advance_time(0.3).unwrap();
+    let cpu = core.evaluate_grid(bounds, 32, 32);
+    assert_ne!(before, cpu);
+
+    let gpu = renderer.compute_field(&core, bounds, 32, 32).unwrap();
+    for (g, c) in gpu.iter().zip(&cpu.values) {
+        assert!((*g as f64 - c).abs() < 1e-3, "gpu {g} vs cpu {c}");
+    }
+}
+
+#[test]
+#[ignore = "needs a GPU adapter"]
+fn gpu_field_evaluator_matches_headless_renderer() {
+    let mut evaluator = gpu(GpuFieldEvaluator::new());
+    let mut renderer = gpu(HeadlessRenderer::new(1, 1));
+    let mut core = QuantumSlamCore::new(10.0).unwrap();
+    for (x, y) in [(0.0, 4.0), (-4.0, -3.0), (4.0, -3.0)] {
+        core.add_landmark(x, y).unwrap();
+    }
+    core.observe(0.7, -0.4).unwrap();
+
+    let bounds = (-5.0, 5.0, -5.0, 5.0);
+    assert_eq!(
+        evaluator.evaluate(&core, bounds, 40, 30).unwrap(),
+        renderer.compute_field(&core, bounds, 40, 30).unwrap()
+    );
+}
+
+#[test]
+#[ignore = "needs a GPU adapter"]
+fn oversized_grid_reports_capacity_exceeded() {
+    let mut evaluator = gpu(GpuFieldEvaluator::new());
+    let mut core = QuantumSlamCore::new(10.0).unwrap();
+    core.add_landmark(0.0, 4.0).unwrap();
+    core.observe(0.7, -0.4).unwrap();
+
+    // 2^32 セルの f32 はどのデバイスのストレージバッファ上限も超える
+    let err = evaluator.evaluate(&core, (-5.0, 5.0, -5.0, 5.0), 1 << 16, 1 << 16).unwrap_err();
+    assert!(matches!(err, Error::CapacityExceeded { requested, .. } if requested == 1 << 32), "{err}");
+}
+
+#[test]
+#[ignore = "needs a GPU adapter"]
+fn embedded_shaders_validate() {
+    // 表示用の blit シェーダーは wasm のサーフェスでしか走らないため、ここでコンパイルを確認する
+    gpu(validate_shader());
+}
+
+#[test]
+fn shader_source_exposes_the_field_mode_entry_points() {
+    let source = shader_source();
+    for mode in [FieldMode::Coherent, FieldMode::Incoherent, FieldMode::Phase] {
+        assert!(source.contains(&format!("fn {}(", mode.entry_point())), "{mode:?}");
+    }
+}
+
+#[test]
+fn plan_tiles_covers_every_workgroup_once() {
+    // 100 x 40 px = 7 x 3 ワークグループ (16 px)
+    let tiles = plan_tiles(100, 40, 3);
+    assert_eq!(tiles.len(), 3);
+    assert_eq!(tiles[2], Tile { origin: [96, 0], workgroups: [1, 3] });
+
+    let mut covered = vec![0; 7 * 3];
+    for tile in &tiles {
+        for gy in 0..tile.workgroups[1] {
+            for gx in 0..tile.workgroups[0] {
+                covered[((tile.origin[1] / 16 + gy) * 7 + tile.origin[0] / 16 + gx) as usize] += 1;
+            }
+        }
+    }
+    assert!(covered.iter().all(|&count| count == 1), "{covered:?}");
+
+    assert_eq!(plan_tiles(100, 40, 1024), vec![Tile { origin: [0, 0], workgroups: [7, 3] }]);
+}
+
+#[test]
+#[ignore = "needs a GPU adapter"]
+fn tiled_dispatch_matches_a_single_dispatch() {
+    let mut renderer = gpu(HeadlessRenderer::new(70, 50));
+
+    let landmarks = [Landmark::new(0.2, 0.1), Landmark::new(-0.4, 0.3)];
+    let uniforms = Uniforms::builder().resolution(70, 50).build().unwrap();
+
+    let single = renderer.render_frame_to_bytes(&uniforms, &landmarks, 1.5);
+    assert!(single.iter().any(|&byte| byte != 0));
+    renderer.set_max_tile_workgroups(1);
+    let tiled = renderer.render_frame_to_bytes(&uniforms, &landmarks, 1.5);
+    assert_eq!(single, tiled);
+}
+
+#[test]
+#[ignore = "needs a GPU adapter"]
+fn field_stats_summarize_the_green_channel() {
+    let mut renderer = gpu(HeadlessRenderer::new(40, 30));
+    let landmarks = [Landmark::new(0.2, 0.1), Landmark::new(-0.4, 0.3)];
+    let uniforms = Uniforms::builder().resolution(40, 30).time(1.5).build().unwrap();
+
+    renderer.render(&uniforms, &landmarks);
+    assert_eq!(renderer.field_stats(), None);
+
+    renderer.set_collect_stats(true);
+    assert_eq!(renderer.field_stats(), None);
+    renderer.render(&uniforms, &landmarks);
+    let (min, max, mean) = renderer.field_stats().unwrap();
+
+    let green: Vec<f32> = renderer.read_pixels().chunks(4).map(|px| px[1] as f32 / 255.0).collect();
+    let expected_min = green.iter().copied().fold(f32::INFINITY, f32::min);
+    let expected_max = green.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let expected_mean = green.iter().sum::<f32>() / green.len() as f32;
+    assert!(expected_max > expected_min);
+    assert!((min - expected_min).abs() < 1e-3, "{min} vs {expected_min}");
+    assert!((max - expected_max).abs() < 1e-3, "{max} vs {expected_max}");
+    assert!((mean - expected_mean).abs() < 1e-3, "{mean} vs {expected_mean}");
+
+    renderer.set_collect_stats(false);
+    assert_eq!(renderer.field_stats(), None);
+}
+
+#[test]
+#[ignore = "needs a GPU adapter"]
+fn confidence_feedback_follows_the_current_frame_where_history_is_dark() {
+    let mut renderer = gpu(HeadlessRenderer::new(40, 30));
+    let landmarks = [Landmark::new(0.2, 0.1), Landmark::new(-0.4, 0.3)];
+    let mut first_frame_green = |mode: FeedbackMode, strength: f32| -> Vec<u8> {
+        let uniforms = Uniforms::builder()
+            .resolution(40, 30)
+            .feedback_strength(strength)
+            .feedback_mode(mode)
+            .build()
+            .unwrap();
+        renderer.clear();
+        renderer.render(&uniforms, &landmarks);
+        renderer.read_pixels().chunks(4).map(|px| px[1]).collect()
+    };
+
+    // 履歴が空 (prev = 0) なら Confidence は現在フレームをそのまま使い、Uniform は 1 - α 倍に薄める
+    let uniform = first_frame_green(FeedbackMode::Uniform, 0.9);
+    let confidence = first_frame_green(FeedbackMode::Confidence, 0.9);
+    assert!(uniform.iter().zip(&confidence).all(|(u, c)| u <= c));
+    assert!(uniform.iter().zip(&confidence).any(|(u, c)| u < c));
+
+    // フィードバックが無ければ両者は同じ
+    assert_eq!(first_frame_green(FeedbackMode::Uniform, 0.0), first_frame_green(FeedbackMode::Confidence, 0.0));
+}
+
+#[test]
+#[ignore = "needs a GPU adapter"]
+fn set_field_mode_switches_the_compute_entry_point() {
+    let mut renderer = gpu(HeadlessRenderer::new(40, 30));
+    let landmarks = [Landmark::new(0.2, 0.1), Landmark::new(-0.4, 0.3), Landmark::new(0.5, -0.5)];
+    let uniforms = Uniforms::builder().resolution(40, 30).feedback_strength(0.0).build().unwrap();
+
+    let mut frames = Vec::new();
+    for mode in [FieldMode::Coherent, FieldMode::Incoherent, FieldMode::Phase, FieldMode::Coherent] {
+        renderer.set_field_mode(mode);
+        frames.push(renderer.render_frame_to_bytes(&uniforms, &landmarks, 1.5));
+    }
+
+    assert_ne!(frames[0], frames[1]);
+    assert_ne!(frames[0], frames[2]);
+    assert_ne!(frames[1], frames[2]);
+    assert_eq!(frames[0], frames[3]);
+}
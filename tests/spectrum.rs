@@ -0,0 +1,43 @@
+//! Spatial-frequency analysis of the field.
+#![cfg(feature = "fft")]
+
+use inverse_observation_induced_probability_field_interference::{ProbabilityField, QuantumSlamCore};
+
+#[test]
+fn fft_puts_a_cosine_in_its_frequency_bins() {
+    // 幅 4 の領域で x 方向に 3 周期の余弦 + 定数 1
+    let (nx, ny) = (32, 8);
+    let field = ProbabilityField::from_fn((0.0, 4.0, 0.0, 1.0), nx, ny, |x, _| {
+        1.0 + (std::f64::consts::TAU * 3.0 * x as f64 / 4.0).cos()
+    });
+    let spectrum = field.fft();
+    assert_eq!(spectrum.len(), nx * ny);
+
+    let n = (nx * ny) as f64;
+    let tol = 1e-3;
+    assert!((spectrum[0].re - n).abs() < tol && spectrum[0].im.abs() < tol, "DC {}", spectrum[0]);
+    // 実数の余弦は kx = 3 と kx = nx - 3 に半分ずつ (セル中心のずれは位相に出る)
+    for kx in [3, nx - 3] {
+        assert!((spectrum[kx].norm() - n / 2.0).abs() < tol, "bin {kx}: {}", spectrum[kx]);
+    }
+    let leaked: f64 = spectrum
+        .iter()
+        .enumerate()
+        .filter(|&(i, _)| ![0, 3, nx - 3].contains(&i))
+        .map(|(_, c)| c.norm())
+        .sum();
+    assert!(leaked < tol, "energy outside the cosine bins: {leaked}");
+}
+
+#[test]
+fn field_fft_transforms_the_evaluated_grid() {
+    let mut core = QuantumSlamCore::new(10.0).unwrap();
+    for (x, y) in [(0.0, 4.0), (-4.0, -3.0), (4.0, -3.0)] {
+        core.add_landmark(x, y).unwrap();
+    }
+    core.observe(0.7, -0.4).unwrap();
+    let bounds = (-5.0, 5.0, -5.0, 5.0);
+
+    let expected = core.evaluate_grid(bounds, 16, 12).fft();
+    assert_eq!(core.field_fft(bounds, 16, 12), expected);
+}
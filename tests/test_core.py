@@ -1,5 +1,6 @@
 import pytest
 import math
+import struct
 
 # プロジェクト名(Cargo.tomlのlib name)に合わせてインポート
 import inverse_observation_induced_probability_field_interference
@@ -89,6 +90,731 @@ def test_interference_resolution():
     )
 
 
+
+# ランドマーク配置のバリエーション (collinear は直線に関する鏡像解が同確率になる)
+LANDMARK_GEOMETRIES = {
+    "collinear": [(-8.0, 0.0), (0.0, 0.0), (8.0, 0.0)],
+    "triangular": [(0.0, 10.0), (-10.0, -10.0), (10.0, -10.0)],
+    # random.Random(7) で生成した固定配置
+    "random": [(-3.52, -6.98), (3.02, -8.55), (0.72, -2.69), (-8.84, 0.15), (-9.25, -1.33), (-8.6, -8.19)],
+}
+
+
+@pytest.mark.parametrize("geometry", sorted(LANDMARK_GEOMETRIES))
+@pytest.mark.parametrize("true_pos", [(1.5, 2.0), (-1.0, -2.5), (0.3, 0.7)])
+def test_observe_estimate_accuracy(geometry, true_pos):
+    """
+    observe -> estimate_position の往復で、真のカメラ位置がグリッド精度で復元されることを確認
+    """
+    sim = inverse_observation_induced_probability_field_interference.PyQuantumSlam(10.0)
+    for x, y in LANDMARK_GEOMETRIES[geometry]:
+        sim.add_landmark(x, y)
+
+    sim.update_observation(*true_pos)
+    (est_x, est_y), _ = sim.estimate_position((-5.0, 5.0, -5.0, 5.0), 201)
+
+    # セル幅 (10 / 201 ≈ 0.05) の2倍を許容誤差とする
+    tolerance = 0.1
+    candidates = [true_pos]
+    if geometry == "collinear":
+        candidates.append((true_pos[0], -true_pos[1]))
+
+    errors = [math.hypot(est_x - x, est_y - y) for x, y in candidates]
+    assert min(errors) < tolerance, (
+        f"[{geometry}] estimate ({est_x:.3f}, {est_y:.3f}) too far from {true_pos}"
+    )
+
+
+
+def test_decay_factor_constructor_argument():
+    """
+    コンストラクタの decay_factor がプロパティと同じ値を設定し、残差の減衰に効くことを確認
+    """
+    module = inverse_observation_induced_probability_field_interference
+    sim = module.PyQuantumSlam(10.0, decay_factor=8.0)
+    assert sim.decay_factor == 8.0
+
+    default = module.PyQuantumSlam(10.0)
+    for s in (sim, default):
+        s.add_landmark(0.0, 10.0)
+        s.update_observation(0.0, 0.0)
+
+    # 観測距離から外れた点では、減衰率が大きいほど確率が小さい
+    assert sim.get_probability(0.0, 0.5) < default.get_probability(0.0, 0.5)
+
+    with pytest.raises(ValueError):
+        module.PyQuantumSlam(10.0, decay_factor=-1.0)
+
+
+def test_probability_grid_matches_pointwise():
+    """
+    probability_grid が行優先・セル中心で get_probability と一致することを確認
+    """
+    sim = inverse_observation_induced_probability_field_interference.PyQuantumSlam(10.0)
+    for x, y in LANDMARK_GEOMETRIES["triangular"]:
+        sim.add_landmark(x, y)
+    sim.update_observation(0.3, 0.7)
+
+    nx, ny = 8, 5
+    grid = sim.probability_grid(-5.0, 5.0, -2.5, 2.5, nx, ny)
+    assert len(grid) == nx * ny
+
+    for iy in range(ny):
+        for ix in range(nx):
+            x = -5.0 + (ix + 0.5) * 10.0 / nx
+            y = -2.5 + (iy + 0.5) * 5.0 / ny
+            assert grid[iy * nx + ix] == pytest.approx(sim.get_probability(x, y), rel=1e-5)
+
+
+def test_set_observation_matches_simulated_observation():
+    """
+    実測距離を set_observation で与えた場が、update_observation のシミュレーションと一致することを確認
+    """
+    module = inverse_observation_induced_probability_field_interference
+    measured = module.PyQuantumSlam(10.0)
+    simulated = module.PyQuantumSlam(10.0)
+
+    true_pos = (0.3, 0.7)
+    for x, y in LANDMARK_GEOMETRIES["triangular"]:
+        landmark_id = measured.add_landmark(x, y)
+        measured.set_observation(landmark_id, math.hypot(x - true_pos[0], y - true_pos[1]))
+        simulated.add_landmark(x, y)
+    simulated.update_observation(*true_pos)
+
+    for x, y in [true_pos, (2.0, -1.0)]:
+        assert measured.get_probability(x, y) == pytest.approx(simulated.get_probability(x, y), rel=1e-5)
+
+    with pytest.raises(ValueError):
+        measured.set_observation(999, 1.0)
+
+
+def test_bearing_only_observation_localizes_camera():
+    """
+    方位のみの観測 (距離なし) でも、場の最大が真のカメラ位置に来ることを確認
+    """
+    sim = inverse_observation_induced_probability_field_interference.PyQuantumSlam(10.0)
+    for x, y in LANDMARK_GEOMETRIES["triangular"]:
+        sim.add_landmark(x, y)
+
+    true_pos = (0.7, -0.4)
+    sim.observe_bearing(*true_pos, 0.3)
+
+    (x, y), _ = sim.estimate_pose()
+    assert math.hypot(x - true_pos[0], y - true_pos[1]) < 0.05
+    assert sim.get_probability(*true_pos) > 5.0 * sim.get_probability(true_pos[0] + 2.0, true_pos[1])
+
+
+def test_range_bearing_resolves_mirror_ambiguity():
+    """
+    直線配置では距離のみだと鏡像解が同確率になるが、方位を併用すると真の位置だけが残ることを確認
+    """
+    module = inverse_observation_induced_probability_field_interference
+    range_only = module.PyQuantumSlam(10.0)
+    range_bearing = module.PyQuantumSlam(10.0)
+    range_bearing.bearing_wave_number = 5.0
+    for x, y in LANDMARK_GEOMETRIES["collinear"]:
+        range_only.add_landmark(x, y)
+        range_bearing.add_landmark(x, y)
+
+    true_pos, mirror = (1.0, 2.0), (1.0, -2.0)
+    range_only.update_observation(*true_pos)
+    range_bearing.observe_range_bearing(*true_pos, 0.0)
+
+    assert range_only.get_probability(*mirror) == pytest.approx(range_only.get_probability(*true_pos), rel=1e-4)
+    assert range_bearing.get_probability(*true_pos) > 20.0 * range_bearing.get_probability(*mirror)
+    assert range_bearing.get_probability(*true_pos) == pytest.approx(range_only.get_probability(*true_pos), rel=1e-4)
+
+
+def test_reinforce_and_decay_confidence():
+    """
+    decay_all で全ランドマークが薄れ、reinforce の証拠で信頼度が上下することを確認
+    """
+    sim = inverse_observation_induced_probability_field_interference.PyQuantumSlam(10.0)
+    ids = [sim.add_landmark(x, y) for x, y in LANDMARK_GEOMETRIES["triangular"]]
+    true_pos = (0.3, 0.7)
+    sim.update_observation(*true_pos)
+    p_full = sim.get_probability(*true_pos)
+
+    # 振幅が半分 -> |psi|^2 は 1/4
+    sim.decay_all(0.5)
+    assert sim.get_probability(*true_pos) == pytest.approx(0.25 * p_full, rel=1e-4)
+
+    for landmark_id in ids:
+        sim.reinforce(landmark_id, 10.0)
+    assert sim.get_probability(*true_pos) == pytest.approx(p_full, rel=1e-2)
+
+    # 矛盾したランドマークは場からほぼ消える (3本中2本が残る)
+    sim.reinforce(ids[0], -20.0)
+    assert sim.get_probability(*true_pos) == pytest.approx(4.0 / 9.0 * p_full, rel=1e-2)
+
+    with pytest.raises(ValueError):
+        sim.decay_all(1.5)
+    with pytest.raises(ValueError):
+        sim.reinforce(999, 1.0)
+
+
+def test_associate_matches_anonymous_ranges():
+    """
+    ラベルのない距離計測が正しいランドマークに対応付けられ、対応のない計測は None になることを確認
+    """
+    sim = inverse_observation_induced_probability_field_interference.PyQuantumSlam(10.0)
+    geometry = LANDMARK_GEOMETRIES["random"]
+    ids = [sim.add_landmark(x, y) for x, y in geometry]
+    sim.update_observation(0.3, 0.7)
+
+    # 少し動いたカメラからの計測を順序を入れ替えて与え、遠い反射を1つ混ぜる
+    cam = (0.32, 0.68)
+    order = [3, 0, 5, 1, 4, 2]
+    ranges = [math.hypot(geometry[i][0] - cam[0], geometry[i][1] - cam[1]) for i in order] + [40.0]
+
+    result = sim.associate(ranges)
+    assert result[:-1] == [ids[i] for i in order]
+    assert result[-1] is None
+
+
+def test_trajectory_recording_and_csv_export(tmp_path):
+    """
+    record_pose で推定位置が時刻付きで蓄積され、replay 可能な CSV に書き出されることを確認
+    """
+    sim = inverse_observation_induced_probability_field_interference.PyQuantumSlam(10.0)
+    for x, y in LANDMARK_GEOMETRIES["triangular"]:
+        sim.add_landmark(x, y)
+
+    path = [(0.0, 0.0), (0.5, 0.2), (1.0, 0.5)]
+    for step, cam in enumerate(path):
+        sim.time = 0.1 * step
+        sim.update_observation(*cam)
+        sim.record_pose()
+
+    trajectory = sim.trajectory()
+    assert [t for t, _, _ in trajectory] == pytest.approx([0.0, 0.1, 0.2])
+    for (_, (x, y), _), cam in zip(trajectory, path):
+        assert math.hypot(x - cam[0], y - cam[1]) < 0.05
+
+    csv_path = tmp_path / "trajectory.csv"
+    sim.write_trajectory_csv(str(csv_path))
+    rows = csv_path.read_text().splitlines()
+    assert rows[0] == "time,cam_x,cam_y"
+    assert len(rows) == 1 + len(path)
+
+
+def test_agents_share_landmarks_with_separate_observations():
+    """
+    共有ランドマークに対し、各エージェントの場がそれぞれの真の位置でピークを持つことを確認
+    """
+    sim = inverse_observation_induced_probability_field_interference.PyQuantumSlam(10.0)
+    for x, y in LANDMARK_GEOMETRIES["triangular"]:
+        sim.add_landmark(x, y)
+
+    positions = {1: (0.0, 0.0), 2: (2.0, -1.0)}
+    for agent_id, cam in positions.items():
+        sim.observe_for_agent(agent_id, *cam)
+
+    reference = inverse_observation_induced_probability_field_interference.PyQuantumSlam(10.0)
+    for x, y in LANDMARK_GEOMETRIES["triangular"]:
+        reference.add_landmark(x, y)
+    for agent_id, cam in positions.items():
+        reference.update_observation(*cam)
+        for x, y in positions.values():
+            assert sim.probability_at_for_agent(agent_id, x, y) == pytest.approx(reference.get_probability(x, y), rel=1e-5)
+
+    assert sim.probability_at_for_agent(3, 0.0, 0.0) == 0.0
+
+
+def test_amplitude_at_keeps_phase():
+    """
+    amplitude_at の |psi|^2 が get_probability と一致し、位相が場所によって変わることを確認
+    """
+    sim = inverse_observation_induced_probability_field_interference.PyQuantumSlam(10.0)
+    for x, y in LANDMARK_GEOMETRIES["triangular"]:
+        sim.add_landmark(x, y)
+    sim.update_observation(0.3, 0.7)
+
+    phases = []
+    for x, y in [(0.3, 0.7), (0.3, 0.8), (0.3, 0.6)]:
+        re, im = sim.amplitude_at(x, y)
+        assert re * re + im * im == pytest.approx(sim.get_probability(x, y), rel=1e-5)
+        phases.append(math.atan2(im, re))
+
+    # 真の位置では全ての残差が 0 なので位相も 0
+    assert phases[0] == pytest.approx(0.0, abs=1e-6)
+    # 真の位置の上下で位相の符号が反転する (|psi|^2 だけでは区別できない)
+    assert phases[1] > 0.1 and phases[2] < -0.1
+
+
+def test_normalized_grid_and_mass_in_rect():
+    """
+    正規化グリッドの和が 1 になり、真の位置周辺に確率質量が集中することを確認
+    """
+    sim = inverse_observation_induced_probability_field_interference.PyQuantumSlam(10.0)
+    for x, y in LANDMARK_GEOMETRIES["triangular"]:
+        sim.add_landmark(x, y)
+    sim.update_observation(0.3, 0.7)
+
+    bounds = (-5.0, 5.0, -5.0, 5.0)
+    grid = sim.normalized_grid(bounds, 64, 64)
+    assert sum(grid) == pytest.approx(1.0, rel=1e-9)
+    assert sim.mass_in_rect(bounds, 64, 64, bounds) == pytest.approx(1.0, rel=1e-9)
+
+    near = sim.mass_in_rect(bounds, 64, 64, (-0.7, 1.3, -0.3, 1.7))
+    far = sim.mass_in_rect(bounds, 64, 64, (2.0, 4.0, -4.0, -2.0))
+    assert near > 2.0 * far
+
+
+def test_entropy_and_concentration_track_localization():
+    """
+    減衰が強く場が絞られるほど、エントロピーが下がり集中度が上がることを確認
+    """
+    module = inverse_observation_induced_probability_field_interference
+    bounds = (-5.0, 5.0, -5.0, 5.0)
+
+    metrics = []
+    for decay_factor in (0.5, 2.0, 8.0):
+        sim = module.PyQuantumSlam(10.0, decay_factor)
+        for x, y in LANDMARK_GEOMETRIES["triangular"]:
+            sim.add_landmark(x, y)
+        sim.update_observation(0.3, 0.7)
+        metrics.append((sim.field_entropy(bounds, 64), sim.field_concentration(bounds, 64)))
+
+    entropies = [h for h, _ in metrics]
+    concentrations = [c for _, c in metrics]
+    assert entropies == sorted(entropies, reverse=True)
+    assert concentrations == sorted(concentrations)
+    assert all(1.0 / 64 ** 2 <= c <= 1.0 for c in concentrations)
+
+
+def test_compare_detects_drift():
+    """
+    同一の場は差 0、カメラ位置がずれた場は KL・L2・ピーク位置の差として検出されることを確認
+    """
+    module = inverse_observation_induced_probability_field_interference
+    bounds = (-5.0, 5.0, -5.0, 5.0)
+
+    def session(cam):
+        sim = module.PyQuantumSlam(10.0)
+        for x, y in LANDMARK_GEOMETRIES["triangular"]:
+            sim.add_landmark(x, y)
+        sim.update_observation(*cam)
+        return sim
+
+    base = session((0.3, 0.7))
+    kl, l2, correlation, peak_offset = base.compare(session((0.3, 0.7)), bounds, 64)
+    assert kl == pytest.approx(0.0, abs=1e-12)
+    assert l2 == pytest.approx(0.0, abs=1e-12)
+    assert correlation == pytest.approx(1.0)
+    assert peak_offset == 0.0
+
+    kl, l2, correlation, peak_offset = base.compare(session((1.3, -0.3)), bounds, 64)
+    assert kl > 0.1 and l2 > 0.0
+    assert correlation < 0.9
+    assert peak_offset == pytest.approx(math.hypot(1.0, 1.0), abs=0.25)
+
+
+def test_step_decoheres_stale_landmarks():
+    """
+    再観測されないランドマークは step で薄れ、毎回観測していれば場が保たれることを確認
+    """
+    module = inverse_observation_induced_probability_field_interference
+    true_pos = (0.3, 0.7)
+
+    def run(reobserve):
+        sim = module.PyQuantumSlam(10.0)
+        for x, y in LANDMARK_GEOMETRIES["triangular"]:
+            sim.add_landmark(x, y)
+        sim.set_decoherence(1.0, 0.5)
+        sim.update_observation(*true_pos)
+        for _ in range(20):
+            sim.step(0.1)
+            if reobserve:
+                sim.update_observation(*true_pos)
+        return sim.get_probability(*true_pos)
+
+    # 全ランドマークが揃って観測された直後の |psi|^2 = 3^2
+    assert run(reobserve=True) == pytest.approx(9.0, rel=1e-5)
+    assert run(reobserve=False) < 0.5 * 9.0 * math.exp(-2.0 * 1.9)
+
+
+def test_landmark_wave_number_override():
+    """
+    ランドマーク固有の波数は真の位置のピークを保ったまま干渉縞を変え、None で既定値に戻ることを確認
+    """
+    sim = inverse_observation_induced_probability_field_interference.PyQuantumSlam(10.0)
+    ids = [sim.add_landmark(x, y) for x, y in LANDMARK_GEOMETRIES["triangular"]]
+    true_pos, off = (0.3, 0.7), (0.9, 0.2)
+    sim.update_observation(*true_pos)
+    baseline = sim.get_probability(*off)
+
+    sim.set_landmark_wave_number(ids[0], 23.0)
+    assert sim.get_probability(*true_pos) == pytest.approx(9.0, rel=1e-5)
+    assert sim.get_probability(*off) != pytest.approx(baseline, rel=1e-3)
+
+    sim.set_landmark_wave_number(ids[0])
+    assert sim.get_probability(*off) == pytest.approx(baseline, rel=1e-6)
+
+    with pytest.raises(ValueError):
+        sim.set_landmark_wave_number(ids[0], -1.0)
+
+
+def test_occupancy_grid_export():
+    """
+    占有グリッドのメタデータと、真の位置のセルが占有 (100) になることを確認
+    """
+    sim = inverse_observation_induced_probability_field_interference.PyQuantumSlam(10.0)
+    for x, y in LANDMARK_GEOMETRIES["triangular"]:
+        sim.add_landmark(x, y)
+    sim.update_observation(0.3, 0.7)
+
+    origin, cell_size, width, height, data = sim.to_occupancy_grid((-4.0, 4.0, -2.0, 2.0), 40)
+    assert origin == pytest.approx([-4.0, -2.0])
+    assert cell_size == pytest.approx([0.2, 0.1])
+    assert (width, height) == (40, 40)
+    assert len(data) == width * height and set(data) <= {0, 100}
+
+    ix = int((0.3 - origin[0]) / cell_size[0])
+    iy = int((0.7 - origin[1]) / cell_size[1])
+    assert data[iy * width + ix] == 100
+    assert data.count(100) < len(data) // 2
+
+
+def test_simulated_scan_closes_the_loop():
+    """
+    シミュレートしたスキャンを観測として戻すと、真の位置付近に場のピークが出ることを確認
+    """
+    sim = inverse_observation_induced_probability_field_interference.PyQuantumSlam(10.0)
+    for x, y in LANDMARK_GEOMETRIES["triangular"]:
+        sim.add_landmark(x, y)
+    sim.landmark_radius = 0.2
+
+    cam = (0.3, 0.7)
+    ranges = sim.simulate_scan(cam[0], cam[1], 0.0, 720, 20.0)
+    assert len(ranges) == 720
+    assert min(ranges) < 20.0
+    assert all(0.0 <= r <= 20.0 for r in ranges)
+
+    sim.observe_scan(cam[0], cam[1], 0.0, ranges, 20.0)
+    sim_ref = inverse_observation_induced_probability_field_interference.PyQuantumSlam(10.0)
+    for x, y in LANDMARK_GEOMETRIES["triangular"]:
+        sim_ref.add_landmark(x, y)
+    sim_ref.update_observation(*cam)
+
+    # 観測距離の誤差は角度分解能の分だけ: 真の位置での確率は正確な観測とほぼ同じ
+    assert sim.get_probability(*cam) > 0.9 * sim_ref.get_probability(*cam)
+
+
+def test_merge_maps_with_transform():
+    """
+    別座標系の地図を変換して取り込み、重複するランドマークが融合されることを確認
+    """
+    module = inverse_observation_induced_probability_field_interference
+    theta, offset = math.pi / 2, (1.0, -2.0)
+    c, s = math.cos(theta), math.sin(theta)
+
+    map_a = module.PyQuantumSlam(10.0)
+    map_b = module.PyQuantumSlam(10.0)
+    for x, y in LANDMARK_GEOMETRIES["triangular"]:
+        map_a.add_landmark(x, y)
+        # B の座標系での位置 (逆変換) に少し誤差を乗せる
+        dx, dy = x - offset[0] + 0.05, y - offset[1]
+        map_b.add_landmark(c * dx + s * dy, -s * dx + c * dy)
+    map_b.add_landmark(3.0, 3.0)
+
+    assert map_a.merge(map_b, offset[0], offset[1], theta, 0.5) == 3
+    # 3 + 1 (取り込まれた新規ランドマーク) の次のID
+    assert map_a.add_landmark(0.0, 0.0) == 5
+
+
+def test_prune_enforces_budget():
+    """
+    信頼度の低いランドマークが削除され、残りが上限数の強い順に保たれることを確認
+    """
+    sim = inverse_observation_induced_probability_field_interference.PyQuantumSlam(10.0)
+    ids = [sim.add_landmark(float(i), 0.0) for i in range(6)]
+    sim.decay_all(0.5)
+    for i, landmark_id in enumerate(ids):
+        sim.reinforce(landmark_id, 0.5 * i - 1.0)
+
+    # 最初の2つは信頼度 0.5 未満に下がっている
+    assert sim.prune(0.5) == 2
+    assert sim.prune(0.0, 2) == 2
+    # 残ったのは最も強い2つ
+    for landmark_id in ids[4:]:
+        sim.set_observation(landmark_id, 1.0)
+    for landmark_id in ids[:4]:
+        with pytest.raises(ValueError):
+            sim.set_observation(landmark_id, 1.0)
+
+
+def test_observe_one_updates_single_landmark():
+    """
+    observe_one を全ランドマークに適用すると update_observation と同じ場になり、
+    一部だけ更新した場合は異なる場になることを確認
+    """
+    module = inverse_observation_induced_probability_field_interference
+    incremental = module.PyQuantumSlam(10.0)
+    full = module.PyQuantumSlam(10.0)
+    ids = [incremental.add_landmark(x, y) for x, y in LANDMARK_GEOMETRIES["triangular"]]
+    for x, y in LANDMARK_GEOMETRIES["triangular"]:
+        full.add_landmark(x, y)
+
+    incremental.update_observation(-1.0, -1.0)
+    full.update_observation(0.3, 0.7)
+
+    incremental.observe_one(ids[0], 0.3, 0.7)
+    assert incremental.get_probability(0.3, 0.7) < 0.9 * full.get_probability(0.3, 0.7)
+
+    for landmark_id in ids[1:]:
+        incremental.observe_one(landmark_id, 0.3, 0.7)
+    assert incremental.get_probability(0.3, 0.7) == pytest.approx(full.get_probability(0.3, 0.7))
+
+    with pytest.raises(ValueError):
+        incremental.observe_one(999, 0.0, 0.0)
+
+
+def test_propagate_moves_landmarks():
+    """
+    速度を持つランドマークを propagate で進めると、移動先に置いた地図と同じ場になることを確認
+    """
+    module = inverse_observation_induced_probability_field_interference
+    moving = module.PyQuantumSlam(10.0)
+    moved = module.PyQuantumSlam(10.0)
+    ids = [moving.add_landmark(x, y) for x, y in LANDMARK_GEOMETRIES["triangular"]]
+    moving.set_landmark_velocity(ids[0], 0.5, -0.25)
+    for x, y in [(1.0, 9.5), (-10.0, -10.0), (10.0, -10.0)]:
+        moved.add_landmark(x, y)
+
+    moving.propagate(2.0)
+    for sim in (moving, moved):
+        sim.update_observation(0.3, 0.7)
+
+    grid_moving = moving.probability_grid(-2.0, 2.0, -2.0, 2.0, 16, 16)
+    grid_moved = moved.probability_grid(-2.0, 2.0, -2.0, 2.0, 16, 16)
+    assert grid_moving == pytest.approx(grid_moved, rel=1e-4)
+
+
+def test_f64_path_matches_f32_at_moderate_wave_number():
+    """
+    f64 の評価が通常の波数では f32 と一致し、大きな波数でも真の位置で全ランドマークが同位相になることを確認
+    """
+    module = inverse_observation_induced_probability_field_interference
+    for k in (10.0, 1e5):
+        sim = module.PyQuantumSlam(k)
+        for x, y in LANDMARK_GEOMETRIES["triangular"]:
+            sim.add_landmark(x, y)
+        sim.update_observation(0.3, 0.7)
+
+        if k == 10.0:
+            for x, y in [(0.0, 0.0), (0.35, 0.6), (-1.2, 2.5)]:
+                assert sim.get_probability_f64(x, y) == pytest.approx(sim.get_probability(x, y), rel=1e-4, abs=1e-6)
+        assert sim.get_probability_f64(0.3, 0.7) == pytest.approx(9.0, rel=1e-2)
+
+
+def test_vectorized_landmark_loop_matches_f64_path():
+    """
+    8 の倍数でないランドマーク数でも、SIMD 化した格子評価が f64 の評価と一致することを確認
+    """
+    module = inverse_observation_induced_probability_field_interference
+    sim = module.PyQuantumSlam(10.0)
+    for i in range(37):
+        a = i * 0.37
+        sim.add_landmark(4.0 * math.cos(a), 4.0 * math.sin(1.3 * a))
+    sim.update_observation(0.3, -0.2)
+
+    n = 8
+    grid = sim.probability_grid(-4.0, 4.0, -4.0, 4.0, n, n)
+    for iy in range(n):
+        for ix in range(n):
+            x = -4.0 + (ix + 0.5) * 8.0 / n
+            y = -4.0 + (iy + 0.5) * 8.0 / n
+            assert grid[iy * n + ix] == pytest.approx(sim.get_probability_f64(x, y), rel=1e-3, abs=1e-4)
+
+
+def test_indexed_grid_matches_full_grid():
+    """
+    空間インデックスで遠いランドマークを省いても、振幅の差が省いた数 * epsilon に収まることを確認
+    """
+    module = inverse_observation_induced_probability_field_interference
+    sim = module.PyQuantumSlam(10.0)
+    for i in range(60):
+        a = i * 0.37
+        sim.add_landmark(20.0 * math.cos(0.71 * a), 20.0 * math.sin(1.3 * a))
+    sim.decay_factor = 4.0
+    sim.update_observation(0.3, -0.2)
+
+    epsilon = 1e-4
+    full = sim.probability_grid(-5.0, 5.0, -5.0, 5.0, 40, 40)
+    culled = sim.probability_grid_indexed((-5.0, 5.0, -5.0, 5.0), 40, 40, 0.5, epsilon)
+    for a, b in zip(full, culled):
+        assert abs(math.sqrt(a) - math.sqrt(b)) <= 60 * epsilon + 1e-3
+
+    with pytest.raises(ValueError):
+        sim.probability_grid_indexed((-5.0, 5.0, -5.0, 5.0), 4, 4, 0.0, epsilon)
+
+
+def test_tiled_grid_streams_and_cancels():
+    """
+    タイルごとの評価を組み立てると probability_grid と一致し、callback が False を返すと打ち切られることを確認
+    """
+    module = inverse_observation_induced_probability_field_interference
+    sim = module.PyQuantumSlam(10.0)
+    for x, y in LANDMARK_GEOMETRIES["triangular"]:
+        sim.add_landmark(x, y)
+    sim.update_observation(0.3, 0.7)
+
+    nx, ny = 23, 17
+    full = sim.probability_grid(-2.0, 2.0, -1.0, 3.0, nx, ny)
+    assembled = [None] * (nx * ny)
+    progress = []
+
+    def collect(origin, tile_nx, tile_ny, values, fraction):
+        for j in range(tile_ny):
+            for i in range(tile_nx):
+                assembled[(origin[1] + j) * nx + origin[0] + i] = values[j * tile_nx + i]
+        progress.append(fraction)
+
+    sim.probability_grid_tiled((-2.0, 2.0, -1.0, 3.0), nx, ny, 8, collect)
+    assert len(progress) == 9 and progress[-1] == pytest.approx(1.0)
+    assert assembled == pytest.approx(full, rel=1e-4, abs=1e-9)
+
+    seen = []
+    sim.probability_grid_tiled((-2.0, 2.0, -1.0, 3.0), nx, ny, 8, lambda *tile: seen.append(tile) or len(seen) < 2)
+    assert len(seen) == 2
+
+
+def test_gpu_field_evaluator_matches_cpu_grid():
+    """
+    GPU の格子評価が CPU の probability_grid と一致することを確認 (GPU が無い環境ではスキップ)
+    """
+    module = inverse_observation_induced_probability_field_interference
+    try:
+        gpu = module.PyGpuFieldEvaluator()
+    except ValueError as e:
+        pytest.skip(f"no GPU adapter: {e}")
+    sim = module.PyQuantumSlam(10.0)
+    for x, y in LANDMARK_GEOMETRIES["triangular"]:
+        sim.add_landmark(x, y)
+    sim.update_observation(0.3, 0.7)
+
+    cpu = sim.probability_grid(-2.0, 2.0, -1.0, 3.0, 32, 32)
+    assert gpu.probability_grid(sim, (-2.0, 2.0, -1.0, 3.0), 32, 32) == pytest.approx(cpu, rel=1e-3, abs=1e-3)
+
+
+def test_non_finite_positions_are_rejected():
+    """
+    NaN / 無限大の座標を黙って受け入れず、ValueError で拒否することを確認
+    """
+    module = inverse_observation_induced_probability_field_interference
+    sim = module.PyQuantumSlam(10.0)
+    landmark_id = sim.add_landmark(0.0, 4.0)
+
+    with pytest.raises(ValueError):
+        sim.add_landmark(float("nan"), 1.0)
+    with pytest.raises(ValueError):
+        sim.update_observation(0.3, float("inf"))
+    with pytest.raises(ValueError):
+        sim.observe_one(landmark_id, float("nan"), 0.0)
+    with pytest.raises(ValueError):
+        sim.observe_with_velocity(0.0, 0.0, float("nan"), 0.0)
+    with pytest.raises(ValueError):
+        sim.set_observation(landmark_id, -1.0)
+    with pytest.raises(ValueError):
+        sim.set_observation(landmark_id, float("nan"))
+    with pytest.raises(ValueError):
+        module.PyQuantumSlam(-10.0)
+    with pytest.raises(ValueError):
+        sim.observe_for_agent(1, float("nan"), 0.0)
+    with pytest.raises(ValueError):
+        sim.observe_bearing(0.0, 0.0, float("inf"))
+    with pytest.raises(ValueError):
+        sim.observe_scan(0.0, 0.0, 0.0, [1.0, float("nan")], 20.0)
+    for dt in (-0.1, float("nan")):
+        with pytest.raises(ValueError):
+            sim.step(dt)
+        with pytest.raises(ValueError):
+            sim.advance_time(dt)
+        with pytest.raises(ValueError):
+            sim.propagate(dt)
+
+    # 拒否された呼び出しは状態を変えない
+    sim.update_observation(0.0, 0.0)
+    assert sim.get_probability(0.0, 0.0) == pytest.approx(1.0)
+
+
+def test_property_setters_validate_values():
+    """
+    プロパティのセッターが不正な値を ValueError で拒否し、値を変えないことを確認
+    """
+    module = inverse_observation_induced_probability_field_interference
+    sim = module.PyQuantumSlam(10.0)
+
+    for name, bad in (
+        ("wave_number", 0.0),
+        ("wave_number", float("nan")),
+        ("bearing_wave_number", -1.0),
+        ("decay_factor", -1.0),
+        ("decay_factor", float("inf")),
+        ("time", float("nan")),
+        ("time", -0.5),
+        ("landmark_radius", -0.1),
+        ("landmark_radius", float("nan")),
+    ):
+        before = getattr(sim, name)
+        with pytest.raises(ValueError):
+            setattr(sim, name, bad)
+        assert getattr(sim, name) == before
+
+    with pytest.raises(ValueError):
+        sim.set_decoherence(-1.0, 0.0)
+
+    sim.time = 2.5
+    sim.landmark_radius = 0.3
+    assert sim.time == 2.5
+    assert sim.landmark_radius == pytest.approx(0.3)
+
+
+def test_reset_observations_marks_landmarks_unobserved():
+    """
+    reset_observations が観測距離だけでなく観測フラグも消し、古い観測が場に残らないことを確認
+    """
+    module = inverse_observation_induced_probability_field_interference
+    sim = module.PyQuantumSlam(10.0)
+    ids = [sim.add_landmark(x, y) for x, y in LANDMARK_GEOMETRIES["triangular"]]
+    sim.update_observation(0.3, 0.7)
+    assert sim.get_probability(0.3, 0.7) > 0.0
+
+    sim.reset_observations()
+    for x, y in ((0.3, 0.7), (0.0, 0.0), (1.0, -1.0)):
+        assert sim.get_probability(x, y) == 0.0
+
+    # 再観測したランドマークだけが場に寄与する
+    sim.observe_one(ids[0], 0.3, 0.7)
+    assert sim.get_probability(0.3, 0.7) == pytest.approx(1.0)
+
+
+def test_save_npy_defaults_to_auto_bounds(tmp_path):
+    """
+    bounds を省略した save_npy が auto_bounds (10% の余白) 上の格子を書き出すことを確認
+    """
+    module = inverse_observation_induced_probability_field_interference
+    sim = module.PyQuantumSlam(10.0)
+    for x, y in LANDMARK_GEOMETRIES["triangular"]:
+        sim.add_landmark(x, y)
+    sim.update_observation(0.3, 0.7)
+
+    path = tmp_path / "field.npy"
+    sim.save_npy(str(path), 12, 8)
+    data = path.read_bytes()
+    header_len = int.from_bytes(data[8:10], "little")
+    assert data[:6] == b"\x93NUMPY"
+    assert b"'shape': (8, 12)" in data[10:10 + header_len]
+
+    values = struct.unpack("<96d", data[10 + header_len:])
+    x_min, x_max, y_min, y_max = sim.auto_bounds(0.1)
+    assert list(values) == pytest.approx(sim.probability_grid(x_min, x_max, y_min, y_max, 12, 8))
+
+    with pytest.raises(ValueError):
+        module.PyQuantumSlam(10.0).save_npy(str(path), 4, 4)
+
+
 if __name__ == "__main__":
     test_constructive_interference()
     test_interference_resolution()
@@ -0,0 +1,38 @@
+//! Validation done by `UniformsBuilder::build`.
+
+use inverse_observation_induced_probability_field_interference::{Error, FeedbackMode, Uniforms};
+
+#[test]
+fn builder_fills_the_uniform_block() {
+    let uniforms = Uniforms::builder()
+        .resolution(64, 48)
+        .time(1.5)
+        .wave_number(40.0)
+        .feedback_strength(0.5)
+        .feedback_mode(FeedbackMode::Confidence)
+        .num_landmarks(3)
+        .camera_pos([0.2, -0.1])
+        .viewport((-2.0, 2.0, -1.0, 1.0))
+        .build()
+        .unwrap();
+
+    assert_eq!(uniforms.resolution, [64.0, 48.0]);
+    assert_eq!((uniforms.time, uniforms.wave_number, uniforms.feedback_strength), (1.5, 40.0, 0.5));
+    assert_eq!(uniforms.feedback_mode, FeedbackMode::Confidence as u32);
+    assert_eq!(uniforms.num_landmarks, 3);
+    assert_eq!((uniforms.view_min, uniforms.view_max), ([-2.0, -1.0], [2.0, 1.0]));
+    // 既定値は元のレンダラーの定数
+    assert_eq!(uniforms.decay_factor, 5.0);
+}
+
+#[test]
+fn builder_rejects_inconsistent_parameters() {
+    assert!(matches!(Uniforms::builder().resolution(0, 48).build(), Err(Error::InvalidParameter(_))));
+    assert!(matches!(Uniforms::builder().viewport((1.0, -1.0, -1.0, 1.0)).build(), Err(Error::InvalidParameter(_))));
+    assert!(Uniforms::builder().feedback_strength(1.5).build().is_err());
+    assert!(Uniforms::builder().feedback_strength(f32::NAN).build().is_err());
+
+    let over = Uniforms::builder().num_landmarks(5).landmark_capacity(4).build();
+    assert!(matches!(over, Err(Error::CapacityExceeded { requested: 5, capacity: 4 })));
+    assert!(Uniforms::builder().num_landmarks(4).landmark_capacity(4).build().is_ok());
+}